@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use ciborium::{de::from_reader, value::Value};
+
+use crate::corpus;
+use crate::output::sanitize;
+use eudcc_core::eudcc::{
+    algorithm_name, certificate_unknown_fields, decode_cose, hex, CoseSign1,
+    CLAIM_KEY_DCCV1, CLAIM_KEY_HCERT, CLAIM_KEY_ISSUER, COSE_HEADER_ALG,
+    COSE_HEADER_KID,
+};
+
+const KNOWN_CLAIMS: &[i128] = &[1, 4, 6, -260];
+const UNKNOWN_ISSUER: &str = "unknown";
+// Algorithms allowed by the EUDCC technical specifications.
+const EXPECTED_ALGORITHMS: &[&str] = &["ES256", "PS256"];
+
+/// Quirks seen in the certificates of a single issuer.
+#[derive(Debug, Default, PartialEq)]
+pub struct IssuerStats {
+    pub certificates: usize,
+    pub claim_orders: BTreeMap<String, usize>,
+    pub schema_versions: BTreeMap<String, usize>,
+    pub algorithms: BTreeMap<String, usize>,
+    pub unknown_fields: BTreeMap<String, usize>,
+}
+
+/// Per-issuer statistics over a corpus of certificates.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub issuers: BTreeMap<String, IssuerStats>,
+    /// Signature algorithms used by each key identifier.
+    pub keys: BTreeMap<String, BTreeMap<String, usize>>,
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+fn integer(value: &Value) -> Option<i128> {
+    value.as_integer().map(i128::from)
+}
+
+fn map_get(map: &[(Value, Value)], key: i128) -> Option<&Value> {
+    map.iter()
+        .find(|(k, _)| integer(k) == Some(key))
+        .map(|(_, v)| v)
+}
+
+fn key_name(key: &Value) -> String {
+    match (key.as_text(), integer(key)) {
+        (Some(text), _) => sanitize(text),
+        (_, Some(i)) => i.to_string(),
+        _ => format!("{:?}", key),
+    }
+}
+
+fn algorithm(cose: &CoseSign1) -> String {
+    let alg = cose.header(COSE_HEADER_ALG);
+    match alg.as_ref().and_then(integer) {
+        Some(alg) => match algorithm_name(alg) {
+            Some(name) => name.to_string(),
+            None => alg.to_string(),
+        },
+        None => "missing".to_string(),
+    }
+}
+
+fn key_identifier(cose: &CoseSign1) -> String {
+    match cose.header(COSE_HEADER_KID) {
+        Some(Value::Bytes(kid)) => hex(&kid),
+        _ => "missing".to_string(),
+    }
+}
+
+impl Report {
+    /// Add a decoded certificate to the report.
+    pub fn add(&mut self, cose: &CoseSign1) {
+        let alg = algorithm(cose);
+        let key = self.keys.entry(key_identifier(cose)).or_default();
+        *key.entry(alg.clone()).or_default() += 1;
+
+        let claims = from_reader::<Value, _>(&cose.payload[..]).ok();
+        let claims = claims
+            .as_ref()
+            .and_then(Value::as_map)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let issuer = map_get(claims, CLAIM_KEY_ISSUER.into())
+            .and_then(Value::as_text)
+            .unwrap_or(UNKNOWN_ISSUER);
+        let stats = self.issuers.entry(sanitize(issuer)).or_default();
+        stats.certificates += 1;
+
+        let order: Vec<String> =
+            claims.iter().map(|(k, _)| key_name(k)).collect();
+        *stats.claim_orders.entry(order.join(", ")).or_default() += 1;
+
+        *stats.algorithms.entry(alg).or_default() += 1;
+
+        let mut unknown = Vec::new();
+        for (key, _) in claims {
+            if !matches!(integer(key), Some(k) if KNOWN_CLAIMS.contains(&k)) {
+                unknown.push(format!("claim {}", key_name(key)));
+            }
+        }
+
+        let certificate = map_get(claims, CLAIM_KEY_HCERT.into())
+            .and_then(Value::as_map)
+            .and_then(|hcert| map_get(hcert, CLAIM_KEY_DCCV1 as i128));
+        let version = certificate
+            .and_then(Value::as_map)
+            .and_then(|c| c.iter().find(|(k, _)| k.as_text() == Some("ver")))
+            .and_then(|(_, v)| v.as_text())
+            .unwrap_or("missing");
+        *stats.schema_versions.entry(sanitize(version)).or_default() += 1;
+
+        if let Some(certificate) = certificate {
+            unknown.extend(certificate_unknown_fields(certificate));
+        }
+        for field in unknown {
+            *stats.unknown_fields.entry(sanitize(&field)).or_default() += 1;
+        }
+    }
+}
+
+fn write_counts(
+    f: &mut fmt::Formatter,
+    title: &str,
+    counts: &BTreeMap<String, usize>,
+) -> fmt::Result {
+    writeln!(f, "  {}:", title)?;
+    if counts.is_empty() {
+        writeln!(f, "    none")?;
+    }
+    for (value, count) in counts {
+        writeln!(f, "    {}: {}", value, count)?;
+    }
+
+    Ok(())
+}
+
+impl Report {
+    /// Key identifiers signing with an algorithm not allowed by the
+    /// specifications, or with more than one algorithm.
+    pub fn flagged_keys(&self) -> Vec<(&str, String)> {
+        let mut flagged = Vec::new();
+        for (kid, algorithms) in &self.keys {
+            for alg in algorithms.keys() {
+                if !EXPECTED_ALGORITHMS.contains(&alg.as_str()) {
+                    flagged.push((kid.as_str(), format!("unexpected {}", alg)));
+                }
+            }
+            if algorithms.len() > 1 {
+                flagged.push((kid.as_str(), "multiple algorithms".to_string()));
+            }
+        }
+
+        flagged
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (issuer, stats) in &self.issuers {
+            writeln!(f, "Issuer {}", issuer)?;
+            writeln!(f, "  certificates: {}", stats.certificates)?;
+            write_counts(f, "claim order", &stats.claim_orders)?;
+            write_counts(f, "schema versions", &stats.schema_versions)?;
+            write_counts(f, "signature algorithms", &stats.algorithms)?;
+            write_counts(f, "unknown fields", &stats.unknown_fields)?;
+        }
+        writeln!(f, "Keys")?;
+        for (kid, algorithms) in &self.keys {
+            write_counts(f, kid, algorithms)?;
+        }
+        let flagged = self.flagged_keys();
+        writeln!(f, "Flagged keys: {}", flagged.len())?;
+        for (kid, reason) in flagged {
+            writeln!(f, "  {}: {}", kid, reason)?;
+        }
+        writeln!(f, "Errors: {}", self.errors.len())?;
+        for (path, error) in &self.errors {
+            writeln!(f, "  {}: {}", path.display(), error)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Analyze every file in a directory, each one holding a single certificate.
+pub fn analyze_dir(dir: &Path) -> io::Result<Report> {
+    let mut report = Report::default();
+    for path in corpus::files(dir)? {
+        let cose = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| decode_cose(data).map_err(|e| e.to_string()));
+        match cose {
+            Ok(cose) => report.add(&cose),
+            Err(e) => report.errors.push((path, e)),
+        }
+    }
+
+    Ok(report)
+}
+
+#[test]
+fn analyze_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/1.json
+    // It is licensed under Apache-2.0 License.
+    let vaccination_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II5XHC9B5G2+$N IOP-IA%NFQGRJPC%OQHIZC4.OI1RM8ZA.A5:S9MKN4NN3F85QNCY0O%0VZ001HOC9JU0D0HT0HB2PL/IB*09B9LW4T*8+DCMH0LDK2%K:XFE70*LP$V25$0Q:J:4MO1P0%0L0HD+9E/HY+4J6TH48S%4K.GJ2PT3QY:GQ3TE2I+-CPHN6D7LLK*2HG%89UV-0LZ 2ZJJ524-LH/CJTK96L6SR9MU9DHGZ%P WUQRENS431T1XCNCF+47AY0-IFO0500TGPN8F5G.41Q2E4T8ALW.INSV$ 07UV5SR+BNQHNML7 /KD3TU 4V*CAT3ZGLQMI/XI%ZJNSBBXK2:UG%UJMI:TU+MMPZ5$/PMX19UE:-PSR3/$NU44CBE6DQ3D7B0FBOFX0DV2DGMB$YPF62I$60/F$Z2I6IFX21XNI-LM%3/DF/U6Z9FEOJVRLVW6K$UG+BKK57:1+D10%4K83F+1VWD1NE";
+
+    let cose = decode_cose(vaccination_data.to_string()).unwrap();
+    let mut report = Report::default();
+    report.add(&cose);
+    report.add(&cose);
+
+    let count =
+        |value: &str| vec![(value.to_string(), 2)].into_iter().collect();
+    let expected = IssuerStats {
+        certificates: 2,
+        claim_orders: count("4, 6, 1, -260"),
+        schema_versions: count("1.0.0"),
+        algorithms: count("ES256"),
+        unknown_fields: BTreeMap::new(),
+    };
+    assert_eq!(report.issuers.len(), 1);
+    assert_eq!(report.issuers["IT"], expected);
+    assert_eq!(report.keys["39301768cdda0513"], count("ES256"));
+    assert!(report.flagged_keys().is_empty());
+
+    report
+        .keys
+        .get_mut("39301768cdda0513")
+        .unwrap()
+        .insert("ES384".into(), 1);
+    assert_eq!(
+        report.flagged_keys(),
+        vec![
+            ("39301768cdda0513", "unexpected ES384".to_string()),
+            ("39301768cdda0513", "multiple algorithms".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,88 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+const RESET: &str = "\x1b[0m";
+
+/// How a piece of output is highlighted.
+///
+/// Statuses are only used by the validity view.
+#[cfg_attr(not(feature = "validity"), allow(dead_code))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Style {
+    Heading,
+    Field,
+    Good,
+    Warning,
+    Bad,
+}
+
+impl Style {
+    fn code(self) -> &'static str {
+        match self {
+            Style::Heading => "\x1b[1m",
+            Style::Field => "\x1b[36m",
+            Style::Good => "\x1b[32m",
+            Style::Warning => "\x1b[33m",
+            Style::Bad => "\x1b[31m",
+        }
+    }
+
+    /// Icon annotating a status.
+    #[cfg_attr(not(feature = "validity"), allow(dead_code))]
+    pub fn icon(self) -> &'static str {
+        match self {
+            Style::Good => "✔",
+            Style::Warning => "!",
+            Style::Bad => "✘",
+            Style::Heading | Style::Field => "",
+        }
+    }
+}
+
+/// Colors of the output, or the lack of them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Palette {
+    enabled: bool,
+}
+
+impl Palette {
+    pub fn new(enabled: bool) -> Self {
+        Palette { enabled }
+    }
+
+    #[cfg_attr(not(feature = "validity"), allow(dead_code))]
+    pub fn enabled(self) -> bool {
+        self.enabled
+    }
+
+    pub fn paint(self, style: Style, text: &str) -> String {
+        if !self.enabled {
+            return text.to_string();
+        }
+
+        format!("{}{}{}", style.code(), text, RESET)
+    }
+}
+
+/// Whether stdout is a terminal.
+fn stdout_is_terminal() -> bool {
+    io::stdout().is_terminal()
+}
+
+/// Colors are used on terminals, unless NO_COLOR is set or the terminal
+/// is a dumb one.
+pub fn detect() -> bool {
+    let no_color = matches!(env::var_os("NO_COLOR"), Some(v) if !v.is_empty());
+    let dumb = matches!(env::var_os("TERM"), Some(t) if t == "dumb");
+
+    !no_color && !dumb && stdout_is_terminal()
+}
+
+#[test]
+fn paint_test() {
+    assert_eq!(Palette::new(false).paint(Style::Bad, "expired"), "expired");
+    assert_eq!(
+        Palette::new(true).paint(Style::Good, "valid"),
+        "\x1b[32mvalid\x1b[0m"
+    );
+}
@@ -0,0 +1,17 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The files in a directory, sorted by path.
+pub fn files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    Ok(paths)
+}
@@ -0,0 +1,191 @@
+//! Comparison of decoded certificates with those of a reference decoder,
+//! field by field, to find where they read the specification differently.
+
+use std::error::Error;
+use std::fmt;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use ciborium::value::Value;
+
+use crate::json;
+
+/// A field decoded differently, or only by one of the decoders.
+#[derive(Debug, PartialEq)]
+pub struct Divergence {
+    /// The path of the field, like `nam.fnt` or `v.0.dt`.
+    pub path: String,
+    /// The value decoded by eudccdec, as JSON, if it has the field.
+    pub ours: Option<String>,
+    /// The value decoded by the reference decoder, as JSON, if it has the
+    /// field.
+    pub reference: Option<String>,
+}
+
+/// The error of a certificate that the reference decoder decoded
+/// differently.
+#[derive(Debug)]
+pub struct Divergences(pub Vec<Divergence>);
+
+impl fmt::Display for Divergences {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0.len() {
+            1 => write!(f, "the reference decoder diverges at 1 field"),
+            n => write!(f, "the reference decoder diverges at {} fields", n),
+        }
+    }
+}
+
+impl Error for Divergences {}
+
+/// Run `command` through the shell, with the input on its stdin, and
+/// parse the certificate it writes to its stdout as JSON.
+pub fn reference(command: &str, input: &[u8]) -> Result<Value, Box<dyn Error>> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("can't run the reference decoder: {}", e))?;
+    // Dropped once written, so that the command sees the end of it.
+    if let Some(mut stdin) = child.stdin.take() {
+        // Commands may well exit without reading all of it.
+        let _ = stdin.write_all(input);
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let error = format!("the reference decoder failed: {}", output.status);
+        return Err(error.into());
+    }
+    let text = String::from_utf8(output.stdout)
+        .map_err(|_| "the reference decoder didn't write UTF-8")?;
+
+    let mut value = json::parse(&text)?;
+    // So that eudccdec itself can be the reference: the version of its
+    // output is no field of the certificate.
+    if let Value::Map(members) = &mut value {
+        members.retain(|(k, _)| k.as_text() != Some("schema_version"));
+    }
+
+    Ok(value)
+}
+
+fn child(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn get<'a>(entries: &'a [(Value, Value)], key: &str) -> Option<&'a Value> {
+    entries
+        .iter()
+        .find(|(k, _)| k.as_text() == Some(key))
+        .map(|(_, v)| v)
+}
+
+fn walk(
+    path: &str,
+    ours: Option<&Value>,
+    reference: Option<&Value>,
+    out: &mut Vec<Divergence>,
+) {
+    match (ours, reference) {
+        (Some(Value::Map(ours)), Some(Value::Map(reference))) => {
+            let keys = ours.iter().chain(reference.iter());
+            let mut seen: Vec<&str> = vec![];
+            for key in keys.filter_map(|(k, _)| k.as_text()) {
+                if seen.contains(&key) {
+                    continue;
+                }
+                seen.push(key);
+                let (a, b) = (get(ours, key), get(reference, key));
+                walk(&child(path, key), a, b, out);
+            }
+        }
+        (Some(Value::Array(ours)), Some(Value::Array(reference))) => {
+            for i in 0..ours.len().max(reference.len()) {
+                let (a, b) = (ours.get(i), reference.get(i));
+                walk(&child(path, &i.to_string()), a, b, out);
+            }
+        }
+        (ours, reference) => {
+            // As JSON, integers and floats of the same value are equal.
+            let ours = ours.map(|v| json::to_string(v, false));
+            let reference = reference.map(|v| json::to_string(v, false));
+            if ours != reference {
+                out.push(Divergence {
+                    path: path.to_string(),
+                    ours,
+                    reference,
+                });
+            }
+        }
+    }
+}
+
+/// The fields of our certificate and of the reference one that differ,
+/// missing ones included, as JSON values.
+pub fn compare(ours: &Value, reference: &Value) -> Vec<Divergence> {
+    let mut divergences = vec![];
+    walk("", Some(ours), Some(reference), &mut divergences);
+
+    divergences
+}
+
+#[test]
+fn compare_test() {
+    let ours = json::parse(
+        r#"{"ver":"1.0.0","nam":{"fnt":"DI<CAPRIO"},"v":[{"dn":2,"dt":"2021-04-10"}]}"#,
+    )
+    .unwrap();
+    assert_eq!(compare(&ours, &ours), []);
+
+    let reference = json::parse(
+        r#"{"nam":{"fnt":"DI<CAPRIO","gnt":"MARILU"},"ver":"1.0.0","v":[{"dn":2.0,"dt":"2021-04-11"},{}]}"#,
+    )
+    .unwrap();
+    let divergence =
+        |path: &str, ours: Option<&str>, reference: Option<&str>| Divergence {
+            path: path.to_string(),
+            ours: ours.map(str::to_string),
+            reference: reference.map(str::to_string),
+        };
+    assert_eq!(
+        compare(&ours, &reference),
+        [
+            divergence("nam.gnt", None, Some("\"MARILU\"")),
+            divergence(
+                "v.0.dt",
+                Some("\"2021-04-10\""),
+                Some("\"2021-04-11\"")
+            ),
+            divergence("v.1", None, Some("{}")),
+        ]
+    );
+    assert_eq!(
+        compare(&ours, &Value::Null),
+        [divergence(
+            "",
+            Some(&json::to_string(&ours, false)),
+            Some("null")
+        )]
+    );
+    assert_eq!(
+        Divergences(compare(&ours, &reference)).to_string(),
+        "the reference decoder diverges at 3 fields"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn reference_test() {
+    let input = br#"{"schema_version":1,"ver":"1.0.0"}"#;
+    let value = reference("cat", input).unwrap();
+    assert_eq!(value, json::parse(r#"{"ver":"1.0.0"}"#).unwrap());
+
+    assert!(reference("exit 1", b"").is_err());
+    assert!(reference("echo not json", b"").is_err());
+}
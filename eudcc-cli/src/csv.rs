@@ -0,0 +1,298 @@
+use eudcc_core::{Certificate, Payload};
+
+use crate::field::allowed;
+use crate::output::sanitize;
+
+/// The columns of every row, in order. Columns that do not apply to the
+/// type of the record are left empty. New columns are only ever appended.
+pub const COLUMNS: &[&str] = &[
+    "issuer",
+    "issued_at",
+    "expires_at",
+    "ver",
+    "fn",
+    "fnt",
+    "gn",
+    "gnt",
+    "dob",
+    "type",
+    "tg",
+    "vp",
+    "mp",
+    "ma",
+    "dn",
+    "sd",
+    "dt",
+    "fr",
+    "df",
+    "du",
+    "tt",
+    "nm",
+    "sc",
+    "dr",
+    "tr",
+    "tc",
+    "co",
+    "is",
+    "ci",
+];
+
+/// The column after the fields, holding the pseudonym of the holder, when
+/// asked for.
+pub const PSEUDONYM_COLUMN: &str = "pseudonym";
+
+/// The column after all the others, holding the path of the input, when
+/// certificates are found in several files.
+pub const PATH_COLUMN: &str = "path";
+
+/// Quote a field as RFC 4180 requires, and only when it has to.
+fn field(text: &str) -> String {
+    let text = sanitize(text);
+    if text.contains(&[',', '"', '\r', '\n'][..]) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+fn write_row(out: &mut String, fields: &[String]) {
+    let fields: Vec<String> = fields.iter().map(|f| field(f)).collect();
+    out.push_str(&fields.join(","));
+    out.push_str("\r\n");
+}
+
+/// The groups of records, one a row, and the columns of their fields.
+const GROUPS: [(&str, &[&str]); 3] = [
+    (
+        "v",
+        &["tg", "vp", "mp", "ma", "dn", "sd", "dt", "co", "is", "ci"],
+    ),
+    ("r", &["tg", "fr", "co", "is", "df", "du", "ci"]),
+    (
+        "t",
+        &[
+            "tg", "tt", "nm", "ma", "sc", "dr", "tr", "tc", "co", "is", "ci",
+        ],
+    ),
+];
+
+/// The path of the field in a column, in the rows of a group of records,
+/// if the records of the group have it.
+fn path(column: &str, group: &str) -> Option<String> {
+    match column {
+        "fn" | "fnt" | "gn" | "gnt" => Some(format!("nam.{}", column)),
+        "issuer" | "issued_at" | "expires_at" | "ver" | "dob" => {
+            Some(column.to_string())
+        }
+        column => GROUPS
+            .iter()
+            .find(|(name, columns)| *name == group && columns.contains(&column))
+            .map(|_| format!("{}.{}", group, column)),
+    }
+}
+
+/// Whether a column is written, in the rows of `group` or, without one, at
+/// all, given the `fields` allowed. The `type` of a row is written with
+/// any field of its records.
+fn written(
+    fields: Option<&[String]>,
+    column: &str,
+    group: Option<&str>,
+) -> bool {
+    let allowed = |column: &str, group: &str| matches!(path(column, group), Some(path) if allowed(fields, &path));
+    GROUPS
+        .iter()
+        .filter(|(name, _)| group.is_none() || group == Some(name))
+        .any(|(name, columns)| match column {
+            "type" => columns.iter().any(|c| allowed(c, name)),
+            column => allowed(column, name),
+        })
+}
+
+/// The header row, with the columns of the `fields` allowed only, then
+/// the pseudonym column if `pseudonym` and the path column if `path`.
+pub fn header(
+    pseudonym: bool,
+    path: bool,
+    fields: Option<&[String]>,
+) -> String {
+    let mut out = String::new();
+    let mut columns: Vec<String> = COLUMNS
+        .iter()
+        .filter(|c| written(fields, c, None))
+        .map(|c| c.to_string())
+        .collect();
+    if pseudonym {
+        columns.push(PSEUDONYM_COLUMN.to_string());
+    }
+    if path {
+        columns.push(PATH_COLUMN.to_string());
+    }
+    write_row(&mut out, &columns);
+
+    out
+}
+
+/// One row for each record of the certificate, or a single row without
+/// record columns if it has none, ending with the `pseudonym` of the
+/// holder and the `path` of its input if given. Only the columns of the `fields` allowed are written, and
+/// left empty in the rows whose records don't have them.
+pub fn rows(
+    payload: &Payload,
+    certificate: &Certificate,
+    pseudonym: Option<&str>,
+    path: Option<&str>,
+    fields: Option<&[String]>,
+) -> String {
+    let common = [
+        payload.issuer.clone(),
+        payload.issued_at.to_string(),
+        payload.expires_at.to_string(),
+        certificate.ver.clone(),
+        certificate.nam.fn_.clone(),
+        certificate.nam.fnt.clone(),
+        certificate.nam.gn.clone(),
+        certificate.nam.gnt.clone(),
+        certificate.dob.clone(),
+    ];
+    let row = |group: Option<&str>, record: &[(&str, &str)]| {
+        let mut values = common.to_vec();
+        for column in &COLUMNS[common.len()..] {
+            let value = record.iter().find(|(name, _)| name == column);
+            values.push(value.map(|(_, v)| v.to_string()).unwrap_or_default());
+        }
+        let mut row: Vec<String> = COLUMNS
+            .iter()
+            .zip(values)
+            .filter(|(column, _)| written(fields, column, None))
+            .map(|(column, value)| {
+                if written(fields, column, group) {
+                    value
+                } else {
+                    String::new()
+                }
+            })
+            .collect();
+        row.extend(pseudonym.map(str::to_string));
+        row.extend(path.map(str::to_string));
+        row
+    };
+
+    let mut out = String::new();
+    for v in &certificate.v {
+        let (dn, sd) = (v.dn.to_string(), v.sd.to_string());
+        let record = [
+            ("type", "vaccination"),
+            ("tg", v.tg.as_str()),
+            ("vp", &v.vp),
+            ("mp", &v.mp),
+            ("ma", &v.ma),
+            ("dn", &dn),
+            ("sd", &sd),
+            ("dt", &v.dt),
+            ("co", &v.co),
+            ("is", &v.is),
+            ("ci", &v.ci),
+        ];
+        write_row(&mut out, &row(Some("v"), &record));
+    }
+    for r in &certificate.r {
+        let record = [
+            ("type", "recovery"),
+            ("tg", r.tg.as_str()),
+            ("fr", &r.fr),
+            ("df", &r.df),
+            ("du", &r.du),
+            ("co", &r.co),
+            ("is", &r.is),
+            ("ci", &r.ci),
+        ];
+        write_row(&mut out, &row(Some("r"), &record));
+    }
+    for t in &certificate.t {
+        let record = [
+            ("type", "test"),
+            ("tg", t.tg.as_str()),
+            ("tt", &t.tt),
+            ("nm", &t.nm),
+            ("ma", &t.ma),
+            ("sc", &t.sc),
+            ("dr", &t.dr),
+            ("tr", &t.tr),
+            ("tc", &t.tc),
+            ("co", &t.co),
+            ("is", &t.is),
+            ("ci", &t.ci),
+        ];
+        write_row(&mut out, &row(Some("t"), &record));
+    }
+    if out.is_empty() {
+        write_row(&mut out, &row(None, &[]));
+    }
+
+    out
+}
+
+#[test]
+fn csv_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/2.json
+    // It is licensed under Apache-2.0 License.
+    let recovery_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *MEB2B2JJ59J-9BC6:X9NECX0AKQC:3DCV4*XUA2P-FHT-H4SI/J9WVHWVH+ZEOV1J$HNTICZUBOM*LP$V25$0Q:J40IA3L/*84-5%:C92JN*4CY0*%9F/8J2P4.818T+:IX3M3.96RPVD9J-OZT1-NT0 2$$0$2PZX69B9VCDHI2/T9TU1BPIJKH/T7B-S-*O/Y41FD+X49+5Z-6%.HDD8R6W1FDJGJSFJ/4Q:T0.KJTNP8EFULNC:HA0K5HKRB4TD85LOLF92GF.3O.Z8CC7-2FQYG$%21 2O*4R60NM8JI0EUGP$I/XK$M8ZQE6YB9M66P8N31I.ROSK%IA1Q2N53Q-OQ2VC6E26T11ROSNK5W-*H+MJ%0RGZVGWNURI75RBSQSHLH1JG*CMH2.-S$7VX6N*Z1881J7G.F9I+SV06F+1M*93%D";
+    let payload =
+        eudcc_core::decode_payload(recovery_data.to_string()).unwrap();
+    let mut certificate = payload.certificate().unwrap();
+
+    assert_eq!(header(false, false, None).split(',').count(), COLUMNS.len());
+    assert!(header(false, false, None)
+        .starts_with("issuer,issued_at,expires_at,ver,fn,"));
+    assert!(header(false, true, None).ends_with(",is,ci,path\r\n"));
+
+    // Only the fields allowed are written, and in their records only.
+    let fields: Vec<String> = ["nam.fnt", "dob", "v.dt", "r.fr"]
+        .map(String::from)
+        .to_vec();
+    assert_eq!(
+        header(false, false, Some(&fields)),
+        "fnt,dob,type,dt,fr\r\n"
+    );
+    assert_eq!(
+        rows(&payload, &certificate, None, None, Some(&fields)),
+        "DI<CAPRIO,1977-06-16,recovery,,2021-05-02\r\n"
+    );
+    let fields = vec!["v".to_string()];
+    assert_eq!(
+        rows(&payload, &certificate, None, None, Some(&fields)),
+        ",,,,,,,,,,\r\n"
+    );
+
+    let out = rows(&payload, &certificate, None, None, None);
+    assert_eq!(out.lines().count(), 1);
+    let fields: Vec<&str> = out.trim_end().split(',').collect();
+    assert_eq!(fields.len(), COLUMNS.len());
+    assert_eq!(fields[0], "IT");
+    assert_eq!(fields[9], "recovery");
+    assert_eq!(fields[10], "840539006");
+
+    certificate.r.clear();
+    certificate.nam.fn_ = "Di \"Caprio\", Jr.".to_string();
+    let out = rows(&payload, &certificate, None, None, None);
+    assert!(out.contains(",\"Di \"\"Caprio\"\", Jr.\","));
+    assert!(out.ends_with(",,,,\r\n"));
+    let out = rows(&payload, &certificate, None, Some("scans/a,b.txt"), None);
+    assert!(out.ends_with(",,,,\"scans/a,b.txt\"\r\n"));
+
+    // The pseudonym goes before the path, whatever the fields allowed.
+    let fields = vec!["dob".to_string()];
+    assert_eq!(header(true, true, Some(&fields)), "dob,pseudonym,path\r\n");
+    assert_eq!(
+        rows(
+            &payload,
+            &certificate,
+            Some("ab12"),
+            Some("a.txt"),
+            Some(&fields)
+        ),
+        "1977-06-16,ab12,a.txt\r\n"
+    );
+}
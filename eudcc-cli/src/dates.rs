@@ -0,0 +1,186 @@
+use eudcc_verify::date::{parse_timestamp, Date};
+
+use crate::lang::Labels;
+use crate::output::sanitize;
+use crate::zone::TimeZone;
+
+// 9999-12-31T23:59:59Z.
+const LAST_TIMESTAMP: i64 = 253_402_300_799;
+
+/// How dates and timestamps are written in the human readable view.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DateFormat {
+    /// As they are in the certificate, timestamps as seconds.
+    Raw,
+    /// ISO 8601, like `2021-04-10` and `2021-05-03T10:27:15Z`, the
+    /// default.
+    Iso,
+    /// With the name of the month, like `10 April 2021`.
+    Long,
+    /// Numeric, like `10/04/2021` or `10.04.2021`.
+    Short,
+}
+
+impl DateFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "raw" => Some(DateFormat::Raw),
+            "iso" => Some(DateFormat::Iso),
+            "long" => Some(DateFormat::Long),
+            "short" => Some(DateFormat::Short),
+            _ => None,
+        }
+    }
+}
+
+/// Writes dates and timestamps, the latter in UTC or in a time zone.
+pub struct Dates {
+    pub format: DateFormat,
+    pub zone: Option<TimeZone>,
+}
+
+impl Dates {
+    fn format_date(&self, date: Date, labels: &Labels) -> String {
+        match self.format {
+            DateFormat::Raw | DateFormat::Iso => date.to_string(),
+            DateFormat::Long => format!(
+                "{}{} {} {}",
+                date.day,
+                labels.day_suffix,
+                labels.months[usize::from(date.month - 1)],
+                date.year
+            ),
+            DateFormat::Short => format!(
+                "{:02}{s}{:02}{s}{:04}",
+                date.day,
+                date.month,
+                date.year,
+                s = labels.date_separator
+            ),
+        }
+    }
+
+    /// A date, like the date of birth. Partial dates, like a year alone,
+    /// and invalid ones are written as they are.
+    pub fn date(&self, date: &str, labels: &Labels) -> String {
+        match Date::parse(date) {
+            Ok(parsed) if self.format != DateFormat::Raw => {
+                self.format_date(parsed, labels)
+            }
+            _ => sanitize(date),
+        }
+    }
+
+    /// A Unix timestamp, like the issuing time of the certificate. Those
+    /// past the year 9999 are written as they are.
+    pub fn timestamp(&self, timestamp: u64, labels: &Labels) -> String {
+        match i64::try_from(timestamp) {
+            Ok(timestamp) if timestamp <= LAST_TIMESTAMP => {
+                self.instant(timestamp, labels)
+            }
+            _ => timestamp.to_string(),
+        }
+    }
+
+    fn instant(&self, timestamp: i64, labels: &Labels) -> String {
+        let (offset, zone) = match &self.zone {
+            Some(zone) => zone.at(timestamp),
+            None => (0, "UTC"),
+        };
+        let local = timestamp + offset;
+        let date = Date::from_timestamp(local);
+        let seconds = local.rem_euclid(24 * 60 * 60);
+        let (hour, minute) = (seconds / 3600, seconds / 60 % 60);
+
+        match self.format {
+            DateFormat::Raw => timestamp.to_string(),
+            DateFormat::Iso => {
+                let offset = match offset {
+                    0 => "Z".to_string(),
+                    _ => format!(
+                        "{}{:02}:{:02}",
+                        if offset < 0 { '-' } else { '+' },
+                        offset.abs() / 3600,
+                        offset.abs() / 60 % 60
+                    ),
+                };
+                format!(
+                    "{}T{:02}:{:02}:{:02}{}",
+                    date,
+                    hour,
+                    minute,
+                    seconds % 60,
+                    offset
+                )
+            }
+            DateFormat::Long | DateFormat::Short => format!(
+                "{} {:02}:{:02} {}",
+                self.format_date(date, labels),
+                hour,
+                minute,
+                sanitize(zone)
+            ),
+        }
+    }
+
+    /// An ISO 8601 date and time, like the sample collection time of a
+    /// test. Invalid ones are written as they are.
+    pub fn date_time(&self, date_time: &str, labels: &Labels) -> String {
+        match parse_timestamp(date_time) {
+            Ok(timestamp) if self.format != DateFormat::Raw => {
+                self.instant(timestamp, labels)
+            }
+            _ => sanitize(date_time),
+        }
+    }
+}
+
+impl Default for Dates {
+    fn default() -> Self {
+        Dates {
+            format: DateFormat::Iso,
+            zone: None,
+        }
+    }
+}
+
+#[test]
+fn dates_test() {
+    use crate::lang::Lang;
+
+    let (en, de) = (Lang::En.labels(), Lang::De.labels());
+    let mut dates = Dates::default();
+    assert_eq!(dates.format, DateFormat::Iso);
+    dates.format = DateFormat::Raw;
+    assert_eq!(dates.date("2021-04-10", en), "2021-04-10");
+    assert_eq!(dates.timestamp(1_620_037_635, en), "1620037635");
+    assert_eq!(
+        dates.date_time("2021-05-03T10:27:15Z", en),
+        "2021-05-03T10:27:15Z"
+    );
+
+    dates.format = DateFormat::Long;
+    assert_eq!(dates.date("2021-04-10", en), "10 April 2021");
+    assert_eq!(dates.date("2021-04-10", de), "10. April 2021");
+    assert_eq!(dates.date("1977", en), "1977");
+    assert_eq!(dates.date("2021-02-30", en), "2021-02-30");
+    assert_eq!(
+        dates.date_time("2021-05-03T12:27:15+02:00", en),
+        "3 May 2021 10:27 UTC"
+    );
+
+    dates.format = DateFormat::Short;
+    assert_eq!(dates.timestamp(u64::MAX, en), u64::MAX.to_string());
+    assert_eq!(dates.date("2021-04-10", en), "10/04/2021");
+    assert_eq!(dates.timestamp(1_620_037_635, de), "03.05.2021 10:27 UTC");
+
+    dates.format = DateFormat::Iso;
+    assert_eq!(dates.timestamp(1_620_037_635, en), "2021-05-03T10:27:15Z");
+    dates.zone = Some(TimeZone::from_rule("<-0330>3:30").unwrap());
+    assert_eq!(
+        dates.timestamp(1_620_037_635, en),
+        "2021-05-03T06:57:15-03:30"
+    );
+    dates.format = DateFormat::Short;
+    assert_eq!(dates.timestamp(1_620_037_635, de), "03.05.2021 06:57 -0330");
+}
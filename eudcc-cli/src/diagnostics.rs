@@ -0,0 +1,305 @@
+use std::error::Error;
+use std::fmt::Write;
+
+#[cfg(feature = "corpus")]
+use crate::crosscheck::{Divergence, Divergences};
+use crate::json;
+use eudcc_core::DecodeError;
+
+/// How diagnostics are written to stderr.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(LogFormat::Text),
+            "json" => Some(LogFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Format an error, and the chain of errors that caused it, as a single
+/// diagnostic line.
+///
+/// `input` names the input the error comes from, when there are more
+/// than stdin. Schema violations found in strict mode follow the text
+/// line, one a line, pointing at the JSON path of the offending field,
+/// and so do the fields a reference decoder decoded differently.
+pub fn format_error(
+    format: LogFormat,
+    input: Option<&str>,
+    error: &(dyn Error + 'static),
+) -> String {
+    let mut causes = Vec::new();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        causes.push(cause.to_string());
+        source = cause.source();
+    }
+    let violations = match error.downcast_ref::<DecodeError>() {
+        Some(DecodeError::Invalid(violations)) => violations.as_slice(),
+        _ => &[],
+    };
+    let divergences = divergences(format, error);
+
+    match format {
+        LogFormat::Text => {
+            let mut line = match input {
+                Some(input) => format!("Error: {}: {}", input, error),
+                None => format!("Error: {}", error),
+            };
+            for cause in causes {
+                let _ = write!(line, ": {}", cause);
+            }
+            for violation in violations {
+                let _ = write!(
+                    line,
+                    "\n  --> {}: {}",
+                    violation.path, violation.message
+                );
+            }
+            line.push_str(&divergences);
+            line
+        }
+        LogFormat::Json => {
+            let causes: Vec<String> =
+                causes.iter().map(|c| json::string(c)).collect();
+            // Decoding errors also tell which stage of the pipeline failed.
+            let stage = match error.downcast_ref::<DecodeError>() {
+                Some(error) => {
+                    format!(",\"stage\":{}", json::string(error.stage()))
+                }
+                None => String::new(),
+            };
+            let violations = match violations {
+                [] => String::new(),
+                violations => {
+                    let violations: Vec<String> = violations
+                        .iter()
+                        .map(|v| {
+                            format!(
+                                "{{\"path\":{},\"message\":{}}}",
+                                json::string(&v.path),
+                                json::string(&v.message)
+                            )
+                        })
+                        .collect();
+                    format!(",\"violations\":[{}]", violations.join(","))
+                }
+            };
+            let input = match input {
+                Some(input) => format!(",\"input\":{}", json::string(input)),
+                None => String::new(),
+            };
+            format!(
+                "{{\"level\":\"error\"{}{},\"message\":{},\"causes\":[{}]{}{}}}",
+                input,
+                stage,
+                json::string(&error.to_string()),
+                causes.join(","),
+                violations,
+                divergences
+            )
+        }
+    }
+}
+
+/// The fields a reference decoder decoded differently, if that is the
+/// error: lines following the text one, or a member of the JSON object.
+#[cfg(feature = "corpus")]
+fn divergences(format: LogFormat, error: &(dyn Error + 'static)) -> String {
+    let divergences = match error.downcast_ref::<Divergences>() {
+        Some(Divergences(divergences)) => divergences.as_slice(),
+        None => return String::new(),
+    };
+    // The whole certificate has no path of its own.
+    let path = |divergence: &Divergence| match divergence.path.as_str() {
+        "" => "certificate".to_string(),
+        path => path.to_string(),
+    };
+
+    match format {
+        LogFormat::Text => {
+            let mut lines = String::new();
+            for divergence in divergences {
+                let value = |v: &Option<String>| match v {
+                    Some(v) => v.clone(),
+                    None => "missing".to_string(),
+                };
+                let _ = write!(
+                    lines,
+                    "\n  --> {}: ours {}, reference {}",
+                    path(divergence),
+                    value(&divergence.ours),
+                    value(&divergence.reference)
+                );
+            }
+            lines
+        }
+        LogFormat::Json => {
+            // The values are JSON already, and left out if missing.
+            let member = |name: &str, v: &Option<String>| match v {
+                Some(v) => format!(",{}:{}", json::string(name), v),
+                None => String::new(),
+            };
+            let divergences: Vec<String> = divergences
+                .iter()
+                .map(|d| {
+                    format!(
+                        "{{\"path\":{}{}{}}}",
+                        json::string(&path(d)),
+                        member("ours", &d.ours),
+                        member("reference", &d.reference)
+                    )
+                })
+                .collect();
+            format!(",\"divergences\":[{}]", divergences.join(","))
+        }
+    }
+}
+
+#[cfg(not(feature = "corpus"))]
+fn divergences(_format: LogFormat, _error: &(dyn Error + 'static)) -> String {
+    String::new()
+}
+
+/// How many certificates were decoded, and how many failed to.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Summary {
+    pub decoded: usize,
+    pub failed: usize,
+    /// Whether the others were given up at the first failure.
+    pub stopped: bool,
+}
+
+impl Summary {
+    pub fn count(&mut self, decoded: bool) {
+        if decoded {
+            self.decoded += 1;
+        } else {
+            self.failed += 1;
+        }
+    }
+}
+
+/// Format the totals of a batch as a single diagnostic line.
+pub fn format_summary(format: LogFormat, summary: &Summary) -> String {
+    let total = summary.decoded + summary.failed;
+    match format {
+        LogFormat::Text => {
+            let mut line = format!(
+                "Summary: {} certificates, {} decoded, {} failed",
+                total, summary.decoded, summary.failed
+            );
+            if summary.stopped {
+                line.push_str(", stopped at the first failure");
+            }
+            line
+        }
+        LogFormat::Json => format!(
+            "{{\"level\":\"info\",\"summary\":{{\"total\":{},\"decoded\":{},\"failed\":{},\"stopped\":{}}}}}",
+            total, summary.decoded, summary.failed, summary.stopped
+        ),
+    }
+}
+
+#[test]
+fn format_error_test() {
+    use eudcc_core::Violation;
+    use std::io;
+
+    let cause = io::Error::new(
+        io::ErrorKind::InvalidData,
+        "invalid \"base45\"\ncharacter",
+    );
+    let error = DecodeError::Base45 {
+        error: cause,
+        input_len: 3,
+        offset: 0,
+    };
+
+    assert_eq!(
+        format_error(LogFormat::Text, None, &error),
+        "Error: invalid base45 data at offset 0 of 3 input bytes: invalid \"base45\"\ncharacter"
+    );
+    assert_eq!(
+        format_error(LogFormat::Json, None, &error),
+        r#"{"level":"error","stage":"base45","message":"invalid base45 data at offset 0 of 3 input bytes","causes":["invalid \"base45\"\ncharacter"]}"#
+    );
+    assert_eq!(
+        format_error(LogFormat::Json, Some("cert.txt"), &error),
+        r#"{"level":"error","input":"cert.txt","stage":"base45","message":"invalid base45 data at offset 0 of 3 input bytes","causes":["invalid \"base45\"\ncharacter"]}"#
+    );
+
+    let violation = |path: &str, message: &str| Violation {
+        path: path.to_string(),
+        message: message.to_string(),
+    };
+    let error = DecodeError::Invalid(vec![
+        violation("$.nam.fnt", "is required"),
+        violation("$.v[0].dt", "must be a date, as YYYY-MM-DD"),
+    ]);
+    assert_eq!(
+        format_error(LogFormat::Text, Some("cert.txt"), &error),
+        "Error: cert.txt: certificate has 2 schema violations
+  --> $.nam.fnt: is required
+  --> $.v[0].dt: must be a date, as YYYY-MM-DD"
+    );
+    assert_eq!(
+        format_error(LogFormat::Json, None, &error),
+        r#"{"level":"error","stage":"hcert","message":"certificate has 2 schema violations","causes":[],"violations":[{"path":"$.nam.fnt","message":"is required"},{"path":"$.v[0].dt","message":"must be a date, as YYYY-MM-DD"}]}"#
+    );
+}
+
+#[cfg(feature = "corpus")]
+#[test]
+fn format_divergences_test() {
+    let error = Divergences(vec![
+        Divergence {
+            path: "v.0.dt".to_string(),
+            ours: Some("\"2021-04-10\"".to_string()),
+            reference: Some("\"2021-04-11\"".to_string()),
+        },
+        Divergence {
+            path: "nam.gnt".to_string(),
+            ours: None,
+            reference: Some("\"MARILU\"".to_string()),
+        },
+    ]);
+    assert_eq!(
+        format_error(LogFormat::Text, Some("cert.txt"), &error),
+        "Error: cert.txt: the reference decoder diverges at 2 fields
+  --> v.0.dt: ours \"2021-04-10\", reference \"2021-04-11\"
+  --> nam.gnt: ours missing, reference \"MARILU\""
+    );
+    assert_eq!(
+        format_error(LogFormat::Json, None, &error),
+        r#"{"level":"error","message":"the reference decoder diverges at 2 fields","causes":[],"divergences":[{"path":"v.0.dt","ours":"2021-04-10","reference":"2021-04-11"},{"path":"nam.gnt","reference":"MARILU"}]}"#
+    );
+}
+
+#[test]
+fn format_summary_test() {
+    let mut summary = Summary::default();
+    summary.count(true);
+    summary.count(false);
+    summary.count(true);
+    assert_eq!(
+        format_summary(LogFormat::Text, &summary),
+        "Summary: 3 certificates, 2 decoded, 1 failed"
+    );
+    summary.stopped = true;
+    assert_eq!(
+        format_summary(LogFormat::Text, &summary),
+        "Summary: 3 certificates, 2 decoded, 1 failed, stopped at the first failure"
+    );
+    assert_eq!(
+        format_summary(LogFormat::Json, &summary),
+        r#"{"level":"info","summary":{"total":3,"decoded":2,"failed":1,"stopped":true}}"#
+    );
+}
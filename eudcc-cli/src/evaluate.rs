@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use eudcc_core::Certificate;
+use eudcc_verify::date::{Date, InvalidDate};
+use eudcc_verify::validity::{
+    recovery_window, test_freshness, vaccination_completeness, Completeness,
+    RecoveryPolicy, RecoveryWindow, TestFreshness, TestPolicy,
+    VaccinationPolicy,
+};
+
+use crate::color::{Palette, Style};
+use crate::output::sanitize;
+
+// Default policy: the limits most member states applied.
+const DAYS_AFTER_LAST_DOSE: i64 = 14;
+const NAAT_MAX_HOURS: i64 = 72;
+const RAT_MAX_HOURS: i64 = 48;
+
+fn line(out: &mut String, palette: Palette, record: &str, outcome: Outcome) {
+    let (style, outcome) = outcome;
+    if palette.enabled() {
+        let outcome = palette.paint(style, &outcome);
+        let _ = writeln!(out, "{} {}: {}", style.icon(), record, outcome);
+    } else {
+        let _ = writeln!(out, "{}: {}", record, outcome);
+    }
+}
+
+type Outcome = (Style, String);
+
+/// Evaluate every record of a certificate at `now`, a Unix timestamp,
+/// one line for each, marked as good, bad or needing a look when colored.
+pub fn render(
+    certificate: &Certificate,
+    now: i64,
+    palette: Palette,
+) -> Result<String, InvalidDate> {
+    let today = Date::from_timestamp(now);
+    let vaccination_policy = VaccinationPolicy {
+        doses: BTreeMap::new(),
+        days_after_last_dose: DAYS_AFTER_LAST_DOSE,
+    };
+    let recovery_policy = RecoveryPolicy::default();
+    let test_policy = TestPolicy {
+        naat_max_hours: NAAT_MAX_HOURS,
+        rat_max_hours: RAT_MAX_HOURS,
+    };
+
+    let mut out = String::new();
+    for (i, record) in certificate.v.iter().enumerate() {
+        let outcome =
+            match vaccination_completeness(record, &vaccination_policy, today)?
+            {
+                Completeness::Complete => (Style::Good, "complete".to_string()),
+                Completeness::CompleteAfter(date) => {
+                    (Style::Warning, format!("complete after {}", date))
+                }
+                Completeness::Incomplete => {
+                    (Style::Bad, "incomplete".to_string())
+                }
+            };
+        line(
+            &mut out,
+            palette,
+            &format!("vaccination {}", i + 1),
+            outcome,
+        );
+    }
+    for (i, record) in certificate.r.iter().enumerate() {
+        let outcome = match recovery_window(record, &recovery_policy, today)? {
+            RecoveryWindow::NotYetValid { valid_from } => {
+                (Style::Warning, format!("not valid before {}", valid_from))
+            }
+            RecoveryWindow::Valid {
+                valid_from,
+                valid_until,
+            } => (
+                Style::Good,
+                format!("valid from {} until {}", valid_from, valid_until),
+            ),
+            RecoveryWindow::Expired { valid_until } => {
+                (Style::Bad, format!("expired after {}", valid_until))
+            }
+        };
+        line(&mut out, palette, &format!("recovery {}", i + 1), outcome);
+    }
+    for (i, record) in certificate.t.iter().enumerate() {
+        let outcome = match test_freshness(record, &test_policy, now)? {
+            TestFreshness::Fresh {
+                age_hours,
+                max_hours,
+            } => (
+                Style::Good,
+                format!("fresh, {} of {} hours", age_hours, max_hours),
+            ),
+            TestFreshness::Stale {
+                age_hours,
+                max_hours,
+            } => (
+                Style::Bad,
+                format!("stale, {} of {} hours", age_hours, max_hours),
+            ),
+            TestFreshness::InFuture => {
+                (Style::Warning, "collected in the future".to_string())
+            }
+            TestFreshness::UnknownType => (
+                Style::Warning,
+                format!("unknown test type {}", sanitize(&record.tt)),
+            ),
+        };
+        line(&mut out, palette, &format!("test {}", i + 1), outcome);
+    }
+
+    Ok(out)
+}
+
+#[test]
+fn render_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/2.json
+    // It is licensed under Apache-2.0 License.
+    let recovery_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *MEB2B2JJ59J-9BC6:X9NECX0AKQC:3DCV4*XUA2P-FHT-H4SI/J9WVHWVH+ZEOV1J$HNTICZUBOM*LP$V25$0Q:J40IA3L/*84-5%:C92JN*4CY0*%9F/8J2P4.818T+:IX3M3.96RPVD9J-OZT1-NT0 2$$0$2PZX69B9VCDHI2/T9TU1BPIJKH/T7B-S-*O/Y41FD+X49+5Z-6%.HDD8R6W1FDJGJSFJ/4Q:T0.KJTNP8EFULNC:HA0K5HKRB4TD85LOLF92GF.3O.Z8CC7-2FQYG$%21 2O*4R60NM8JI0EUGP$I/XK$M8ZQE6YB9M66P8N31I.ROSK%IA1Q2N53Q-OQ2VC6E26T11ROSNK5W-*H+MJ%0RGZVGWNURI75RBSQSHLH1JG*CMH2.-S$7VX6N*Z1881J7G.F9I+SV06F+1M*93%D";
+    let certificate = eudcc_core::decode(recovery_data.to_string()).unwrap();
+
+    // 2021-06-01T00:00:00Z
+    assert_eq!(
+        render(&certificate, 1_622_505_600, Palette::new(false)).unwrap(),
+        "recovery 1: valid from 2021-05-04 until 2021-10-31\n"
+    );
+    // 2021-11-01T00:00:00Z
+    assert_eq!(
+        render(&certificate, 1_635_724_800, Palette::new(true)).unwrap(),
+        "✘ recovery 1: \x1b[31mexpired after 2021-10-31\x1b[0m\n"
+    );
+}
@@ -0,0 +1,192 @@
+use ciborium::value::{self, Value};
+use eudcc_core::{Certificate, Payload};
+
+use crate::json;
+use crate::output::sanitize;
+
+/// The fields of a certificate, with the payload claims next to them, as
+/// a map to look paths up in.
+pub fn context(
+    payload: &Payload,
+    certificate: &Certificate,
+) -> Result<Value, value::Error> {
+    let mut fields = vec![
+        ("issuer".into(), payload.issuer.as_str().into()),
+        ("issued_at".into(), payload.issued_at.into()),
+        ("expires_at".into(), payload.expires_at.into()),
+    ];
+    if let Value::Map(certificate) = Value::serialized(certificate)? {
+        fields.extend(certificate);
+    }
+
+    Ok(Value::Map(fields))
+}
+
+/// Look up a dotted path, like `nam.fn` or `v.0.dt`, where numbers index
+/// arrays.
+pub fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |value, key| match value {
+        Value::Map(entries) => entries
+            .iter()
+            .find(|(k, _)| k.as_text() == Some(key))
+            .map(|(_, v)| v),
+        Value::Array(values) => values.get(key.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+/// Whether a dotted path without indices, like `nam.fn` or `v.dt`, is one
+/// of `fields` or under one of them, as `nam.fn` is under `nam`. Without
+/// fields, every path is.
+pub fn allowed(fields: Option<&[String]>, path: &str) -> bool {
+    match fields {
+        Some(fields) => fields.iter().any(|field| {
+            path == field
+                || matches!(path.strip_prefix(field.as_str()), Some(rest) if rest.starts_with('.'))
+        }),
+        None => true,
+    }
+}
+
+/// Only keep the `fields` of a value, and what leads to them: the records
+/// of an array all keep the same ones, so that `v.dt` is the date of every
+/// vaccination.
+pub fn restrict(value: &Value, fields: &[String]) -> Value {
+    restrict_at(value, "", fields)
+}
+
+fn restrict_at(value: &Value, path: &str, fields: &[String]) -> Value {
+    match value {
+        Value::Map(entries) => Value::Map(
+            entries
+                .iter()
+                .filter_map(|(key, value)| {
+                    let path = match (path, key.as_text()?) {
+                        ("", name) => name.to_string(),
+                        (path, name) => format!("{}.{}", path, name),
+                    };
+                    let under = format!("{}.", path);
+                    if allowed(Some(fields), &path) {
+                        Some((key.clone(), value.clone()))
+                    } else if fields.iter().any(|f| f.starts_with(&under))
+                        && matches!(value, Value::Map(_) | Value::Array(_))
+                    {
+                        Some((key.clone(), restrict_at(value, &path, fields)))
+                    } else {
+                        None
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(
+            values
+                .iter()
+                .map(|value| restrict_at(value, path, fields))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+/// Print the value at each path, one a line: strings and numbers as they
+/// are, objects and arrays as JSON.
+pub fn render(context: &Value, paths: &[&str]) -> Result<String, String> {
+    let mut out = String::new();
+    for path in paths {
+        match lookup(context, path) {
+            Some(Value::Text(text)) => out.push_str(&sanitize(text)),
+            Some(value) => out.push_str(&json::to_string(value, false)),
+            None => {
+                return Err(format!("no field {} in the certificate", path))
+            }
+        }
+        out.push('\n');
+    }
+
+    Ok(out)
+}
+
+#[test]
+fn render_test() {
+    let context = Value::Map(vec![
+        ("issuer".into(), "IT".into()),
+        (
+            "nam".into(),
+            Value::Map(vec![("fn".into(), "Di Caprio".into())]),
+        ),
+        (
+            "v".into(),
+            Value::Array(vec![Value::Map(vec![
+                ("dn".into(), 2.into()),
+                ("dt".into(), "2021-04-10".into()),
+            ])]),
+        ),
+    ]);
+
+    assert_eq!(
+        render(&context, &["nam.fn", "v.0.dt", "v.0.dn"]).unwrap(),
+        "Di Caprio\n2021-04-10\n2\n"
+    );
+    assert_eq!(
+        render(&context, &["nam"]).unwrap(),
+        "{\"fn\":\"Di Caprio\"}\n"
+    );
+    assert_eq!(
+        render(&context, &["v.1.dt"]).unwrap_err(),
+        "no field v.1.dt in the certificate"
+    );
+    assert!(lookup(&context, "issuer.x").is_none());
+}
+
+#[test]
+fn restrict_test() {
+    let certificate = Value::Map(vec![
+        (
+            "nam".into(),
+            Value::Map(vec![
+                ("fn".into(), "Di Caprio".into()),
+                ("fnt".into(), "DI<CAPRIO".into()),
+            ]),
+        ),
+        ("dob".into(), "1977-06-16".into()),
+        (
+            "v".into(),
+            Value::Array(vec![
+                Value::Map(vec![
+                    ("dn".into(), 1.into()),
+                    ("dt".into(), "2021-03-10".into()),
+                ]),
+                Value::Map(vec![
+                    ("dn".into(), 2.into()),
+                    ("dt".into(), "2021-04-10".into()),
+                ]),
+            ]),
+        ),
+    ]);
+    let fields = |fields: &str| -> Vec<String> {
+        fields.split(',').map(str::to_string).collect()
+    };
+
+    assert_eq!(
+        json::to_string(
+            &restrict(&certificate, &fields("nam.fnt,v.dt")),
+            false
+        ),
+        r#"{"nam":{"fnt":"DI<CAPRIO"},"v":[{"dt":"2021-03-10"},{"dt":"2021-04-10"}]}"#
+    );
+    assert_eq!(
+        json::to_string(&restrict(&certificate, &fields("nam,dob")), false),
+        r#"{"nam":{"fn":"Di Caprio","fnt":"DI<CAPRIO"},"dob":"1977-06-16"}"#
+    );
+    assert_eq!(
+        json::to_string(&restrict(&certificate, &fields("na,dob.x")), false),
+        "{}"
+    );
+
+    let list = fields("nam,v.dt");
+    assert!(allowed(Some(&list), "nam.fn"));
+    assert!(allowed(Some(&list), "v.dt"));
+    assert!(!allowed(Some(&list), "v.dn"));
+    assert!(!allowed(Some(&list), "name"));
+    assert!(allowed(None, "dob"));
+}
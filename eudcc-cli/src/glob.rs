@@ -0,0 +1,287 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Whether an input is a glob pattern, rather than a path.
+pub fn is_pattern(input: &str) -> bool {
+    input.contains(['*', '?', '['])
+}
+
+/// Whether a file name matches a component of a pattern: `*` matches any
+/// characters, `?` one, and `[abc]`, `[a-z]` or `[!abc]` one of a set.
+/// Like shells do, wildcards don't match the dot of hidden files.
+fn matches(pattern: &str, name: &str) -> bool {
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    match_chars(&pattern, &name)
+}
+
+fn match_chars(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some(('*', rest)), _) => {
+            match_chars(rest, name)
+                || matches!(name.split_first(), Some((_, name)) if match_chars(pattern, name))
+        }
+        (Some(('?', rest)), Some((_, name))) => match_chars(rest, name),
+        (Some(('[', set)), Some((c, name))) => {
+            match set.iter().position(|&s| s == ']') {
+                // The first character can be the ] itself.
+                Some(0) | None => false,
+                Some(end) => {
+                    let (set, rest) = set.split_at(end);
+                    in_set(set, *c) && match_chars(&rest[1..], name)
+                }
+            }
+        }
+        (Some((p, rest)), Some((c, name))) => p == c && match_chars(rest, name),
+        _ => false,
+    }
+}
+
+fn in_set(set: &[char], c: char) -> bool {
+    let (negated, set) = match set.split_first() {
+        Some(('!', set)) => (true, set),
+        _ => (false, set),
+    };
+    let mut found = false;
+    let mut i = 0;
+    while i < set.len() {
+        if i + 2 < set.len() && set[i + 1] == '-' {
+            found |= (set[i]..=set[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            found |= set[i] == c;
+            i += 1;
+        }
+    }
+
+    found != negated
+}
+
+/// The entries of a directory, sorted by path.
+fn entries(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let base = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(base)? {
+        paths.push(dir.join(entry?.file_name()));
+    }
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// Whether a path is a directory, rather than a link to one: links to
+/// directories are not followed, as they could lead back up the tree.
+fn is_dir(path: &Path) -> bool {
+    matches!(fs::symlink_metadata(path), Ok(metadata) if metadata.is_dir())
+}
+
+/// Every file in a directory tree, sorted by path, leaving out links to
+/// directories.
+pub fn walk(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in entries(dir)? {
+        if is_dir(&path) {
+            files.extend(walk(&path)?);
+        } else if !path.is_dir() {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+/// The paths under `dir` matching the components of a pattern, where `**`
+/// matches any directories, none included.
+fn glob(
+    dir: &Path,
+    components: &[&str],
+    found: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    let (component, rest) = match components.split_first() {
+        Some(split) => split,
+        None => {
+            found.push(dir.to_path_buf());
+            return Ok(());
+        }
+    };
+    if *component == "**" {
+        glob(dir, rest, found)?;
+        for path in entries(dir)? {
+            let hidden = matches!(
+                path.file_name(),
+                Some(name) if name.to_string_lossy().starts_with('.')
+            );
+            if is_dir(&path) && !hidden {
+                glob(&path, components, found)?;
+            }
+        }
+    } else if is_pattern(component) {
+        for path in entries(dir)? {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if matches(component, &name) {
+                glob(&path, rest, found)?;
+            }
+        }
+    } else {
+        let path = dir.join(component);
+        if path.exists() {
+            glob(&path, rest, found)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The files an input stands for: those matching it, if it is a glob
+/// pattern, and those in the trees of the directories among them when
+/// `recursive`. Other inputs, `-` included, stand for themselves.
+pub fn expand(input: &str, recursive: bool) -> io::Result<Vec<String>> {
+    let paths = if is_pattern(input) {
+        let (root, pattern) = match input.strip_prefix('/') {
+            Some(pattern) => (Path::new("/"), pattern),
+            None => (Path::new(""), input),
+        };
+        let components: Vec<&str> =
+            pattern.split('/').filter(|c| !c.is_empty()).collect();
+        let mut found = Vec::new();
+        glob(root, &components, &mut found)?;
+        found.sort();
+        found.dedup();
+        if found.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no files match",
+            ));
+        }
+        found
+    } else if Path::new(input).is_dir() {
+        if !recursive {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "is a directory, see --recursive",
+            ));
+        }
+        vec![PathBuf::from(input)]
+    } else {
+        return Ok(vec![input.to_string()]);
+    };
+
+    let mut files = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            files.push(path);
+        } else if recursive {
+            files.extend(walk(&path)?);
+        }
+    }
+
+    Ok(files
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect())
+}
+
+#[test]
+fn matches_test() {
+    assert!(matches("*.txt", "1.txt"));
+    assert!(matches("*", "1.txt"));
+    assert!(!matches("*.txt", "1.json"));
+    assert!(!matches("*.txt", ".hidden.txt"));
+    assert!(matches(".*", ".hidden.txt"));
+    assert!(matches("?.txt", "1.txt"));
+    assert!(!matches("?.txt", "10.txt"));
+    assert!(matches("[0-9].txt", "7.txt"));
+    assert!(matches("[!0-9]*", "scan.txt"));
+    assert!(!matches("[!0-9]*", "7.txt"));
+    assert!(matches("a*b*c", "aXXbYYc"));
+    assert!(!matches("[abc", "a"));
+}
+
+#[test]
+fn expand_test() {
+    let dir = std::env::temp_dir().join("eudccdec-glob-test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("scans/2021/05")).unwrap();
+    fs::create_dir_all(dir.join("scans/.cache")).unwrap();
+    for file in [
+        "scans/a.txt",
+        "scans/b.json",
+        "scans/2021/c.txt",
+        "scans/2021/05/d.txt",
+        "scans/.cache/e.txt",
+    ] {
+        fs::write(dir.join(file), "HC1:").unwrap();
+    }
+    let root = dir.to_str().unwrap();
+    let found = |input: &str, recursive| {
+        let input = format!("{}/{}", root, input);
+        let files: Vec<String> = expand(&input, recursive)
+            .unwrap()
+            .iter()
+            .map(|file| file.trim_start_matches(root).to_string())
+            .collect();
+        files
+    };
+
+    assert_eq!(
+        found("scans/**/*.txt", false),
+        ["/scans/2021/05/d.txt", "/scans/2021/c.txt", "/scans/a.txt"]
+    );
+    assert_eq!(found("scans/*", false), ["/scans/a.txt", "/scans/b.json"]);
+    assert_eq!(
+        found("scans/20*", true),
+        ["/scans/2021/05/d.txt", "/scans/2021/c.txt"]
+    );
+    assert_eq!(found("scans/2021", true).len(), 2);
+    assert_eq!(found("scans/.cache", true), ["/scans/.cache/e.txt"]);
+    assert_eq!(found("scans/a.txt", false), ["/scans/a.txt"]);
+
+    let error = expand(&format!("{}/scans", root), false).unwrap_err();
+    assert_eq!(error.to_string(), "is a directory, see --recursive");
+    let error = expand(&format!("{}/*.pdf", root), false).unwrap_err();
+    assert_eq!(error.to_string(), "no files match");
+    assert_eq!(expand("-", true).unwrap(), ["-"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn expand_cycle_test() {
+    let dir = std::env::temp_dir().join("eudccdec-glob-cycle-test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("scans/2021")).unwrap();
+    fs::write(dir.join("scans/a.txt"), "HC1:").unwrap();
+    fs::write(dir.join("scans/2021/b.txt"), "HC1:").unwrap();
+    std::os::unix::fs::symlink(&dir, dir.join("scans/2021/up")).unwrap();
+    std::os::unix::fs::symlink(
+        dir.join("scans/a.txt"),
+        dir.join("scans/2021/c.txt"),
+    )
+    .unwrap();
+    let root = dir.to_str().unwrap();
+    let found = |input: &str, recursive| {
+        let input = format!("{}/{}", root, input);
+        let files: Vec<String> = expand(&input, recursive)
+            .unwrap()
+            .iter()
+            .map(|file| file.trim_start_matches(root).to_string())
+            .collect();
+        files
+    };
+
+    // The link back up the tree is left alone, links to files are not.
+    let files = ["/scans/2021/b.txt", "/scans/2021/c.txt", "/scans/a.txt"];
+    assert_eq!(found("scans", true), files);
+    assert_eq!(found("**/*.txt", false), files);
+    fs::remove_dir_all(&dir).unwrap();
+}
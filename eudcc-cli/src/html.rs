@@ -0,0 +1,106 @@
+use std::fmt::Write;
+
+use eudcc_core::{Certificate, Payload};
+
+use crate::output::sanitize;
+use crate::report;
+
+// Styles are inlined, so that the report is a single file.
+const HEAD: &str = "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<title>EU Digital COVID Certificates</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+section { margin-bottom: 3em; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #999; padding: 0.3em 0.6em; text-align: left; }
+th { background: #eee; }
+</style>
+</head>
+<body>
+<h1>EU Digital COVID Certificates</h1>
+";
+const TAIL: &str = "</body>
+</html>
+";
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in sanitize(text).chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// A self-contained HTML report on some certificates, a section each,
+/// headed by the name of the holder and the path of its input, if given.
+pub fn render(
+    certificates: &[(Option<String>, Payload, Certificate)],
+) -> String {
+    let mut out = HEAD.to_string();
+    for (path, payload, certificate) in certificates {
+        let name = &certificate.nam;
+        let _ = writeln!(
+            out,
+            "<section>\n<h2>{} {}</h2>",
+            escape(&name.gn),
+            escape(&name.fn_)
+        );
+        if let Some(path) = path {
+            let _ = writeln!(out, "<p class=\"path\">{}</p>", escape(path));
+        }
+        for table in report::tables(payload, certificate) {
+            let _ = writeln!(out, "<h3>{}</h3>\n<table>\n<tr>", table.title);
+            for column in table.header {
+                let _ = writeln!(out, "<th>{}</th>", column);
+            }
+            out.push_str("</tr>\n");
+            for row in &table.rows {
+                out.push_str("<tr>\n");
+                for cell in row {
+                    let _ = writeln!(out, "<td>{}</td>", escape(cell));
+                }
+                out.push_str("</tr>\n");
+            }
+            out.push_str("</table>\n");
+        }
+        out.push_str("</section>\n");
+    }
+    out.push_str(TAIL);
+
+    out
+}
+
+#[test]
+fn render_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/2.json
+    // It is licensed under Apache-2.0 License.
+    let recovery_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *MEB2B2JJ59J-9BC6:X9NECX0AKQC:3DCV4*XUA2P-FHT-H4SI/J9WVHWVH+ZEOV1J$HNTICZUBOM*LP$V25$0Q:J40IA3L/*84-5%:C92JN*4CY0*%9F/8J2P4.818T+:IX3M3.96RPVD9J-OZT1-NT0 2$$0$2PZX69B9VCDHI2/T9TU1BPIJKH/T7B-S-*O/Y41FD+X49+5Z-6%.HDD8R6W1FDJGJSFJ/4Q:T0.KJTNP8EFULNC:HA0K5HKRB4TD85LOLF92GF.3O.Z8CC7-2FQYG$%21 2O*4R60NM8JI0EUGP$I/XK$M8ZQE6YB9M66P8N31I.ROSK%IA1Q2N53Q-OQ2VC6E26T11ROSNK5W-*H+MJ%0RGZVGWNURI75RBSQSHLH1JG*CMH2.-S$7VX6N*Z1881J7G.F9I+SV06F+1M*93%D";
+    let payload =
+        eudcc_core::decode_payload(recovery_data.to_string()).unwrap();
+    let mut certificate = payload.certificate().unwrap();
+    certificate.nam.fn_ = "<b>Di Caprio</b>".to_string();
+
+    let path = Some("scans/<a>.txt".to_string());
+    let html = render(&[(path, payload, certificate)]);
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.ends_with("</html>\n"));
+    assert!(
+        html.contains("<h2>Marilù Teresa &lt;b&gt;Di Caprio&lt;/b&gt;</h2>")
+    );
+    assert!(html.contains("<h3>Recoveries</h3>"));
+    assert!(html.contains("<td>2021-05-04</td>\n<td>2021-10-31</td>"));
+    assert_eq!(html.matches("<section>").count(), 1);
+    assert!(html.contains("<p class=\"path\">scans/&lt;a&gt;.txt</p>"));
+}
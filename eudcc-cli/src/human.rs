@@ -0,0 +1,279 @@
+use std::fmt::Write;
+
+use eudcc_core::{Certificate, Payload};
+
+use crate::color::{Palette, Style};
+use crate::dates::Dates;
+use crate::lang::Labels;
+use crate::name::{self, IdName};
+use crate::output::sanitize;
+
+// Display names from the EU DCC value sets, and of the countries
+// issuing certificates. Codes missing here are printed as they are.
+pub const DISEASES: &[(&str, &str)] = &[("840539006", "COVID-19")];
+pub const VACCINES: &[(&str, &str)] = &[
+    ("1119305005", "SARS-CoV-2 antigen vaccine"),
+    ("1119349007", "SARS-CoV-2 mRNA vaccine"),
+    ("J07BX03", "covid-19 vaccines"),
+];
+pub const PRODUCTS: &[(&str, &str)] = &[
+    ("EU/1/20/1528", "Comirnaty"),
+    ("EU/1/20/1507", "Spikevax"),
+    ("EU/1/21/1529", "Vaxzevria"),
+    ("EU/1/20/1525", "COVID-19 Vaccine Janssen"),
+    ("EU/1/21/1618", "Nuvaxovid"),
+];
+pub const MANUFACTURERS: &[(&str, &str)] = &[
+    ("ORG-100001699", "AstraZeneca AB"),
+    ("ORG-100030215", "Biontech Manufacturing GmbH"),
+    ("ORG-100001417", "Janssen-Cilag International"),
+    ("ORG-100031184", "Moderna Biotech Spain S.L."),
+    ("ORG-100032020", "Novavax CZ AS"),
+];
+pub const TEST_TYPES: &[(&str, &str)] =
+    &[("LP6464-4", "NAAT"), ("LP217198-3", "rapid antigen test")];
+pub const TEST_RESULTS: &[(&str, &str)] =
+    &[("260415000", "not detected"), ("260373001", "detected")];
+pub const COUNTRIES: &[(&str, &str)] = &[
+    ("AT", "Austria"),
+    ("BE", "Belgium"),
+    ("BG", "Bulgaria"),
+    ("CH", "Switzerland"),
+    ("CY", "Cyprus"),
+    ("CZ", "Czechia"),
+    ("DE", "Germany"),
+    ("DK", "Denmark"),
+    ("EE", "Estonia"),
+    ("ES", "Spain"),
+    ("FI", "Finland"),
+    ("FR", "France"),
+    ("GB", "United Kingdom"),
+    ("GR", "Greece"),
+    ("HR", "Croatia"),
+    ("HU", "Hungary"),
+    ("IE", "Ireland"),
+    ("IS", "Iceland"),
+    ("IT", "Italy"),
+    ("LI", "Liechtenstein"),
+    ("LT", "Lithuania"),
+    ("LU", "Luxembourg"),
+    ("LV", "Latvia"),
+    ("MT", "Malta"),
+    ("NL", "Netherlands"),
+    ("NO", "Norway"),
+    ("PL", "Poland"),
+    ("PT", "Portugal"),
+    ("RO", "Romania"),
+    ("SE", "Sweden"),
+    ("SI", "Slovenia"),
+    ("SK", "Slovakia"),
+];
+
+/// Display name of a code, or the code itself.
+pub fn display(values: &[(&str, &'static str)], code: &str) -> String {
+    match values.iter().find(|(c, _)| *c == code) {
+        Some((_, name)) => name.to_string(),
+        None => sanitize(code),
+    }
+}
+
+/// The output being written, with how to write it.
+struct View<'a> {
+    out: String,
+    palette: Palette,
+    labels: &'a Labels,
+    dates: &'a Dates,
+}
+
+impl View<'_> {
+    fn row(&mut self, label: &str, value: &str) {
+        let label = format!("{}:", label);
+        let label = format!("{:<w$}", label, w = self.labels.record_width());
+        let label = self.palette.paint(Style::Field, &label);
+        let _ = writeln!(self.out, "  {}{}", label, value);
+    }
+
+    fn heading(&mut self, heading: &str) {
+        let heading = self.palette.paint(Style::Heading, heading);
+        let _ = writeln!(self.out, "\n{}", heading);
+    }
+
+    fn date(&self, date: &str) -> String {
+        self.dates.date(date, self.labels)
+    }
+}
+
+/// A human readable view of the certificate: the holder, then every
+/// record under a heading summarizing it.
+pub fn render(
+    payload: &Payload,
+    certificate: &Certificate,
+    id_name: IdName,
+    palette: Palette,
+    labels: &Labels,
+    dates: &Dates,
+) -> String {
+    let mut view = View {
+        out: name::render(&certificate.nam, id_name, labels),
+        palette,
+        labels,
+        dates,
+    };
+    let _ = writeln!(
+        view.out,
+        "{:<w$}{}",
+        format!("{}:", labels.date_of_birth),
+        view.date(&certificate.dob),
+        w = labels.holder_width()
+    );
+
+    for v in &certificate.v {
+        view.heading(&format!(
+            "{}: {} {} {} {} — {}, {}",
+            labels.vaccination,
+            labels.dose,
+            v.dn,
+            labels.of,
+            v.sd,
+            view.date(&v.dt),
+            display(COUNTRIES, &v.co)
+        ));
+        view.row(labels.disease, &display(DISEASES, &v.tg));
+        view.row(labels.vaccine, &display(VACCINES, &v.vp));
+        view.row(labels.product, &display(PRODUCTS, &v.mp));
+        view.row(labels.manufacturer, &display(MANUFACTURERS, &v.ma));
+        view.row(labels.issuer, &sanitize(&v.is));
+        view.row(labels.identifier, &sanitize(&v.ci));
+    }
+    for r in &certificate.r {
+        view.heading(&format!(
+            "{}: {} {} {} {}, {}",
+            labels.recovery,
+            labels.valid_from,
+            view.date(&r.df),
+            labels.valid_to,
+            view.date(&r.du),
+            display(COUNTRIES, &r.co)
+        ));
+        view.row(labels.disease, &display(DISEASES, &r.tg));
+        let positive_on = view.date(&r.fr);
+        view.row(labels.positive_on, &positive_on);
+        view.row(labels.issuer, &sanitize(&r.is));
+        view.row(labels.identifier, &sanitize(&r.ci));
+    }
+    for t in &certificate.t {
+        view.heading(&format!(
+            "{}: {}, {} — {}, {}",
+            labels.test,
+            display(TEST_TYPES, &t.tt),
+            display(TEST_RESULTS, &t.tr),
+            dates.date_time(&t.sc, labels),
+            display(COUNTRIES, &t.co)
+        ));
+        view.row(labels.disease, &display(DISEASES, &t.tg));
+        if !t.nm.is_empty() {
+            view.row(labels.test_name, &sanitize(&t.nm));
+        }
+        if !t.ma.is_empty() {
+            view.row(labels.manufacturer, &sanitize(&t.ma));
+        }
+        view.row(labels.test_centre, &sanitize(&t.tc));
+        view.row(labels.issuer, &sanitize(&t.is));
+        view.row(labels.identifier, &sanitize(&t.ci));
+    }
+
+    let _ = writeln!(
+        view.out,
+        "\n{} {}, {} {}, {} {}",
+        labels.issued_by,
+        sanitize(&payload.issuer),
+        labels.issued_at,
+        dates.timestamp(payload.issued_at, labels),
+        labels.expiring_at,
+        dates.timestamp(payload.expires_at, labels)
+    );
+
+    view.out
+}
+
+#[test]
+fn render_test() {
+    use crate::lang::Lang;
+
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/1.json
+    // It is licensed under Apache-2.0 License.
+    let vaccination_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II5XHC9B5G2+$N IOP-IA%NFQGRJPC%OQHIZC4.OI1RM8ZA.A5:S9MKN4NN3F85QNCY0O%0VZ001HOC9JU0D0HT0HB2PL/IB*09B9LW4T*8+DCMH0LDK2%K:XFE70*LP$V25$0Q:J:4MO1P0%0L0HD+9E/HY+4J6TH48S%4K.GJ2PT3QY:GQ3TE2I+-CPHN6D7LLK*2HG%89UV-0LZ 2ZJJ524-LH/CJTK96L6SR9MU9DHGZ%P WUQRENS431T1XCNCF+47AY0-IFO0500TGPN8F5G.41Q2E4T8ALW.INSV$ 07UV5SR+BNQHNML7 /KD3TU 4V*CAT3ZGLQMI/XI%ZJNSBBXK2:UG%UJMI:TU+MMPZ5$/PMX19UE:-PSR3/$NU44CBE6DQ3D7B0FBOFX0DV2DGMB$YPF62I$60/F$Z2I6IFX21XNI-LM%3/DF/U6Z9FEOJVRLVW6K$UG+BKK57:1+D10%4K83F+1VWD1NE";
+    let payload =
+        eudcc_core::decode_payload(vaccination_data.to_string()).unwrap();
+    let certificate = payload.certificate().unwrap();
+    let en = Lang::En.labels();
+
+    assert_eq!(
+        render(
+            &payload,
+            &certificate,
+            IdName::Original,
+            Palette::new(false),
+            en,
+            &Dates::default()
+        ),
+        "name:           Di Caprio, Marilù Teresa  <- compare with the ID
+transliterated: DI CAPRIO, MARILU TERESA
+date of birth:  1977-06-16
+
+Vaccination: dose 2 of 2 — 2021-04-10, Italy
+  disease:      COVID-19
+  vaccine:      SARS-CoV-2 mRNA vaccine
+  product:      Comirnaty
+  manufacturer: Biontech Manufacturing GmbH
+  issuer:       IT
+  identifier:   01ITE7300E1AB2A84C719004F103DCB1F70A#6
+
+issued by IT, at 2021-05-21T10:33:44Z, expiring at 2021-11-17T11:33:44Z
+"
+    );
+    let colored = render(
+        &payload,
+        &certificate,
+        IdName::Original,
+        Palette::new(true),
+        en,
+        &Dates::default(),
+    );
+    assert!(colored.contains("\n\x1b[1mVaccination: dose 2 of 2"));
+    assert!(colored.contains("  \x1b[36mdisease:      \x1b[0mCOVID-19\n"));
+
+    let german = render(
+        &payload,
+        &certificate,
+        IdName::Original,
+        Palette::new(false),
+        Lang::De.labels(),
+        &Dates::default(),
+    );
+    assert!(german.contains("\nGeburtsdatum:   1977-06-16\n"));
+    assert!(german.contains("\nImpfung: Dosis 2 von 2 — 2021-04-10, Italy\n"));
+    assert!(german.contains("\n  Hersteller:   Biontech Manufacturing GmbH\n"));
+    assert!(german.ends_with(
+        "\nausgestellt von IT, am 2021-05-21T10:33:44Z, läuft ab am \
+         2021-11-17T11:33:44Z\n"
+    ));
+
+    let dates = Dates {
+        format: crate::dates::DateFormat::Long,
+        zone: None,
+    };
+    let german = render(
+        &payload,
+        &certificate,
+        IdName::Original,
+        Palette::new(false),
+        Lang::De.labels(),
+        &dates,
+    );
+    assert!(german.contains("\nGeburtsdatum:   16. Juni 1977\n"));
+    assert!(german.ends_with(
+        "am 21. Mai 2021 10:33 UTC, läuft ab am 17. November 2021 11:33 UTC\n"
+    ));
+}
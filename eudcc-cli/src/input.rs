@@ -0,0 +1,205 @@
+use std::fs;
+use std::io::{self, Read};
+
+use eudcc_core::eudcc::{self, CoseSign1, DecodeError, Metadata};
+use eudcc_core::{DecodeOptions, Payload};
+
+const HC1_PREFIX: &str = "HC1:";
+
+/// What the certificate is read as.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Input {
+    /// The `HC1:` string of the QR code.
+    Hc1,
+    /// The COSE structure, as CBOR, like `--output cose` writes it.
+    Cose,
+}
+
+impl Input {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "hc1" => Some(Input::Hc1),
+            "cose" => Some(Input::Cose),
+            _ => None,
+        }
+    }
+}
+
+/// Read a whole input: the file at `path`, or stdin for `-`.
+pub fn read(path: &str) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    match path {
+        "-" => {
+            io::stdin().read_to_end(&mut data)?;
+        }
+        path => data = fs::read(path)?,
+    }
+
+    Ok(data)
+}
+
+fn text(data: Vec<u8>) -> io::Result<String> {
+    String::from_utf8(data)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Read a whole input as text.
+pub fn read_text(path: &str) -> io::Result<String> {
+    text(read(path)?)
+}
+
+/// Where a certificate comes from: a whole input, or a line of one in
+/// batch mode.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Origin<'a> {
+    Path(&'a str),
+    Line(&'a str),
+}
+
+impl Origin<'_> {
+    pub fn read(self) -> io::Result<Vec<u8>> {
+        match self {
+            Origin::Path(path) => read(path),
+            Origin::Line(line) => Ok(line.as_bytes().to_vec()),
+        }
+    }
+
+    pub fn read_text(self) -> io::Result<String> {
+        match self {
+            Origin::Path(path) => read_text(path),
+            Origin::Line(line) => Ok(line.to_string()),
+        }
+    }
+}
+
+/// The non-empty lines of an input, numbered from 1, each holding a
+/// certificate in batch mode.
+pub fn lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+}
+
+/// A certificate as read from an input, at the stage of decoding it was
+/// given at.
+pub enum Source {
+    Hc1(String),
+    Cose(Vec<u8>),
+}
+
+impl Source {
+    pub fn read(input: Input, origin: Origin) -> io::Result<Self> {
+        match input {
+            Input::Hc1 => Ok(Source::Hc1(origin.read_text()?)),
+            Input::Cose => Ok(Source::Cose(origin.read()?)),
+        }
+    }
+
+    /// The data as it was read.
+    #[cfg(feature = "corpus")]
+    pub fn bytes(&self) -> &[u8] {
+        match self {
+            Source::Hc1(data) => data.as_bytes(),
+            Source::Cose(data) => data,
+        }
+    }
+
+    /// Decode the CWT payload, as controlled by `options`.
+    pub fn payload(
+        &self,
+        options: &DecodeOptions,
+    ) -> Result<Payload, DecodeError> {
+        match self {
+            Source::Hc1(data) => {
+                eudcc::decode_payload_with(data.as_bytes(), options)
+            }
+            Source::Cose(data) => eudcc::parse_hcert_with(
+                &eudcc::parse_cose(data)?.payload,
+                options,
+            ),
+        }
+    }
+
+    /// Decode the CWT metadata and the key identifier, as controlled by
+    /// `options`, skipping the hcert.
+    pub fn metadata(
+        &self,
+        options: &DecodeOptions,
+    ) -> Result<Metadata, DecodeError> {
+        match self {
+            Source::Hc1(data) => {
+                eudcc::decode_metadata_with(data.as_bytes(), options)
+            }
+            Source::Cose(data) => {
+                eudcc::parse_metadata_with(&eudcc::parse_cose(data)?, options)
+            }
+        }
+    }
+
+    /// Decode the COSE structure.
+    pub fn cose(&self) -> Result<CoseSign1, DecodeError> {
+        match self {
+            Source::Hc1(data) => eudcc::decode_cose_bytes(data.as_bytes()),
+            Source::Cose(data) => eudcc::parse_cose(data),
+        }
+    }
+
+    /// The COSE structure as it was encoded, rather than as re-encoded
+    /// from the decoded one.
+    pub fn cose_bytes(&self) -> Result<Vec<u8>, DecodeError> {
+        match self {
+            Source::Hc1(data) => {
+                let data = data.trim();
+                let data = data.strip_prefix(HC1_PREFIX).unwrap_or(data);
+                eudcc::decompress(&eudcc::decode_base45(data.as_bytes())?)
+            }
+            Source::Cose(data) => Ok(data.clone()),
+        }
+    }
+}
+
+#[test]
+fn source_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/2.json
+    // It is licensed under Apache-2.0 License.
+    let recovery_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *MEB2B2JJ59J-9BC6:X9NECX0AKQC:3DCV4*XUA2P-FHT-H4SI/J9WVHWVH+ZEOV1J$HNTICZUBOM*LP$V25$0Q:J40IA3L/*84-5%:C92JN*4CY0*%9F/8J2P4.818T+:IX3M3.96RPVD9J-OZT1-NT0 2$$0$2PZX69B9VCDHI2/T9TU1BPIJKH/T7B-S-*O/Y41FD+X49+5Z-6%.HDD8R6W1FDJGJSFJ/4Q:T0.KJTNP8EFULNC:HA0K5HKRB4TD85LOLF92GF.3O.Z8CC7-2FQYG$%21 2O*4R60NM8JI0EUGP$I/XK$M8ZQE6YB9M66P8N31I.ROSK%IA1Q2N53Q-OQ2VC6E26T11ROSNK5W-*H+MJ%0RGZVGWNURI75RBSQSHLH1JG*CMH2.-S$7VX6N*Z1881J7G.F9I+SV06F+1M*93%D";
+    let options = DecodeOptions::new();
+
+    // What --output cose writes is read back by --from cose.
+    let hc1 = Source::Hc1(recovery_data.to_string());
+    let cose = Source::Cose(hc1.cose_bytes().unwrap());
+    assert_eq!(hc1.cose().unwrap(), cose.cose().unwrap());
+    assert_eq!(
+        hc1.payload(&options).unwrap(),
+        cose.payload(&options).unwrap()
+    );
+    assert_eq!(cose.cose_bytes().unwrap(), hc1.cose_bytes().unwrap());
+    assert_eq!(
+        hc1.metadata(&options).unwrap(),
+        cose.metadata(&options).unwrap()
+    );
+
+    let options = options.reject_issuers(&["IT"]);
+    assert!(matches!(
+        cose.payload(&options),
+        Err(DecodeError::IssuerRejected(_))
+    ));
+    assert!(matches!(
+        cose.metadata(&options),
+        Err(DecodeError::IssuerRejected(_))
+    ));
+    assert!(matches!(
+        Source::Cose(vec![0xff]).payload(&options),
+        Err(DecodeError::Cbor { .. })
+    ));
+}
+
+#[test]
+fn lines_test() {
+    let lines: Vec<(usize, &str)> =
+        lines("HC1:A\n\n  HC1:B \r\nHC1:C").collect();
+    assert_eq!(lines, vec![(1, "HC1:A"), (3, "HC1:B"), (4, "HC1:C")]);
+    assert_eq!(Origin::Line("HC1:A").read_text().unwrap(), "HC1:A");
+}
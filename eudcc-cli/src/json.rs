@@ -0,0 +1,365 @@
+use std::fmt::Write;
+
+use ciborium::value::Value;
+use eudcc_core::eudcc::hex;
+
+const INDENT: &str = "  ";
+
+/// Quote and escape a string as a JSON string.
+pub fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+
+    out
+}
+
+/// JSON object keys must be strings, so other CBOR keys are converted.
+fn key(key: &Value) -> String {
+    match key {
+        Value::Text(text) => string(text),
+        Value::Integer(i) => string(&i128::from(*i).to_string()),
+        key => {
+            let mut out = String::new();
+            write_value(&mut out, key, false, 0);
+            string(&out)
+        }
+    }
+}
+
+fn newline(out: &mut String, pretty: bool, level: usize) {
+    if pretty {
+        out.push('\n');
+        for _ in 0..level {
+            out.push_str(INDENT);
+        }
+    }
+}
+
+fn write_value(out: &mut String, value: &Value, pretty: bool, level: usize) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Integer(i) => out.push_str(&i128::from(*i).to_string()),
+        Value::Float(f) if f.is_finite() => {
+            let _ = write!(out, "{}", f);
+        }
+        Value::Float(_) => out.push_str("null"),
+        Value::Text(text) => out.push_str(&string(text)),
+        // Byte strings have no JSON counterpart: write them in hexadecimal.
+        Value::Bytes(bytes) => out.push_str(&string(&hex(bytes))),
+        Value::Tag(_, value) => write_value(out, value, pretty, level),
+        Value::Array(items) if items.is_empty() => out.push_str("[]"),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, pretty, level + 1);
+                write_value(out, item, pretty, level + 1);
+            }
+            newline(out, pretty, level);
+            out.push(']');
+        }
+        Value::Map(entries) if entries.is_empty() => out.push_str("{}"),
+        Value::Map(entries) => {
+            let separator = if pretty { ": " } else { ":" };
+            out.push('{');
+            for (i, (k, v)) in entries.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, pretty, level + 1);
+                out.push_str(&key(k));
+                out.push_str(separator);
+                write_value(out, v, pretty, level + 1);
+            }
+            newline(out, pretty, level);
+            out.push('}');
+        }
+        _ => out.push_str("null"),
+    }
+}
+
+/// Write a CBOR value as compact JSON, or as indented JSON if `pretty`.
+pub fn to_string(value: &Value, pretty: bool) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value, pretty, 0);
+
+    out
+}
+
+/// How deep arrays and objects can be nested in the JSON parsed, so that
+/// hostile input can't exhaust the stack.
+#[cfg_attr(not(feature = "corpus"), allow(dead_code))]
+const MAX_DEPTH: usize = 128;
+
+#[cfg_attr(not(feature = "corpus"), allow(dead_code))]
+struct Parser<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+#[cfg_attr(not(feature = "corpus"), allow(dead_code))]
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        self.text.get(self.offset..).unwrap_or_default()
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("invalid JSON at offset {}: {}", self.offset, message)
+    }
+
+    fn whitespace(&mut self) {
+        let rest = self.rest();
+        let trimmed = rest.trim_start_matches([' ', '\t', '\n', '\r']);
+        self.offset += rest.len() - trimmed.len();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.whitespace();
+        if self.rest().starts_with(token) {
+            self.offset += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+        self.whitespace();
+        match self.rest().chars().next() {
+            Some('{') => self.object(depth),
+            Some('[') => self.array(depth),
+            Some('"') => Ok(Value::Text(self.string()?)),
+            Some('-' | '0'..='9') => self.number(),
+            _ if self.eat("null") => Ok(Value::Null),
+            _ if self.eat("true") => Ok(Value::Bool(true)),
+            _ if self.eat("false") => Ok(Value::Bool(false)),
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Value, String> {
+        self.offset += 1;
+        let mut entries = vec![];
+        if self.eat("}") {
+            return Ok(Value::Map(entries));
+        }
+        loop {
+            self.whitespace();
+            if !self.rest().starts_with('"') {
+                return Err(self.error("expected a key"));
+            }
+            let key = self.string()?;
+            if !self.eat(":") {
+                return Err(self.error("expected ':'"));
+            }
+            entries.push((Value::Text(key), self.value(depth + 1)?));
+            if self.eat("}") {
+                return Ok(Value::Map(entries));
+            }
+            if !self.eat(",") {
+                return Err(self.error("expected ',' or '}'"));
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Value, String> {
+        self.offset += 1;
+        let mut items = vec![];
+        if self.eat("]") {
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value(depth + 1)?);
+            if self.eat("]") {
+                return Ok(Value::Array(items));
+            }
+            if !self.eat(",") {
+                return Err(self.error("expected ',' or ']'"));
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.offset += 1;
+        let mut out = String::new();
+        loop {
+            match self.rest().chars().next() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') => {
+                    self.offset += 1;
+                    return Ok(out);
+                }
+                Some('\\') => {
+                    self.offset += 1;
+                    out.push(self.escape()?);
+                }
+                Some(c) if (c as u32) < 0x20 => {
+                    return Err(self.error("control character in string"));
+                }
+                Some(c) => {
+                    out.push(c);
+                    self.offset += c.len_utf8();
+                }
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, String> {
+        let c = match self.rest().chars().next() {
+            Some(c) => c,
+            None => return Err(self.error("unterminated string")),
+        };
+        self.offset += c.len_utf8();
+        let c = match c {
+            '"' | '\\' | '/' => c,
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => {
+                let mut code = self.hex()?;
+                // Characters past the BMP come as UTF-16 surrogate pairs.
+                if (0xd800..0xdc00).contains(&code) && self.eat("\\u") {
+                    let low = self.hex()?;
+                    if !(0xdc00..0xe000).contains(&low) {
+                        return Err(self.error("invalid surrogate pair"));
+                    }
+                    code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                }
+                match char::from_u32(code) {
+                    Some(c) => c,
+                    None => return Err(self.error("invalid \\u escape")),
+                }
+            }
+            _ => return Err(self.error("invalid escape")),
+        };
+
+        Ok(c)
+    }
+
+    fn hex(&mut self) -> Result<u32, String> {
+        let digits = self.rest().get(..4).unwrap_or_default();
+        if digits.len() < 4 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.error("invalid \\u escape"));
+        }
+        self.offset += 4;
+
+        u32::from_str_radix(digits, 16).map_err(|e| self.error(&e.to_string()))
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| {
+                !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E')
+            })
+            .unwrap_or(rest.len());
+        let number = rest.get(..len).unwrap_or_default();
+        let value = if number.contains(['.', 'e', 'E']) {
+            number.parse().ok().map(Value::Float)
+        } else {
+            // Integers too large for CBOR are kept as floats.
+            match number.parse::<i128>() {
+                Ok(i) => Some(match ciborium::value::Integer::try_from(i) {
+                    Ok(i) => Value::Integer(i),
+                    Err(_) => Value::Float(i as f64),
+                }),
+                Err(_) => None,
+            }
+        };
+        match value {
+            Some(value) => {
+                self.offset += len;
+                Ok(value)
+            }
+            None => Err(self.error("invalid number")),
+        }
+    }
+}
+
+/// Parse JSON text into a CBOR value, the counterpart of [`to_string`]:
+/// numbers without a fraction or an exponent are integers, the others
+/// floats.
+#[cfg_attr(not(feature = "corpus"), allow(dead_code))]
+pub fn parse(text: &str) -> Result<Value, String> {
+    let mut parser = Parser { text, offset: 0 };
+    let value = parser.value(0)?;
+    parser.whitespace();
+    if !parser.rest().is_empty() {
+        return Err(parser.error("trailing data"));
+    }
+
+    Ok(value)
+}
+
+#[test]
+fn json_test() {
+    let value = Value::Map(vec![
+        ("name".into(), "Marilù \"Teresa\"\n".into()),
+        (Value::from(-260), Value::Array(vec![1.into(), Value::Null])),
+        ("raw".into(), Value::Bytes(vec![0xde, 0xad])),
+        ("empty".into(), Value::Array(vec![])),
+        ("ok".into(), true.into()),
+    ]);
+
+    assert_eq!(
+        to_string(&value, false),
+        r#"{"name":"Marilù \"Teresa\"\n","-260":[1,null],"raw":"dead","empty":[],"ok":true}"#
+    );
+    assert_eq!(
+        to_string(&value, true),
+        r#"{
+  "name": "Marilù \"Teresa\"\n",
+  "-260": [
+    1,
+    null
+  ],
+  "raw": "dead",
+  "empty": [],
+  "ok": true
+}"#
+    );
+    assert_eq!(string("\u{1b}[2J"), r#""\u001b[2J""#);
+}
+
+#[test]
+fn parse_test() {
+    let text = r#"{"name":"Marilù \"Teresa\"\n","-260":[1,null],"raw":"dead","empty":[],"ok":true}"#;
+    let value = parse(text).unwrap();
+    assert_eq!(to_string(&value, false), text);
+    assert_eq!(parse(&to_string(&value, true)).unwrap(), value);
+
+    assert_eq!(
+        parse(" [-2, 1.5e1, \"\\u00e8\\ud83d\\ude00\\/\"] ").unwrap(),
+        Value::Array(vec![(-2).into(), Value::Float(15.0), "è😀/".into()])
+    );
+    assert_eq!(
+        parse(r#"{"a":1,}"#),
+        Err("invalid JSON at offset 7: expected a key".to_string())
+    );
+    assert!(parse("[1] 2").is_err());
+    assert!(parse("\"\u{1}\"").is_err());
+    assert!(parse("\"\\ud800\\u0041\"").is_err());
+    assert!(parse(&"[".repeat(1000)).is_err());
+}
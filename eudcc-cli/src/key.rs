@@ -0,0 +1,81 @@
+use ciborium::value::Value;
+use eudcc_core::eudcc::{
+    algorithm_name, hex, CoseSign1, COSE_HEADER_ALG, COSE_HEADER_KID,
+};
+use eudcc_core::sha256;
+
+use crate::json;
+
+const BASE64: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard base64, padded, as trust lists write key identifiers.
+fn base64(data: &[u8]) -> String {
+    let mut out = String::new();
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = u32::from(b[0]) << 16 | u32::from(b[1]) << 8 | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
+
+/// A JSON line describing the key that signed a certificate, for looking
+/// up its document signer certificate: key identifier, algorithm, issuing
+/// country and SHA-256 digest of the signature.
+pub fn stub(cose: &CoseSign1, country: &str) -> String {
+    let kid = match cose.header(COSE_HEADER_KID) {
+        Some(Value::Bytes(kid)) => json::string(&base64(&kid)),
+        _ => "null".to_string(),
+    };
+    let alg = match cose.header(COSE_HEADER_ALG).and_then(|a| a.as_integer()) {
+        Some(alg) => {
+            let alg = i128::from(alg);
+            match algorithm_name(alg) {
+                Some(name) => json::string(name),
+                None => alg.to_string(),
+            }
+        }
+        None => "null".to_string(),
+    };
+
+    format!(
+        "{{\"kid\":{},\"alg\":{},\"country\":{},\"sig_sha256\":{}}}\n",
+        kid,
+        alg,
+        json::string(country),
+        json::string(&hex(&sha256(&cose.signature)))
+    )
+}
+
+#[test]
+fn stub_test() {
+    assert_eq!(base64(b""), "");
+    assert_eq!(base64(b"f"), "Zg==");
+    assert_eq!(base64(b"fo"), "Zm8=");
+    assert_eq!(base64(b"foo"), "Zm9v");
+
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/1.json
+    // It is licensed under Apache-2.0 License.
+    let vaccination_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II5XHC9B5G2+$N IOP-IA%NFQGRJPC%OQHIZC4.OI1RM8ZA.A5:S9MKN4NN3F85QNCY0O%0VZ001HOC9JU0D0HT0HB2PL/IB*09B9LW4T*8+DCMH0LDK2%K:XFE70*LP$V25$0Q:J:4MO1P0%0L0HD+9E/HY+4J6TH48S%4K.GJ2PT3QY:GQ3TE2I+-CPHN6D7LLK*2HG%89UV-0LZ 2ZJJ524-LH/CJTK96L6SR9MU9DHGZ%P WUQRENS431T1XCNCF+47AY0-IFO0500TGPN8F5G.41Q2E4T8ALW.INSV$ 07UV5SR+BNQHNML7 /KD3TU 4V*CAT3ZGLQMI/XI%ZJNSBBXK2:UG%UJMI:TU+MMPZ5$/PMX19UE:-PSR3/$NU44CBE6DQ3D7B0FBOFX0DV2DGMB$YPF62I$60/F$Z2I6IFX21XNI-LM%3/DF/U6Z9FEOJVRLVW6K$UG+BKK57:1+D10%4K83F+1VWD1NE";
+    let cose =
+        eudcc_core::eudcc::decode_cose(vaccination_data.to_string()).unwrap();
+
+    assert_eq!(
+        stub(&cose, "IT"),
+        r#"{"kid":"OTAXaM3aBRM=","alg":"ES256","country":"IT","sig_sha256":"1215ac5751f9126134d1ba64326ad672c9a54111935365cc9748e0c11248a2c2"}
+"#
+    );
+}
@@ -0,0 +1,275 @@
+/// Language of the labels of the human readable views.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Lang {
+    En,
+    It,
+    De,
+    Fr,
+}
+
+impl Lang {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "en" => Some(Lang::En),
+            "it" => Some(Lang::It),
+            "de" => Some(Lang::De),
+            "fr" => Some(Lang::Fr),
+            _ => None,
+        }
+    }
+
+    pub fn labels(self) -> &'static Labels {
+        match self {
+            Lang::En => &EN,
+            Lang::It => &IT,
+            Lang::De => &DE,
+            Lang::Fr => &FR,
+        }
+    }
+}
+
+/// Labels of the human readable views, in one language.
+///
+/// Headings are made of several labels, like "Vaccination: dose 2 of 2",
+/// so the order of their words is the same in every language.
+pub struct Labels {
+    pub name: &'static str,
+    pub transliterated: &'static str,
+    pub date_of_birth: &'static str,
+    pub compare_with_id: &'static str,
+    pub vaccination: &'static str,
+    pub dose: &'static str,
+    pub of: &'static str,
+    pub recovery: &'static str,
+    pub valid_from: &'static str,
+    pub valid_to: &'static str,
+    pub test: &'static str,
+    pub disease: &'static str,
+    pub vaccine: &'static str,
+    pub product: &'static str,
+    pub manufacturer: &'static str,
+    pub issuer: &'static str,
+    pub identifier: &'static str,
+    pub positive_on: &'static str,
+    pub test_name: &'static str,
+    pub test_centre: &'static str,
+    pub issued_by: &'static str,
+    pub issued_at: &'static str,
+    pub expiring_at: &'static str,
+    pub months: [&'static str; 12],
+    /// What follows the day of long dates, like the dot of "10. April".
+    pub day_suffix: &'static str,
+    /// What separates the day, month and year of short dates.
+    pub date_separator: char,
+}
+
+impl Labels {
+    /// Width of the labels of the holder, colon included, so that their
+    /// values line up.
+    pub fn holder_width(&self) -> usize {
+        let labels = [self.name, self.transliterated, self.date_of_birth];
+        width(&labels, 15)
+    }
+
+    /// Width of the labels of the record fields, colon included.
+    pub fn record_width(&self) -> usize {
+        let labels = [
+            self.disease,
+            self.vaccine,
+            self.product,
+            self.manufacturer,
+            self.issuer,
+            self.identifier,
+            self.positive_on,
+            self.test_name,
+            self.test_centre,
+        ];
+        width(&labels, 13)
+    }
+}
+
+/// Width of the longest label, and of a colon and a space after it, but
+/// at least `min`.
+fn width(labels: &[&str], min: usize) -> usize {
+    let longest = labels.iter().map(|l| l.chars().count() + 1).max();
+    longest.unwrap_or(0).max(min) + 1
+}
+
+const EN: Labels = Labels {
+    name: "name",
+    transliterated: "transliterated",
+    date_of_birth: "date of birth",
+    compare_with_id: "compare with the ID",
+    vaccination: "Vaccination",
+    dose: "dose",
+    of: "of",
+    recovery: "Recovery",
+    valid_from: "valid",
+    valid_to: "to",
+    test: "Test",
+    disease: "disease",
+    vaccine: "vaccine",
+    product: "product",
+    manufacturer: "manufacturer",
+    issuer: "issuer",
+    identifier: "identifier",
+    positive_on: "positive on",
+    test_name: "test name",
+    test_centre: "test centre",
+    issued_by: "issued by",
+    issued_at: "at",
+    expiring_at: "expiring at",
+    months: [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ],
+    day_suffix: "",
+    date_separator: '/',
+};
+
+const IT: Labels = Labels {
+    name: "nome",
+    transliterated: "traslitterato",
+    date_of_birth: "data di nascita",
+    compare_with_id: "confrontare con il documento",
+    vaccination: "Vaccinazione",
+    dose: "dose",
+    of: "di",
+    recovery: "Guarigione",
+    valid_from: "valida dal",
+    valid_to: "al",
+    test: "Test",
+    disease: "malattia",
+    vaccine: "vaccino",
+    product: "prodotto",
+    manufacturer: "produttore",
+    issuer: "emittente",
+    identifier: "identificativo",
+    positive_on: "positivo il",
+    test_name: "nome del test",
+    test_centre: "centro test",
+    issued_by: "emesso da",
+    issued_at: "il",
+    expiring_at: "scade il",
+    months: [
+        "gennaio",
+        "febbraio",
+        "marzo",
+        "aprile",
+        "maggio",
+        "giugno",
+        "luglio",
+        "agosto",
+        "settembre",
+        "ottobre",
+        "novembre",
+        "dicembre",
+    ],
+    day_suffix: "",
+    date_separator: '/',
+};
+
+const DE: Labels = Labels {
+    name: "Name",
+    transliterated: "transliteriert",
+    date_of_birth: "Geburtsdatum",
+    compare_with_id: "mit dem Ausweis vergleichen",
+    vaccination: "Impfung",
+    dose: "Dosis",
+    of: "von",
+    recovery: "Genesung",
+    valid_from: "gültig vom",
+    valid_to: "bis",
+    test: "Test",
+    disease: "Krankheit",
+    vaccine: "Impfstoff",
+    product: "Produkt",
+    manufacturer: "Hersteller",
+    issuer: "Aussteller",
+    identifier: "Kennung",
+    positive_on: "positiv am",
+    test_name: "Testname",
+    test_centre: "Testzentrum",
+    issued_by: "ausgestellt von",
+    issued_at: "am",
+    expiring_at: "läuft ab am",
+    months: [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ],
+    day_suffix: ".",
+    date_separator: '.',
+};
+
+const FR: Labels = Labels {
+    name: "nom",
+    transliterated: "translittéré",
+    date_of_birth: "date de naissance",
+    compare_with_id: "comparer avec la pièce d'identité",
+    vaccination: "Vaccination",
+    dose: "dose",
+    of: "sur",
+    recovery: "Rétablissement",
+    valid_from: "valide du",
+    valid_to: "au",
+    test: "Test",
+    disease: "maladie",
+    vaccine: "vaccin",
+    product: "produit",
+    manufacturer: "fabricant",
+    issuer: "émetteur",
+    identifier: "identifiant",
+    positive_on: "positif le",
+    test_name: "nom du test",
+    test_centre: "centre de test",
+    issued_by: "émis par",
+    issued_at: "le",
+    expiring_at: "expire le",
+    months: [
+        "janvier",
+        "février",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+    ],
+    day_suffix: "",
+    date_separator: '/',
+};
+
+#[test]
+fn width_test() {
+    // The English labels keep the widths they always had.
+    assert_eq!(EN.holder_width(), 16);
+    assert_eq!(EN.record_width(), 14);
+    assert_eq!(IT.holder_width(), 17);
+    assert_eq!(IT.record_width(), 16);
+    assert_eq!(Lang::from_name("fr").unwrap().labels().name, "nom");
+    assert!(Lang::from_name("es").is_none());
+}
@@ -0,0 +1,1041 @@
+#![forbid(unsafe_code)]
+
+use std::env;
+use std::error::Error;
+use std::io;
+#[cfg(feature = "corpus")]
+use std::path::Path;
+use std::process;
+use std::time::Duration;
+#[cfg(feature = "validity")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ciborium::value::Value;
+use eudcc_core::eudcc::{self, hex, Metadata};
+use eudcc_core::DecodeOptions;
+#[cfg(feature = "formats")]
+use eudcc_core::{Certificate, Payload};
+
+#[cfg(feature = "corpus")]
+mod analyze;
+mod color;
+#[cfg(feature = "corpus")]
+mod corpus;
+#[cfg(feature = "corpus")]
+mod crosscheck;
+#[cfg(feature = "formats")]
+mod csv;
+mod dates;
+mod diagnostics;
+#[cfg(feature = "validity")]
+mod evaluate;
+mod field;
+mod glob;
+#[cfg(feature = "formats")]
+mod html;
+mod http;
+mod human;
+mod input;
+mod json;
+mod key;
+mod lang;
+#[cfg(feature = "formats")]
+mod markdown;
+#[cfg(feature = "formats")]
+mod msgpack;
+mod name;
+mod output;
+#[cfg(feature = "formats")]
+mod pdf;
+mod pseudonym;
+#[cfg(feature = "corpus")]
+mod replay;
+#[cfg(feature = "formats")]
+mod report;
+mod schema;
+mod sink;
+#[cfg(feature = "formats")]
+mod template;
+mod timings;
+mod tree;
+#[cfg(feature = "formats")]
+mod xml;
+#[cfg(feature = "formats")]
+mod yaml;
+mod zone;
+
+use color::Palette;
+#[cfg(feature = "corpus")]
+use crosscheck::Divergences;
+use dates::{DateFormat, Dates};
+use diagnostics::{LogFormat, Summary};
+use input::{Input, Origin, Source};
+use lang::Lang;
+use name::IdName;
+use output::{sanitize, Encoding};
+use sink::Sink;
+use zone::TimeZone;
+
+const USAGE: &str = "\
+usage: eudccdec [decode] [options] [file...]
+       eudccdec inspect VIEW [options] [file...]
+       eudccdec encode [file...]
+       eudccdec render-pdf [options] [file...]
+       eudccdec analyze [options] <dir>
+       eudccdec replay [options] <dir>
+       eudccdec --help
+       eudccdec --schema
+
+commands:
+    decode                  print the certificate, the default
+    inspect VIEW            print a view of the certificate: --tree,
+                            --metadata, --validity, --name or --key
+    encode                  encode a COSE structure, like --output cose
+                            writes it, as an HC1 string
+    render-pdf              print the certificate as a PDF document
+    analyze <dir>           report the quirks of a corpus of certificates
+    replay <dir>            decode again the inputs saved in a directory
+
+Certificates are read from the files given, or from stdin without any or
+for -. Files can be given as glob patterns, like scans/**/*.txt, and the
+output of each one found by a pattern or --recursive names its path.
+
+options, which can go anywhere:
+    --output FORMAT         print the certificate as human (the default),
+                            debug, json, json-pretty, yaml, xml, csv,
+                            markdown, html, msgpack, cbor, the CWT
+                            payload, or cose, the whole COSE structure
+    --from hc1|cose         read the certificate as the HC1 string (the
+                            default) or as the COSE structure
+    --raw                   same as --output debug
+    --field PATH            only print the field at PATH, like nam.fn or
+                            v.0.dt; can be given more than once
+    --fields LIST           only write these fields in JSON and CSV
+                            output, like nam.fnt,dob,v.dt
+    --cross-check CMD       pipe every input to CMD, a reference decoder
+                            writing the certificate as JSON, and fail
+                            the inputs it decodes differently
+    --pseudonym-key FILE    write a pseudonym of the holder in JSON and
+                            CSV output, the HMAC-SHA-256 with the key in
+                            FILE of their names and date of birth
+    --template FILE         print the certificate through a template
+    --schema                print the JSON Schema of the JSON output
+    -o FILE                 write the output to FILE instead of stdout
+    --sink URI              write the output to URI: -, file:PATH or
+                            http://HOST[:PORT]/PATH
+    --http-timeout SECONDS  timeout of HTTP requests, 10 seconds by default
+    --http-retries N        how many times to retry failed HTTP
+                            connections, waiting twice as long every time;
+                            2 by default
+    --offline               forbid any network access, failing what needs it
+    --json                  same as --output json
+    --json-pretty           same as --output json-pretty
+    --accept-issuers LIST   only accept certificates issued by these
+                            comma separated countries
+    --reject-issuers LIST   reject certificates issued by these countries
+    --strict                check the certificate against its schema,
+                            reporting every violation
+    --id-name original|transliterated
+                            the name to compare against the photo ID
+    --lang en|it|de|fr      language of the labels of the human readable
+                            views, English by default
+    --batch                 read a certificate from every non-empty line of
+                            the inputs, reporting the errors of each one,
+                            then the totals
+    --fail-fast             stop at the first certificate failing, rather
+                            than going on with the others
+    -r, --recursive         decode every file in the directories given
+    --timings               report the time spent in each decoding stage
+    --date-format raw|iso|long|short
+                            how the human readable view writes dates:
+                            as in the certificate, as ISO 8601 (the
+                            default), or localized, like 10 April 2021 or
+                            10/04/2021
+    --utc, --local          write times in UTC (the default) or in the
+                            local time zone
+    --log-format text|json  format of the diagnostics written to stderr
+    --no-color              never color the output, which is otherwise
+                            colored on terminals unless NO_COLOR is set
+    --ascii                 transliterate non-ASCII characters
+    --escape-non-printable  escape non-ASCII and control characters";
+
+/// How the decoded certificate is printed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Human,
+    Debug,
+    Json,
+    JsonPretty,
+    #[cfg(feature = "formats")]
+    Yaml,
+    #[cfg(feature = "formats")]
+    Xml,
+    #[cfg(feature = "formats")]
+    Csv,
+    #[cfg(feature = "formats")]
+    MessagePack,
+    #[cfg(feature = "formats")]
+    Markdown,
+    #[cfg(feature = "formats")]
+    Html,
+    Cbor,
+    Cose,
+}
+
+impl Format {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "human" => Some(Format::Human),
+            "debug" => Some(Format::Debug),
+            "json" => Some(Format::Json),
+            "json-pretty" => Some(Format::JsonPretty),
+            #[cfg(feature = "formats")]
+            "yaml" => Some(Format::Yaml),
+            #[cfg(feature = "formats")]
+            "xml" => Some(Format::Xml),
+            #[cfg(feature = "formats")]
+            "csv" => Some(Format::Csv),
+            #[cfg(feature = "formats")]
+            "msgpack" => Some(Format::MessagePack),
+            #[cfg(feature = "formats")]
+            "markdown" => Some(Format::Markdown),
+            #[cfg(feature = "formats")]
+            "html" => Some(Format::Html),
+            "cbor" => Some(Format::Cbor),
+            "cose" => Some(Format::Cose),
+            _ => None,
+        }
+    }
+}
+
+/// Settings given by the command line options.
+struct Settings {
+    format: Format,
+    options: DecodeOptions,
+    id_name: IdName,
+    log_format: LogFormat,
+    input: Input,
+    lang: Lang,
+    fields: Vec<String>,
+    /// The only fields written in JSON and CSV output, when restricted.
+    allowed_fields: Option<Vec<String>>,
+    /// The key of the pseudonyms of holders, written in JSON and CSV
+    /// output when given.
+    pseudonym_key: Option<Vec<u8>>,
+    /// The command of a reference decoder to compare certificates with.
+    #[cfg(feature = "corpus")]
+    cross_check: Option<String>,
+    #[cfg(feature = "formats")]
+    template: Option<String>,
+    timings: bool,
+    palette: Palette,
+    dates: Dates,
+    encoding: Encoding,
+    /// Whether several certificates can be written, each one in a
+    /// document of its own for streams like YAML.
+    #[cfg(feature = "formats")]
+    several: bool,
+}
+
+/// What `run` prints: text, or binary data written as is, or a part of
+/// a document holding every certificate.
+enum Output {
+    Text(String),
+    Binary(Vec<u8>),
+    /// CSV rows, after the header if they are the first ones.
+    #[cfg(feature = "formats")]
+    Rows(String),
+    // There are none without the formats that have parts.
+    #[cfg_attr(not(feature = "formats"), allow(dead_code))]
+    Part(Box<Part>),
+}
+
+/// A certificate in a document holding every one of them, like an HTML
+/// report, which is only written once all of them are decoded.
+// Parts are boxed by Output, and kept as they are in the document.
+#[allow(clippy::large_enum_variant)]
+enum Part {
+    /// The path of the input if labelled, and the certificate.
+    #[cfg(feature = "formats")]
+    Html(Option<String>, Payload, Certificate),
+    /// The `eudcc` element of the certificate.
+    #[cfg(feature = "formats")]
+    Xml(Value),
+}
+
+/// The document holding every certificate, for the formats that can't
+/// just be concatenated.
+#[derive(Default)]
+struct Document {
+    /// Whether there can be several certificates, from several inputs or
+    /// in batch mode, rather than how many were decoded: the document
+    /// has the same shape whichever fail.
+    #[cfg(feature = "formats")]
+    several: bool,
+    /// Whether the CSV header was written, as it only heads the first rows.
+    #[cfg(feature = "formats")]
+    csv_header: bool,
+    #[cfg(feature = "formats")]
+    html: Vec<(Option<String>, Payload, Certificate)>,
+    #[cfg(feature = "formats")]
+    xml: Vec<Value>,
+}
+
+impl Document {
+    fn add(&mut self, part: Part) {
+        match part {
+            #[cfg(feature = "formats")]
+            Part::Html(path, payload, certificate) => {
+                self.html.push((path, payload, certificate))
+            }
+            #[cfg(feature = "formats")]
+            Part::Xml(element) => self.xml.push(element),
+        }
+    }
+
+    /// The document, if any certificate went in it.
+    fn finish(self) -> Option<String> {
+        #[cfg(feature = "formats")]
+        if !self.html.is_empty() {
+            return Some(html::render(&self.html));
+        }
+        #[cfg(feature = "formats")]
+        if let (false, [element]) = (self.several, &self.xml[..]) {
+            return Some(xml::to_string("eudcc", element));
+        }
+        // An eudccs root, holding an eudcc element for every certificate.
+        #[cfg(feature = "formats")]
+        if !self.xml.is_empty() {
+            let elements = Value::Array(self.xml);
+            let document = Value::Map(vec![("eudcc".into(), elements)]);
+            return Some(xml::to_string("eudccs", &document));
+        }
+
+        None
+    }
+}
+
+fn render_metadata(metadata: &Metadata) -> String {
+    let kid = match &metadata.kid {
+        Some(kid) => hex(kid),
+        None => "missing".to_string(),
+    };
+    let issuer = match eudcc::special_issuer(&metadata.issuer) {
+        Some(description) => format!("{} ({})", metadata.issuer, description),
+        None => sanitize(&metadata.issuer),
+    };
+
+    format!(
+        "issuer: {}\nissued at: {}\nexpires at: {}\nkid: {}\n",
+        issuer, metadata.issued_at, metadata.expires_at, kid
+    )
+}
+
+/// Split the command, with its arguments, from the inputs following it.
+fn split_command<'a>(args: &'a [&'a str]) -> (&'a [&'a str], &'a [&'a str]) {
+    let len = match args.first() {
+        Some(&"inspect") => 2,
+        Some(&"decode") | Some(&"encode") | Some(&"render-pdf") => 1,
+        // Their arguments are directories, not inputs.
+        Some(&"analyze") | Some(&"replay") => args.len(),
+        _ => 0,
+    };
+
+    args.split_at(len.min(args.len()))
+}
+
+/// Whether an argument looks like an option but is none, nor one of the
+/// views of inspect, rather than a file: `-` is stdin.
+fn is_unknown_option(arg: &str) -> bool {
+    const VIEWS: [&str; 5] =
+        ["--metadata", "--validity", "--name", "--key", "--tree"];
+
+    arg.starts_with('-') && arg != "-" && !VIEWS.contains(&arg)
+}
+
+/// Head the text a certificate printed with its path, if it has to be
+/// named: structured formats hold the path themselves instead.
+fn labelled(label: Option<&str>, output: String) -> String {
+    match label {
+        Some(label) => format!("==> {} <==\n{}", sanitize(label), output),
+        None => output,
+    }
+}
+
+fn run(
+    args: &[&str],
+    origin: Origin,
+    label: Option<&str>,
+    settings: &Settings,
+) -> Result<Output, Box<dyn Error>> {
+    let (format, options) = (settings.format, &settings.options);
+    let output = match args {
+        [] | ["decode"] => {
+            let source = Source::read(settings.input, origin)?;
+            let (payload, timings) = if settings.timings {
+                let (payload, timings) = timings::measure(&source, options)?;
+                (payload, Some(timings))
+            } else {
+                (source.payload(options)?, None)
+            };
+            let certificate = payload.certificate()?;
+            #[cfg(feature = "corpus")]
+            if let Some(command) = &settings.cross_check {
+                let reference = crosscheck::reference(command, source.bytes())?;
+                let ours = Value::serialized(&certificate)?;
+                let divergences = crosscheck::compare(&ours, &reference);
+                if !divergences.is_empty() {
+                    return Err(Box::new(Divergences(divergences)));
+                }
+            }
+            let pseudonym = settings
+                .pseudonym_key
+                .as_ref()
+                .map(|key| pseudonym::pseudonym(key, &certificate));
+            // In the JSON document itself, rather than next to it.
+            let json = matches!(format, Format::Json | Format::JsonPretty);
+            if let (Some(timings), false) = (&timings, json) {
+                eprintln!("{}", timings::format(settings.log_format, timings));
+            }
+            #[cfg(feature = "formats")]
+            if let Some(path) = &settings.template {
+                let context = field::context(&payload, &certificate)?;
+                let template = std::fs::read_to_string(path)?;
+                let output = template::render(&template, &context)?;
+                return Ok(Output::Text(labelled(label, output)));
+            }
+            if !settings.fields.is_empty() {
+                let context = field::context(&payload, &certificate)?;
+                let fields: Vec<&str> =
+                    settings.fields.iter().map(String::as_str).collect();
+                let output = field::render(&context, &fields)?;
+                return Ok(Output::Text(labelled(label, output)));
+            }
+            match format {
+                Format::Human => human::render(
+                    &payload,
+                    &certificate,
+                    settings.id_name,
+                    settings.palette,
+                    settings.lang.labels(),
+                    &settings.dates,
+                ),
+                Format::Debug => format!("{:#?}\n", certificate),
+                Format::Json | Format::JsonPretty => {
+                    let mut value = Value::serialized(&certificate)?;
+                    if let Some(fields) = &settings.allowed_fields {
+                        value = field::restrict(&value, fields);
+                    }
+                    // Versioned, so that consumers can tell which schema of
+                    // --schema the document follows.
+                    let version = schema::SCHEMA_VERSION.into();
+                    let version = ("schema_version".into(), version);
+                    // A header would make it no longer JSON: the path goes
+                    // next to the certificate instead, as do the timings.
+                    if label.is_some()
+                        || timings.is_some()
+                        || pseudonym.is_some()
+                    {
+                        let mut members = vec![version];
+                        if let Some(label) = label {
+                            members.push(("path".into(), label.into()));
+                        }
+                        members.push(("certificate".into(), value));
+                        if let Some(pseudonym) = pseudonym {
+                            members
+                                .push(("pseudonym".into(), pseudonym.into()));
+                        }
+                        if let Some(timings) = &timings {
+                            members
+                                .push(("timings".into(), timings.to_value()));
+                        }
+                        value = Value::Map(members);
+                    } else if let Value::Map(members) = &mut value {
+                        members.insert(0, version);
+                    }
+                    let pretty = format == Format::JsonPretty;
+                    let output = json::to_string(&value, pretty);
+                    return Ok(Output::Text(format!("{}\n", output)));
+                }
+                #[cfg(feature = "formats")]
+                Format::Yaml => {
+                    let mut value = Value::serialized(&certificate)?;
+                    // Like JSON, with every document of a stream started.
+                    if let Some(label) = label {
+                        value = Value::Map(vec![
+                            ("path".into(), label.into()),
+                            ("certificate".into(), value),
+                        ]);
+                    }
+                    let mut output = yaml::to_string(&value);
+                    if settings.several || label.is_some() {
+                        output.insert_str(0, "---\n");
+                    }
+                    return Ok(Output::Text(output));
+                }
+                #[cfg(feature = "formats")]
+                Format::Xml => {
+                    let claims = Value::Map(vec![
+                        ("issuer".into(), payload.issuer.as_str().into()),
+                        ("issued_at".into(), payload.issued_at.into()),
+                        ("expires_at".into(), payload.expires_at.into()),
+                    ]);
+                    let mut element = vec![
+                        ("payload".into(), claims),
+                        (
+                            "certificate".into(),
+                            Value::serialized(&certificate)?,
+                        ),
+                    ];
+                    if let Some(label) = label {
+                        element.insert(0, ("path".into(), label.into()));
+                    }
+                    let element = Value::Map(element);
+                    return Ok(Output::Part(Box::new(Part::Xml(element))));
+                }
+                #[cfg(feature = "formats")]
+                Format::Csv => {
+                    let fields = settings.allowed_fields.as_deref();
+                    let rows = csv::rows(
+                        &payload,
+                        &certificate,
+                        pseudonym.as_deref(),
+                        label,
+                        fields,
+                    );
+                    return Ok(Output::Rows(rows));
+                }
+                #[cfg(feature = "formats")]
+                Format::Markdown => {
+                    let output =
+                        markdown::render(&payload, &certificate, label);
+                    return Ok(Output::Text(output));
+                }
+                #[cfg(feature = "formats")]
+                Format::Html => {
+                    let path = label.map(str::to_string);
+                    let part = Part::Html(path, payload, certificate);
+                    return Ok(Output::Part(Box::new(part)));
+                }
+                #[cfg(feature = "formats")]
+                Format::MessagePack => {
+                    let value = Value::serialized(&certificate)?;
+                    return Ok(Output::Binary(msgpack::to_vec(&value)));
+                }
+                Format::Cbor => {
+                    // The claims map as it was signed, rather than as
+                    // re-encoded from the decoded payload.
+                    return Ok(Output::Binary(source.cose()?.payload));
+                }
+                Format::Cose => {
+                    return Ok(Output::Binary(source.cose_bytes()?));
+                }
+            }
+        }
+        #[cfg(feature = "encode")]
+        ["encode"] => {
+            let data = origin.read()?;
+            format!("{}\n", eudcc_core::encode_cose_bytes(&data)?)
+        }
+        ["inspect", "--metadata"] => {
+            let source = Source::read(settings.input, origin)?;
+            render_metadata(&source.metadata(options)?)
+        }
+        #[cfg(feature = "validity")]
+        ["inspect", "--validity"] => {
+            let certificate = Source::read(settings.input, origin)?
+                .payload(options)?
+                .certificate()?;
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+            evaluate::render(
+                &certificate,
+                now.as_secs() as i64,
+                settings.palette,
+            )?
+        }
+        ["inspect", "--name"] => {
+            let certificate = Source::read(settings.input, origin)?
+                .payload(options)?
+                .certificate()?;
+            name::render(
+                &certificate.nam,
+                settings.id_name,
+                settings.lang.labels(),
+            )
+        }
+        #[cfg(feature = "formats")]
+        ["render-pdf"] => {
+            let source = Source::read(settings.input, origin)?;
+            let payload = source.payload(options)?;
+            let certificate = payload.certificate()?;
+            return Ok(Output::Binary(pdf::render(&payload, &certificate)));
+        }
+        ["inspect", "--key"] => {
+            let cose = Source::read(settings.input, origin)?.cose()?;
+            let claims = eudcc::parse_hcert(&cose.payload)?;
+            key::stub(&cose, &claims.issuer)
+        }
+        ["inspect", "--tree"] => {
+            tree::render(&Source::read(settings.input, origin)?.cose()?)
+        }
+        #[cfg(feature = "corpus")]
+        ["analyze", dir] => analyze::analyze_dir(Path::new(dir))?.to_string(),
+        #[cfg(feature = "corpus")]
+        ["replay", dir] => replay::replay_dir(Path::new(dir))?.to_string(),
+        _ => return Err(USAGE.into()),
+    };
+
+    Ok(Output::Text(labelled(label, output)))
+}
+
+/// Run the command on a certificate and write what it prints, headed by
+/// its name if `labelled`, or add it to the `document`, or report its
+/// error naming the input, and tell whether it succeeded.
+fn write(
+    command: &[&str],
+    origin: Origin,
+    name: Option<&str>,
+    labelled: bool,
+    settings: &Settings,
+    sink: &mut dyn Sink,
+    document: &mut Document,
+) -> bool {
+    let label = if labelled { name } else { None };
+    let written = match run(command, origin, label, settings) {
+        Ok(Output::Text(output)) => write_text(&output, settings, sink),
+        Ok(Output::Binary(output)) => sink.write(&output),
+        #[cfg(feature = "formats")]
+        Ok(Output::Rows(rows)) if !document.csv_header => {
+            document.csv_header = true;
+            let fields = settings.allowed_fields.as_deref();
+            let pseudonym = settings.pseudonym_key.is_some();
+            let header = csv::header(pseudonym, labelled, fields);
+            write_text(&(header + &rows), settings, sink)
+        }
+        #[cfg(feature = "formats")]
+        Ok(Output::Rows(rows)) => write_text(&rows, settings, sink),
+        Ok(Output::Part(part)) => {
+            document.add(*part);
+            Ok(())
+        }
+        Err(error) => {
+            let format = settings.log_format;
+            eprintln!("{}", diagnostics::format_error(format, name, &*error));
+            return false;
+        }
+    };
+    check_written(written, settings);
+
+    true
+}
+
+fn write_text(
+    output: &str,
+    settings: &Settings,
+    sink: &mut dyn Sink,
+) -> io::Result<()> {
+    let output = output::encode(output, settings.encoding);
+    sink.write(output.as_bytes())
+}
+
+/// Report an error in the command line, like the other diagnostics, and
+/// exit with status 2.
+fn exit_usage(format: LogFormat, message: &str) -> ! {
+    let error: Box<dyn Error> = message.into();
+    eprintln!("{}", diagnostics::format_error(format, None, &*error));
+    process::exit(2);
+}
+
+/// Without a sink there is no point going on with the other inputs.
+fn check_written(written: io::Result<()>, settings: &Settings) {
+    if let Err(error) = written {
+        let format = settings.log_format;
+        eprintln!("{}", diagnostics::format_error(format, None, &error));
+        process::exit(1);
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    // Diagnostics always go to stderr, so stdout only carries the output.
+    let mut settings = Settings {
+        format: Format::Human,
+        options: DecodeOptions::new(),
+        id_name: IdName::Original,
+        log_format: LogFormat::Text,
+        input: Input::Hc1,
+        lang: Lang::En,
+        fields: Vec::new(),
+        allowed_fields: None,
+        pseudonym_key: None,
+        #[cfg(feature = "corpus")]
+        cross_check: None,
+        #[cfg(feature = "formats")]
+        template: None,
+        timings: false,
+        palette: Palette::new(false),
+        dates: Dates::default(),
+        encoding: Encoding::Utf8,
+        #[cfg(feature = "formats")]
+        several: false,
+    };
+    let mut batch = false;
+    let mut fail_fast = false;
+    let mut recursive = false;
+    let mut sink_uri = None;
+    let mut pseudonym_key = None;
+    let mut no_color = false;
+    let mut local = false;
+    let mut client = http::Client::new();
+    // What is not an option: the command, its arguments, and the views of
+    // inspect.
+    let mut positional = Vec::new();
+    // Reported once every option is read, as --log-format may come later.
+    let mut usage = false;
+    loop {
+        match args[..] {
+            ["--help", ..] | ["-h", ..] => {
+                println!("{}", USAGE);
+                return;
+            }
+            ["--schema", ..] => {
+                print!("{}", schema::SCHEMA);
+                return;
+            }
+            ["--output", name, ..] => {
+                match Format::from_name(name) {
+                    Some(format) => settings.format = format,
+                    None => usage = true,
+                }
+                args.drain(..2);
+            }
+            ["--from", name, ..] => {
+                match Input::from_name(name) {
+                    Some(input) => settings.input = input,
+                    None => usage = true,
+                }
+                args.drain(..2);
+            }
+            ["--fields", list, ..] => {
+                let fields = list.split(',').map(str::to_string).collect();
+                settings.allowed_fields = Some(fields);
+                args.drain(..2);
+            }
+            #[cfg(feature = "corpus")]
+            ["--cross-check", command, ..] => {
+                settings.cross_check = Some(command.to_string());
+                args.drain(..2);
+            }
+            ["--pseudonym-key", path, ..] => {
+                pseudonym_key = Some(path.to_string());
+                args.drain(..2);
+            }
+            ["--field", path, ..] => {
+                settings.fields.push(path.to_string());
+                args.drain(..2);
+            }
+            #[cfg(feature = "formats")]
+            ["--template", path, ..] => {
+                settings.template = Some(path.to_string());
+                args.drain(..2);
+            }
+            ["-o", path, ..] => {
+                sink_uri = Some(format!("file:{}", path));
+                args.drain(..2);
+            }
+            ["--sink", uri, ..] => {
+                sink_uri = Some(uri.to_string());
+                args.drain(..2);
+            }
+            ["--http-timeout", seconds, ..] => {
+                match seconds.parse() {
+                    Ok(seconds) => {
+                        client.timeout = Duration::from_secs(seconds)
+                    }
+                    Err(_) => usage = true,
+                }
+                args.drain(..2);
+            }
+            ["--offline", ..] => {
+                client.offline = true;
+                args.remove(0);
+            }
+            ["--http-retries", retries, ..] => {
+                match retries.parse() {
+                    Ok(retries) => client.retries = retries,
+                    Err(_) => usage = true,
+                }
+                args.drain(..2);
+            }
+            ["--raw", ..] => {
+                settings.format = Format::Debug;
+                args.remove(0);
+            }
+            ["--json", ..] => {
+                settings.format = Format::Json;
+                args.remove(0);
+            }
+            ["--json-pretty", ..] => {
+                settings.format = Format::JsonPretty;
+                args.remove(0);
+            }
+            ["--accept-issuers", list, ..] => {
+                let issuers: Vec<&str> = list.split(',').collect();
+                settings.options = settings.options.accept_issuers(&issuers);
+                args.drain(..2);
+            }
+            ["--reject-issuers", list, ..] => {
+                let issuers: Vec<&str> = list.split(',').collect();
+                settings.options = settings.options.reject_issuers(&issuers);
+                args.drain(..2);
+            }
+            ["--strict", ..] => {
+                settings.options = settings.options.strict(true);
+                args.remove(0);
+            }
+            ["--id-name", name, ..] => {
+                match IdName::from_name(name) {
+                    Some(name) => settings.id_name = name,
+                    None => usage = true,
+                }
+                args.drain(..2);
+            }
+            ["--lang", name, ..] => {
+                match Lang::from_name(name) {
+                    Some(lang) => settings.lang = lang,
+                    None => usage = true,
+                }
+                args.drain(..2);
+            }
+            ["--date-format", name, ..] => {
+                match DateFormat::from_name(name) {
+                    Some(format) => settings.dates.format = format,
+                    None => usage = true,
+                }
+                args.drain(..2);
+            }
+            ["--utc", ..] => {
+                local = false;
+                args.remove(0);
+            }
+            ["--local", ..] => {
+                local = true;
+                args.remove(0);
+            }
+            ["--log-format", name, ..] => {
+                match LogFormat::from_name(name) {
+                    Some(format) => settings.log_format = format,
+                    None => usage = true,
+                }
+                args.drain(..2);
+            }
+            ["--batch", ..] => {
+                batch = true;
+                args.remove(0);
+            }
+            ["--fail-fast", ..] => {
+                fail_fast = true;
+                args.remove(0);
+            }
+            ["--recursive", ..] | ["-r", ..] => {
+                recursive = true;
+                args.remove(0);
+            }
+            ["--timings", ..] => {
+                settings.timings = true;
+                args.remove(0);
+            }
+            ["--ascii", ..] => {
+                settings.encoding = Encoding::Ascii;
+                args.remove(0);
+            }
+            ["--no-color", ..] => {
+                no_color = true;
+                args.remove(0);
+            }
+            ["--escape-non-printable", ..] => {
+                settings.encoding = Encoding::EscapeNonPrintable;
+                args.remove(0);
+            }
+            [arg, ..] if is_unknown_option(arg) => {
+                usage = true;
+                args.remove(0);
+            }
+            [arg, ..] => {
+                positional.push(arg);
+                args.remove(0);
+            }
+            [] => break,
+        }
+    }
+    if usage {
+        exit_usage(settings.log_format, USAGE);
+    }
+    if let Some(path) = pseudonym_key {
+        match std::fs::read(&path) {
+            // Key files usually end with a newline that is no part of it.
+            Ok(mut key) => {
+                while matches!(key.last(), Some(b'\n' | b'\r')) {
+                    key.pop();
+                }
+                if key.is_empty() {
+                    exit_usage(
+                        settings.log_format,
+                        "the pseudonym key is empty",
+                    );
+                }
+                settings.pseudonym_key = Some(key);
+            }
+            Err(error) => {
+                let format = settings.log_format;
+                let error =
+                    diagnostics::format_error(format, Some(&path), &error);
+                eprintln!("{}", error);
+                process::exit(2);
+            }
+        }
+    }
+    if local {
+        // Without a readable time zone, timestamps stay in UTC.
+        settings.dates.zone = TimeZone::local();
+    }
+    let to_stdout = matches!(sink_uri.as_deref(), None | Some("-"));
+    // Escape sequences would be escaped themselves by the other encodings.
+    let color = !no_color && settings.encoding == Encoding::Utf8 && to_stdout;
+    settings.palette = Palette::new(color && color::detect());
+    let mut sink =
+        match sink::from_uri(sink_uri.as_deref().unwrap_or("-"), &client) {
+            Ok(sink) => sink,
+            Err(error) => exit_usage(settings.log_format, &error),
+        };
+
+    if batch && settings.input == Input::Cose {
+        let error = "--batch only reads HC1 strings, one a line";
+        exit_usage(settings.log_format, error);
+    }
+    let (command, inputs) = split_command(&positional);
+    let inputs = match inputs {
+        [] => &["-"][..],
+        inputs => inputs,
+    };
+    // Errors name their input, unless it is stdin alone.
+    let named = inputs != ["-"];
+    let format = settings.log_format;
+    let mut summary = Summary::default();
+    let mut paths = Vec::new();
+    for input in inputs {
+        match glob::expand(input, recursive) {
+            Ok(expanded) => paths.extend(expanded),
+            Err(error) => {
+                let error =
+                    diagnostics::format_error(format, Some(input), &error);
+                eprintln!("{}", error);
+                summary.count(false);
+            }
+        }
+    }
+    // A PDF document is a page, that can't hold several certificates.
+    let several = batch || paths.len() > 1;
+    if command == ["render-pdf"] && several {
+        exit_usage(format, "render-pdf only renders a single certificate");
+    }
+    // Outputs name where they come from when there can be several, and
+    // the files found, as they are not the ones given.
+    let labelled =
+        several || recursive || inputs.iter().any(|i| glob::is_pattern(i));
+    let mut document = Document::default();
+    #[cfg(feature = "formats")]
+    {
+        document.several = several;
+        settings.several = several;
+    }
+    'inputs: for path in &paths {
+        if fail_fast && summary.failed > 0 {
+            summary.stopped = true;
+            break;
+        }
+        let name = if named { Some(path.as_str()) } else { None };
+        if !batch {
+            let origin = Origin::Path(path);
+            summary.count(write(
+                command,
+                origin,
+                name,
+                labelled,
+                &settings,
+                &mut *sink,
+                &mut document,
+            ));
+            continue;
+        }
+
+        let text = match input::read_text(path) {
+            Ok(text) => text,
+            Err(error) => {
+                eprintln!(
+                    "{}",
+                    diagnostics::format_error(format, name, &error)
+                );
+                summary.count(false);
+                continue;
+            }
+        };
+        for (number, line) in input::lines(&text) {
+            if fail_fast && summary.failed > 0 {
+                summary.stopped = true;
+                break 'inputs;
+            }
+            let name = match name {
+                Some(path) => format!("{}:{}", path, number),
+                None => format!("line {}", number),
+            };
+            let origin = Origin::Line(line);
+            summary.count(write(
+                command,
+                origin,
+                Some(&name),
+                labelled,
+                &settings,
+                &mut *sink,
+                &mut document,
+            ));
+        }
+    }
+    if let Some(output) = document.finish() {
+        check_written(write_text(&output, &settings, &mut *sink), &settings);
+    }
+    if batch {
+        eprintln!("{}", diagnostics::format_summary(format, &summary));
+    }
+    if summary.failed > 0 {
+        process::exit(1);
+    }
+}
+
+#[test]
+fn is_unknown_option_test() {
+    assert!(is_unknown_option("--bogus"));
+    assert!(is_unknown_option("-x"));
+    assert!(!is_unknown_option("-"));
+    assert!(!is_unknown_option("--metadata"));
+    assert!(!is_unknown_option("--tree"));
+    assert!(!is_unknown_option("certificate.txt"));
+}
+
+#[test]
+fn split_command_test() {
+    let none: &[&str] = &[];
+    assert_eq!(split_command(&[]), (none, none));
+    assert_eq!(split_command(&["a.txt", "-"]), (none, &["a.txt", "-"][..]));
+    assert_eq!(
+        split_command(&["inspect", "--tree", "a.txt"]),
+        (&["inspect", "--tree"][..], &["a.txt"][..])
+    );
+    assert_eq!(split_command(&["inspect"]), (&["inspect"][..], none));
+    assert_eq!(
+        split_command(&["analyze", "dir"]),
+        (&["analyze", "dir"][..], none)
+    );
+}
@@ -0,0 +1,93 @@
+use std::fmt::Write;
+
+use eudcc_core::{Certificate, Payload};
+
+use crate::output::sanitize;
+use crate::report;
+
+/// Escape text for a table cell, where line breaks are not allowed.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in sanitize(text).chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// A Markdown document for the certificate, with a section for each
+/// table of its report, after the path of its input if given.
+pub fn render(
+    payload: &Payload,
+    certificate: &Certificate,
+    path: Option<&str>,
+) -> String {
+    let mut out = "# EU Digital COVID Certificate\n".to_string();
+    if let Some(path) = path {
+        let _ = writeln!(out, "\nPath: {}", escape(path));
+    }
+    for table in report::tables(payload, certificate) {
+        let _ = writeln!(out, "\n## {}\n", table.title);
+        let _ = writeln!(out, "| {} |", table.header.join(" | "));
+        let _ = writeln!(out, "|{}", "---|".repeat(table.header.len()));
+        for row in &table.rows {
+            let cells: Vec<String> = row.iter().map(|c| escape(c)).collect();
+            let _ = writeln!(out, "| {} |", cells.join(" | "));
+        }
+    }
+
+    out
+}
+
+#[test]
+fn render_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/2.json
+    // It is licensed under Apache-2.0 License.
+    let recovery_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *MEB2B2JJ59J-9BC6:X9NECX0AKQC:3DCV4*XUA2P-FHT-H4SI/J9WVHWVH+ZEOV1J$HNTICZUBOM*LP$V25$0Q:J40IA3L/*84-5%:C92JN*4CY0*%9F/8J2P4.818T+:IX3M3.96RPVD9J-OZT1-NT0 2$$0$2PZX69B9VCDHI2/T9TU1BPIJKH/T7B-S-*O/Y41FD+X49+5Z-6%.HDD8R6W1FDJGJSFJ/4Q:T0.KJTNP8EFULNC:HA0K5HKRB4TD85LOLF92GF.3O.Z8CC7-2FQYG$%21 2O*4R60NM8JI0EUGP$I/XK$M8ZQE6YB9M66P8N31I.ROSK%IA1Q2N53Q-OQ2VC6E26T11ROSNK5W-*H+MJ%0RGZVGWNURI75RBSQSHLH1JG*CMH2.-S$7VX6N*Z1881J7G.F9I+SV06F+1M*93%D";
+    let payload =
+        eudcc_core::decode_payload(recovery_data.to_string()).unwrap();
+    let mut certificate = payload.certificate().unwrap();
+    certificate.nam.fnt = "DI<CAPRIO|X".to_string();
+
+    assert_eq!(
+        render(&payload, &certificate, None),
+        "# EU Digital COVID Certificate
+
+## Holder
+
+| Field | Value |
+|---|---|
+| Surname | Di Caprio |
+| Forename | Marilù Teresa |
+| Standardised surname | DI\\<CAPRIO\\|X |
+| Standardised forename | MARILU\\<TERESA |
+| Date of birth | 1977-06-16 |
+
+## Payload
+
+| Claim | Value |
+|---|---|
+| Issuer | IT |
+| Issued at | 1621593223 |
+| Expires at | 1637148823 |
+
+## Recoveries
+
+| Positive on | Valid from | Valid until | Country | Disease | Issuer | Identifier |
+|---|---|---|---|---|---|---|
+| 2021-05-02 | 2021-05-04 | 2021-10-31 | Italy | COVID-19 | IT | 01ITA65E2BD36C9E4900B0273D2E7C92EEB9#1 |
+"
+    );
+    let out = render(&payload, &certificate, Some("scans/a_b.txt"));
+    assert!(out.starts_with(
+        "# EU Digital COVID Certificate\n\nPath: scans/a\\_b.txt\n\n## Holder"
+    ));
+}
@@ -0,0 +1,166 @@
+use ciborium::value::Value;
+
+/// The types of a string, binary, array or map header: the fixed one for
+/// short lengths, with its maximum length, and the 8, 16 and 32 bits ones.
+struct Header {
+    fixed: Option<(u8, usize)>,
+    len8: Option<u8>,
+    len16: u8,
+    len32: u8,
+}
+
+const STR: Header = Header {
+    fixed: Some((0xa0, 31)),
+    len8: Some(0xd9),
+    len16: 0xda,
+    len32: 0xdb,
+};
+const BIN: Header = Header {
+    fixed: None,
+    len8: Some(0xc4),
+    len16: 0xc5,
+    len32: 0xc6,
+};
+const ARRAY: Header = Header {
+    fixed: Some((0x90, 15)),
+    len8: None,
+    len16: 0xdc,
+    len32: 0xdd,
+};
+const MAP: Header = Header {
+    fixed: Some((0x80, 15)),
+    len8: None,
+    len16: 0xde,
+    len32: 0xdf,
+};
+
+fn write_header(out: &mut Vec<u8>, header: &Header, len: usize) {
+    match (header.fixed, header.len8) {
+        (Some((marker, max)), _) if len <= max => out.push(marker | len as u8),
+        (_, Some(marker)) if len <= u8::MAX as usize => {
+            out.push(marker);
+            out.push(len as u8);
+        }
+        _ if len <= u16::MAX as usize => {
+            out.push(header.len16);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        _ => {
+            out.push(header.len32);
+            out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+}
+
+fn write_integer(out: &mut Vec<u8>, i: i128) {
+    if let Ok(i) = u64::try_from(i) {
+        match i {
+            0..=0x7f => out.push(i as u8),
+            0x80..=0xff => out.extend_from_slice(&[0xcc, i as u8]),
+            0x100..=0xffff => {
+                out.push(0xcd);
+                out.extend_from_slice(&(i as u16).to_be_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                out.push(0xce);
+                out.extend_from_slice(&(i as u32).to_be_bytes());
+            }
+            _ => {
+                out.push(0xcf);
+                out.extend_from_slice(&i.to_be_bytes());
+            }
+        }
+    } else if let Ok(i) = i64::try_from(i) {
+        match i {
+            -32..=-1 => out.push(i as u8),
+            -0x80..=-33 => out.extend_from_slice(&[0xd0, i as u8]),
+            -0x8000..=-0x81 => {
+                out.push(0xd1);
+                out.extend_from_slice(&(i as i16).to_be_bytes());
+            }
+            -0x8000_0000..=-0x8001 => {
+                out.push(0xd2);
+                out.extend_from_slice(&(i as i32).to_be_bytes());
+            }
+            _ => {
+                out.push(0xd3);
+                out.extend_from_slice(&i.to_be_bytes());
+            }
+        }
+    } else {
+        // CBOR negative integers go down to -2^64, beyond what MessagePack
+        // integers can hold.
+        write_float(out, i as f64);
+    }
+}
+
+fn write_float(out: &mut Vec<u8>, f: f64) {
+    out.push(0xcb);
+    out.extend_from_slice(&f.to_be_bytes());
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => out.push(0xc0),
+        Value::Bool(b) => out.push(if *b { 0xc3 } else { 0xc2 }),
+        Value::Integer(i) => write_integer(out, i128::from(*i)),
+        Value::Float(f) => write_float(out, *f),
+        Value::Text(text) => {
+            write_header(out, &STR, text.len());
+            out.extend_from_slice(text.as_bytes());
+        }
+        Value::Bytes(bytes) => {
+            write_header(out, &BIN, bytes.len());
+            out.extend_from_slice(bytes);
+        }
+        // Tags have no MessagePack counterpart: write the tagged value.
+        Value::Tag(_, value) => write_value(out, value),
+        Value::Array(items) => {
+            write_header(out, &ARRAY, items.len());
+            for item in items {
+                write_value(out, item);
+            }
+        }
+        Value::Map(entries) => {
+            write_header(out, &MAP, entries.len());
+            for (k, v) in entries {
+                write_value(out, k);
+                write_value(out, v);
+            }
+        }
+        _ => out.push(0xc0),
+    }
+}
+
+/// Write a CBOR value as MessagePack.
+pub fn to_vec(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_value(&mut out, value);
+
+    out
+}
+
+#[test]
+fn msgpack_test() {
+    let value = Value::Map(vec![
+        ("dn".into(), 2.into()),
+        ("sd".into(), Value::from(-260)),
+        ("raw".into(), Value::Bytes(vec![0xde, 0xad])),
+        ("v".into(), Value::Array(vec![Value::Null, true.into()])),
+    ]);
+    assert_eq!(
+        to_vec(&value),
+        [
+            0x84, 0xa2, b'd', b'n', 0x02, 0xa2, b's', b'd', 0xd1, 0xfe, 0xfc,
+            0xa3, b'r', b'a', b'w', 0xc4, 0x02, 0xde, 0xad, 0xa1, b'v', 0x92,
+            0xc0, 0xc3,
+        ]
+    );
+
+    assert_eq!(to_vec(&Value::from(-1)), [0xff]);
+    assert_eq!(to_vec(&Value::from(200)), [0xcc, 200]);
+    assert_eq!(to_vec(&Value::from(u64::MAX))[0], 0xcf);
+    assert_eq!(to_vec(&Value::Text("a".repeat(32)))[..2], [0xd9, 32]);
+    let items = Value::Array(vec![Value::Null; 16]);
+    assert_eq!(to_vec(&items)[..3], [0xdc, 0, 16]);
+}
@@ -0,0 +1,97 @@
+use eudcc_core::Name;
+
+use crate::lang::Labels;
+use crate::output::sanitize;
+
+/// Which form of the name the operator compares against the photo ID.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IdName {
+    /// The name as written in the holder's language.
+    Original,
+    /// The ICAO 9303 transliteration, as in the machine readable zone.
+    Transliterated,
+}
+
+impl IdName {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "original" => Some(IdName::Original),
+            "transliterated" => Some(IdName::Transliterated),
+            _ => None,
+        }
+    }
+}
+
+/// Join surname and forename, either of which can be missing.
+fn full_name(surname: &str, forename: &str) -> String {
+    match (surname.is_empty(), forename.is_empty()) {
+        (false, false) => format!("{}, {}", surname, forename),
+        (false, true) => surname.to_string(),
+        _ => forename.to_string(),
+    }
+}
+
+/// The name and its transliteration, one above the other, with a marker
+/// next to the one to compare against the photo ID.
+pub fn render(name: &Name, id_name: IdName, labels: &Labels) -> String {
+    // ICAO 9303 uses `<` in place of spaces.
+    let transliterated =
+        full_name(&name.fnt.replace('<', " "), &name.gnt.replace('<', " "));
+    let marker = format!("  <- {}", labels.compare_with_id);
+    let (original_marker, transliterated_marker) = match id_name {
+        IdName::Original => (marker.as_str(), ""),
+        IdName::Transliterated => ("", marker.as_str()),
+    };
+    let width = labels.holder_width();
+
+    format!(
+        "{:<w$}{}{}\n{:<w$}{}{}\n",
+        format!("{}:", labels.name),
+        sanitize(&full_name(&name.fn_, &name.gn)),
+        original_marker,
+        format!("{}:", labels.transliterated),
+        sanitize(&transliterated),
+        transliterated_marker,
+        w = width
+    )
+}
+
+#[test]
+fn render_test() {
+    use crate::lang::Lang;
+
+    let en = Lang::En.labels();
+    let name = Name {
+        fn_: "Di Caprio".to_string(),
+        fnt: "DI<CAPRIO".to_string(),
+        gn: "Marilù Teresa".to_string(),
+        gnt: "MARILU<TERESA".to_string(),
+    };
+
+    assert_eq!(
+        render(&name, IdName::Original, en),
+        "name:           Di Caprio, Marilù Teresa  <- compare with the ID
+transliterated: DI CAPRIO, MARILU TERESA
+"
+    );
+    assert_eq!(
+        render(&name, IdName::Transliterated, en),
+        "name:           Di Caprio, Marilù Teresa
+transliterated: DI CAPRIO, MARILU TERESA  <- compare with the ID
+"
+    );
+
+    let name = Name {
+        gn: String::new(),
+        gnt: String::new(),
+        ..name
+    };
+    assert!(render(&name, IdName::Original, en)
+        .starts_with("name:           Di Caprio  <-"));
+    assert_eq!(
+        render(&name, IdName::Original, Lang::It.labels()),
+        "nome:            Di Caprio  <- confrontare con il documento
+traslitterato:   DI CAPRIO
+"
+    );
+}
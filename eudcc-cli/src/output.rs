@@ -0,0 +1,154 @@
+use std::fmt::Write;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// How characters that may be unsafe on a terminal are written out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    /// Write the output as it is.
+    Utf8,
+    /// Transliterate non-ASCII characters and escape control characters.
+    Ascii,
+    /// Escape both non-ASCII and control characters.
+    EscapeNonPrintable,
+}
+
+// Latin-1 Supplement and Latin Extended-A letters, starting at U+00C0.
+const TRANSLITERATIONS: &[&str] = &[
+    "A", "A", "A", "A", "A", "A", "AE", "C", "E", "E", "E", "E", "I", "I", "I",
+    "I", "D", "N", "O", "O", "O", "O", "O", "x", "O", "U", "U", "U", "U", "Y",
+    "TH", "ss", "a", "a", "a", "a", "a", "a", "ae", "c", "e", "e", "e", "e",
+    "i", "i", "i", "i", "d", "n", "o", "o", "o", "o", "o", "/", "o", "u", "u",
+    "u", "u", "y", "th", "y", "A", "a", "A", "a", "A", "a", "C", "c", "C", "c",
+    "C", "c", "C", "c", "D", "d", "D", "d", "E", "e", "E", "e", "E", "e", "E",
+    "e", "E", "e", "G", "g", "G", "g", "G", "g", "G", "g", "H", "h", "H", "h",
+    "I", "i", "I", "i", "I", "i", "I", "i", "I", "i", "IJ", "ij", "J", "j",
+    "K", "k", "k", "L", "l", "L", "l", "L", "l", "L", "l", "L", "l", "N", "n",
+    "N", "n", "N", "n", "n", "N", "n", "O", "o", "O", "o", "O", "o", "OE",
+    "oe", "R", "r", "R", "r", "R", "r", "S", "s", "S", "s", "S", "s", "S", "s",
+    "T", "t", "T", "t", "T", "t", "U", "u", "U", "u", "U", "u", "U", "u", "U",
+    "u", "U", "u", "W", "w", "Y", "y", "Y", "Z", "z", "Z", "z", "Z", "z", "s",
+];
+const TRANSLITERATIONS_START: u32 = 0xc0;
+
+/// ASCII version of the box drawing characters used by the tree view, and
+/// of the dashes used by the human readable one.
+fn box_drawing(c: char) -> Option<char> {
+    match c {
+        '├' | '│' => Some('|'),
+        '└' => Some('`'),
+        '─' | '—' => Some('-'),
+        _ => None,
+    }
+}
+
+fn transliterate(c: char) -> Option<&'static str> {
+    let index = (c as u32).checked_sub(TRANSLITERATIONS_START)?;
+    TRANSLITERATIONS.get(index as usize).copied()
+}
+
+fn escape(out: &mut String, c: char) {
+    let _ = write!(out, "\\u{{{:x}}}", c as u32);
+}
+
+/// Encode some output text, always keeping line breaks as they are.
+pub fn encode(text: &str, encoding: Encoding) -> String {
+    if encoding == Encoding::Utf8 {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\n' => out.push(c),
+            c if c.is_control() => escape(&mut out, c),
+            c if c.is_ascii() => out.push(c),
+            c if box_drawing(c).is_some() => out.extend(box_drawing(c)),
+            c if encoding == Encoding::Ascii => match transliterate(c) {
+                Some(ascii) => out.push_str(ascii),
+                None => out.push('?'),
+            },
+            c => escape(&mut out, c),
+        }
+    }
+
+    out
+}
+
+/// Skip a control sequence: parameter and intermediate bytes, then the
+/// final byte.
+fn skip_control_sequence(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| ('\x20'..='\x3f').contains(c)).is_some() {}
+    chars.next_if(|c| ('\x40'..='\x7e').contains(c));
+}
+
+/// Skip a control string (OSC, DCS...) up to its terminator.
+fn skip_control_string(chars: &mut Peekable<Chars>) {
+    while let Some(c) = chars.next() {
+        match c {
+            '\x07' | '\u{9c}' => break,
+            '\x1b' if chars.next_if_eq(&'\\').is_some() => break,
+            _ => {}
+        }
+    }
+}
+
+/// Strip ANSI escape sequences and control characters from a decoded
+/// string, since it comes from untrusted input and would otherwise reach
+/// the terminal as it is.
+pub fn sanitize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                Some('[') => skip_control_sequence(&mut chars),
+                Some(']' | 'P' | 'X' | '^' | '_') => {
+                    skip_control_string(&mut chars)
+                }
+                // Any other escape sequence is two characters long.
+                _ => {}
+            },
+            '\u{9b}' => skip_control_sequence(&mut chars),
+            '\u{90}' | '\u{98}' | '\u{9d}' | '\u{9e}' | '\u{9f}' => {
+                skip_control_string(&mut chars)
+            }
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+#[test]
+fn sanitize_test() {
+    assert_eq!(sanitize("Marilù Teresa"), "Marilù Teresa");
+    assert_eq!(sanitize("Di\x1b[2J\x1b[1;31mCaprio\x1b[0m"), "DiCaprio");
+    assert_eq!(sanitize("IT\x1b]0;pwned\x07"), "IT");
+    assert_eq!(sanitize("IT\x1b]8;;http://x\x1b\\link"), "ITlink");
+    assert_eq!(sanitize("I\nT\r\x08\u{9b}2J"), "IT");
+    assert_eq!(sanitize("IT\x1bc"), "IT");
+}
+
+#[test]
+fn encode_test() {
+    let text = "Marilù Teresa\nŁukasz Øster\x1b[2J 東";
+
+    assert_eq!(encode(text, Encoding::Utf8), text);
+    assert_eq!(
+        encode(text, Encoding::Ascii),
+        "Marilu Teresa\nLukasz Oster\\u{1b}[2J ?"
+    );
+    assert_eq!(
+        encode(text, Encoding::EscapeNonPrintable),
+        "Maril\\u{f9} Teresa\n\\u{141}ukasz \\u{d8}ster\\u{1b}[2J \\u{6771}"
+    );
+
+    let tree = "├── v\n│   └── [0]";
+    assert_eq!(encode(tree, Encoding::Ascii), "|-- v\n|   `-- [0]");
+    assert_eq!(
+        encode(tree, Encoding::EscapeNonPrintable),
+        "|-- v\n|   `-- [0]"
+    );
+}
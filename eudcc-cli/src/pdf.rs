@@ -0,0 +1,221 @@
+use std::fmt::Write;
+
+use eudcc_core::{Certificate, Payload};
+
+use crate::output::sanitize;
+use crate::report;
+
+// A4, in points.
+const PAGE_WIDTH: u32 = 595;
+const PAGE_HEIGHT: u32 = 842;
+const MARGIN: u32 = 56;
+const TITLE_SIZE: u32 = 18;
+const HEADING_SIZE: u32 = 13;
+const TEXT_SIZE: u32 = 10;
+
+// The first objects, every page comes after them with its content
+// stream.
+const CATALOG: usize = 1;
+const PAGES: usize = 2;
+const FONT: usize = 3;
+const BOLD_FONT: usize = 4;
+const FIRST_PAGE: usize = 5;
+
+struct Line {
+    bold: bool,
+    size: u32,
+    // Space before the line, in points.
+    space: u32,
+    text: String,
+}
+
+impl Line {
+    fn new(bold: bool, size: u32, space: u32, text: String) -> Self {
+        Line {
+            bold,
+            size,
+            space,
+            text,
+        }
+    }
+}
+
+/// The lines of the document: a title, then the tables of the report,
+/// one field a line.
+fn lines(payload: &Payload, certificate: &Certificate) -> Vec<Line> {
+    let title = "EU Digital COVID Certificate".to_string();
+    let mut lines = vec![Line::new(true, TITLE_SIZE, 0, title)];
+    for table in report::tables(payload, certificate) {
+        let heading = table.title.to_string();
+        lines.push(Line::new(true, HEADING_SIZE, 16, heading));
+        for (i, row) in table.rows.iter().enumerate() {
+            match row.as_slice() {
+                // Tables of fields have them in the first column.
+                [field, value] if table.header.len() == 2 => {
+                    let text = format!("{}: {}", field, value);
+                    lines.push(Line::new(false, TEXT_SIZE, 4, text));
+                }
+                // Tables of records have a block of lines each.
+                cells => {
+                    let space = if i == 0 { 4 } else { 10 };
+                    let fields = table.header.iter().zip(cells).enumerate();
+                    for (j, (column, cell)) in fields {
+                        let text = format!("{}: {}", column, cell);
+                        let space = if j == 0 { space } else { 4 };
+                        lines.push(Line::new(false, TEXT_SIZE, space, text));
+                    }
+                }
+            }
+        }
+    }
+
+    lines
+}
+
+/// Encode text as a PDF string of the standard fonts, in WinAnsiEncoding,
+/// which matches Latin-1 for the letters certificates use.
+fn string(text: &str) -> Vec<u8> {
+    let mut out = vec![b'('];
+    for c in sanitize(text).chars() {
+        match c {
+            '(' | ')' | '\\' => out.extend_from_slice(&[b'\\', c as u8]),
+            ' '..='~' | '\u{a0}'..='\u{ff}' => out.push(c as u32 as u8),
+            _ => out.push(b'?'),
+        }
+    }
+    out.push(b')');
+
+    out
+}
+
+/// Lay out the lines over as many pages as needed, as content streams.
+fn pages(lines: &[Line]) -> Vec<Vec<u8>> {
+    let mut pages = Vec::new();
+    let mut content = Vec::new();
+    let mut y = PAGE_HEIGHT - MARGIN;
+    for line in lines {
+        let height = line.space + line.size;
+        if y < MARGIN + height && !content.is_empty() {
+            pages.push(content);
+            content = Vec::new();
+            y = PAGE_HEIGHT - MARGIN;
+        }
+        y -= height;
+        let font = if line.bold { "F2" } else { "F1" };
+        let start =
+            format!("BT /{} {} Tf {} {} Td ", font, line.size, MARGIN, y);
+        content.extend_from_slice(start.as_bytes());
+        content.extend_from_slice(&string(&line.text));
+        content.extend_from_slice(b" Tj ET\n");
+    }
+    pages.push(content);
+
+    pages
+}
+
+fn write_object(out: &mut Vec<u8>, offsets: &mut Vec<usize>, body: &[u8]) {
+    offsets.push(out.len());
+    out.extend_from_slice(format!("{} 0 obj\n", offsets.len()).as_bytes());
+    out.extend_from_slice(body);
+    out.extend_from_slice(b"\nendobj\n");
+}
+
+/// Render the certificate as an A4 PDF document.
+pub fn render(payload: &Payload, certificate: &Certificate) -> Vec<u8> {
+    let pages = pages(&lines(payload, certificate));
+    let page = |i: usize| FIRST_PAGE + 2 * i;
+
+    let mut out = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    let catalog = format!("<< /Type /Catalog /Pages {} 0 R >>", PAGES);
+    write_object(&mut out, &mut offsets, catalog.as_bytes());
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", page(i)))
+        .collect();
+    let tree = format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        kids.join(" "),
+        pages.len()
+    );
+    write_object(&mut out, &mut offsets, tree.as_bytes());
+    for name in ["Helvetica", "Helvetica-Bold"] {
+        let font = format!(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /{} \
+             /Encoding /WinAnsiEncoding >>",
+            name
+        );
+        write_object(&mut out, &mut offsets, font.as_bytes());
+    }
+    for (i, content) in pages.iter().enumerate() {
+        let page = format!(
+            "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 {} 0 R /F2 {} 0 R >> >> \
+             /Contents {} 0 R >>",
+            PAGES,
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            FONT,
+            BOLD_FONT,
+            page(i) + 1
+        );
+        write_object(&mut out, &mut offsets, page.as_bytes());
+        let mut stream =
+            format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend_from_slice(content);
+        stream.extend_from_slice(b"endstream");
+        write_object(&mut out, &mut offsets, &stream);
+    }
+
+    let xref = out.len();
+    let mut table =
+        format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+    for offset in &offsets {
+        let _ = writeln!(table, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        table,
+        "trailer\n<< /Size {} /Root {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+        offsets.len() + 1,
+        CATALOG,
+        xref
+    );
+    out.extend_from_slice(table.as_bytes());
+
+    out
+}
+
+#[test]
+fn render_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/2.json
+    // It is licensed under Apache-2.0 License.
+    let recovery_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *MEB2B2JJ59J-9BC6:X9NECX0AKQC:3DCV4*XUA2P-FHT-H4SI/J9WVHWVH+ZEOV1J$HNTICZUBOM*LP$V25$0Q:J40IA3L/*84-5%:C92JN*4CY0*%9F/8J2P4.818T+:IX3M3.96RPVD9J-OZT1-NT0 2$$0$2PZX69B9VCDHI2/T9TU1BPIJKH/T7B-S-*O/Y41FD+X49+5Z-6%.HDD8R6W1FDJGJSFJ/4Q:T0.KJTNP8EFULNC:HA0K5HKRB4TD85LOLF92GF.3O.Z8CC7-2FQYG$%21 2O*4R60NM8JI0EUGP$I/XK$M8ZQE6YB9M66P8N31I.ROSK%IA1Q2N53Q-OQ2VC6E26T11ROSNK5W-*H+MJ%0RGZVGWNURI75RBSQSHLH1JG*CMH2.-S$7VX6N*Z1881J7G.F9I+SV06F+1M*93%D";
+    let payload =
+        eudcc_core::decode_payload(recovery_data.to_string()).unwrap();
+    let certificate = payload.certificate().unwrap();
+
+    let pdf = render(&payload, &certificate);
+    assert!(pdf.starts_with(b"%PDF-1.4\n"));
+    assert!(pdf.ends_with(b"%%EOF\n"));
+    assert!(pdf.windows(16).any(|w| w == b"(Forename: Maril"));
+    assert!(pdf.windows(5).any(|w| w == b"\xf9 Ter"));
+
+    // Every offset of the cross-reference table points at its object.
+    let text = String::from_utf8_lossy(&pdf);
+    let xref = text.rfind("xref\n").unwrap();
+    let entries = text[xref..]
+        .lines()
+        .skip(3)
+        .take_while(|l| l.ends_with(" n "));
+    for (i, entry) in entries.enumerate() {
+        let offset: usize = entry[..10].parse().unwrap();
+        let object = format!("{} 0 obj\n", i + 1);
+        assert!(pdf[offset..].starts_with(object.as_bytes()));
+    }
+
+    assert_eq!(string("a (b) \\ ù ✓"), b"(a \\(b\\) \\\\ \xf9 ?)");
+    let many: Vec<Line> = (0..100)
+        .map(|_| Line::new(false, TEXT_SIZE, 4, String::new()))
+        .collect();
+    assert_eq!(pages(&many).len(), 2);
+}
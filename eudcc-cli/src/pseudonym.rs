@@ -0,0 +1,58 @@
+use eudcc_core::eudcc::hex;
+use eudcc_core::{hmac_sha256, Certificate};
+
+/// A standardised name as ICAO 9303 writes it, whatever the issuer made of
+/// the separators: upper case, with single `<` between its parts.
+fn normalize(name: &str) -> String {
+    let upper = name.to_uppercase();
+    let parts: Vec<&str> = upper
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    parts.join("<")
+}
+
+/// A pseudonymous identifier of the holder: the HMAC-SHA-256, with `key`,
+/// of the standardised names and the date of birth, so that the same
+/// person gets the same one in every certificate, and only those holding
+/// the key can tell who it is.
+pub fn pseudonym(key: &[u8], certificate: &Certificate) -> String {
+    let holder = format!(
+        "{}\n{}\n{}",
+        normalize(&certificate.nam.fnt),
+        normalize(&certificate.nam.gnt),
+        certificate.dob.trim()
+    );
+
+    hex(&hmac_sha256(key, holder.as_bytes()))
+}
+
+#[test]
+fn pseudonym_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/2.json
+    // It is licensed under Apache-2.0 License.
+    let recovery_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *MEB2B2JJ59J-9BC6:X9NECX0AKQC:3DCV4*XUA2P-FHT-H4SI/J9WVHWVH+ZEOV1J$HNTICZUBOM*LP$V25$0Q:J40IA3L/*84-5%:C92JN*4CY0*%9F/8J2P4.818T+:IX3M3.96RPVD9J-OZT1-NT0 2$$0$2PZX69B9VCDHI2/T9TU1BPIJKH/T7B-S-*O/Y41FD+X49+5Z-6%.HDD8R6W1FDJGJSFJ/4Q:T0.KJTNP8EFULNC:HA0K5HKRB4TD85LOLF92GF.3O.Z8CC7-2FQYG$%21 2O*4R60NM8JI0EUGP$I/XK$M8ZQE6YB9M66P8N31I.ROSK%IA1Q2N53Q-OQ2VC6E26T11ROSNK5W-*H+MJ%0RGZVGWNURI75RBSQSHLH1JG*CMH2.-S$7VX6N*Z1881J7G.F9I+SV06F+1M*93%D";
+    let mut certificate =
+        eudcc_core::decode(recovery_data.to_string()).unwrap();
+
+    let id = pseudonym(b"secret", &certificate);
+    assert_eq!(id.len(), 64);
+    assert_eq!(
+        id,
+        hex(&hmac_sha256(
+            b"secret",
+            b"DI<CAPRIO\nMARILU<TERESA\n1977-06-16"
+        ))
+    );
+    assert_ne!(id, pseudonym(b"other secret", &certificate));
+
+    // Only the standardised names count, however they are separated.
+    certificate.nam.fn_ = "Di Caprio Jr.".to_string();
+    certificate.nam.fnt = "di caprio".to_string();
+    certificate.nam.gnt = "MARILU<<TERESA<".to_string();
+    assert_eq!(pseudonym(b"secret", &certificate), id);
+    certificate.dob = "1977-06-17".to_string();
+    assert_ne!(pseudonym(b"secret", &certificate), id);
+}
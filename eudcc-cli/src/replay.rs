@@ -0,0 +1,108 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::{Path, PathBuf};
+
+use crate::corpus;
+use crate::output::sanitize;
+use eudcc_core::eudcc::decode;
+
+/// What happened when running an input through the decoder.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Success,
+    Error(String),
+    Panic(String),
+}
+
+/// Outcomes of replaying every file of a directory.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub outcomes: Vec<(PathBuf, Outcome)>,
+}
+
+/// Run some input through the whole decoding pipeline, catching panics.
+pub fn replay(data: Vec<u8>) -> Outcome {
+    let result = panic::catch_unwind(|| -> Result<_, Box<dyn Error>> {
+        let data = String::from_utf8(data)?;
+        Ok(decode(data)?)
+    });
+
+    match result {
+        Ok(Ok(_)) => Outcome::Success,
+        Ok(Err(e)) => Outcome::Error(e.to_string()),
+        Err(payload) => {
+            let message = match payload.downcast_ref::<&str>() {
+                Some(message) => message.to_string(),
+                None => match payload.downcast_ref::<String>() {
+                    Some(message) => message.clone(),
+                    None => "unknown panic".to_string(),
+                },
+            };
+            Outcome::Panic(message)
+        }
+    }
+}
+
+/// Replay every file in a directory, each one holding a single input.
+pub fn replay_dir(dir: &Path) -> io::Result<Report> {
+    let files = corpus::files(dir)?;
+
+    // Panics are reported per file, don't let the default hook print them
+    // as they happen.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let mut report = Report::default();
+    for path in files {
+        let outcome = match fs::read(&path) {
+            Ok(data) => replay(data),
+            Err(e) => Outcome::Error(e.to_string()),
+        };
+        report.outcomes.push((path, outcome));
+    }
+
+    panic::set_hook(hook);
+
+    Ok(report)
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (mut successes, mut errors, mut panics) = (0, 0, 0);
+        for (path, outcome) in &self.outcomes {
+            match outcome {
+                Outcome::Success => {
+                    successes += 1;
+                    writeln!(f, "{}: ok", path.display())?;
+                }
+                Outcome::Error(e) => {
+                    errors += 1;
+                    writeln!(f, "{}: error: {}", path.display(), sanitize(e))?;
+                }
+                Outcome::Panic(e) => {
+                    panics += 1;
+                    writeln!(f, "{}: panic: {}", path.display(), sanitize(e))?;
+                }
+            }
+        }
+        writeln!(f, "{} ok, {} errors, {} panics", successes, errors, panics)
+    }
+}
+
+#[test]
+fn replay_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/1.json
+    // It is licensed under Apache-2.0 License.
+    let vaccination_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II5XHC9B5G2+$N IOP-IA%NFQGRJPC%OQHIZC4.OI1RM8ZA.A5:S9MKN4NN3F85QNCY0O%0VZ001HOC9JU0D0HT0HB2PL/IB*09B9LW4T*8+DCMH0LDK2%K:XFE70*LP$V25$0Q:J:4MO1P0%0L0HD+9E/HY+4J6TH48S%4K.GJ2PT3QY:GQ3TE2I+-CPHN6D7LLK*2HG%89UV-0LZ 2ZJJ524-LH/CJTK96L6SR9MU9DHGZ%P WUQRENS431T1XCNCF+47AY0-IFO0500TGPN8F5G.41Q2E4T8ALW.INSV$ 07UV5SR+BNQHNML7 /KD3TU 4V*CAT3ZGLQMI/XI%ZJNSBBXK2:UG%UJMI:TU+MMPZ5$/PMX19UE:-PSR3/$NU44CBE6DQ3D7B0FBOFX0DV2DGMB$YPF62I$60/F$Z2I6IFX21XNI-LM%3/DF/U6Z9FEOJVRLVW6K$UG+BKK57:1+D10%4K83F+1VWD1NE";
+
+    assert_eq!(replay(vaccination_data.into()), Outcome::Success);
+    assert_eq!(
+        replay(b"junk".to_vec()),
+        Outcome::Error("data must start with HC1: prefix".to_string())
+    );
+    assert!(matches!(replay(vec![0xff]), Outcome::Error(_)));
+}
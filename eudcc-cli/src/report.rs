@@ -0,0 +1,148 @@
+use eudcc_core::{Certificate, Payload};
+
+use crate::human::{
+    display, COUNTRIES, DISEASES, MANUFACTURERS, PRODUCTS, TEST_RESULTS,
+    TEST_TYPES, VACCINES,
+};
+
+/// A table of a report, with cells as they were decoded: every format
+/// escapes them its own way.
+pub struct Table {
+    pub title: &'static str,
+    pub header: &'static [&'static str],
+    pub rows: Vec<Vec<String>>,
+}
+
+/// The tables of a report on a certificate: the holder and the payload
+/// claims, then one for each kind of record the certificate has.
+pub fn tables(payload: &Payload, certificate: &Certificate) -> Vec<Table> {
+    let name = &certificate.nam;
+    let holder = [
+        ("Surname", name.fn_.as_str()),
+        ("Forename", &name.gn),
+        ("Standardised surname", &name.fnt),
+        ("Standardised forename", &name.gnt),
+        ("Date of birth", &certificate.dob),
+    ];
+    let mut tables = vec![
+        Table {
+            title: "Holder",
+            header: &["Field", "Value"],
+            rows: holder
+                .iter()
+                .map(|(field, value)| {
+                    vec![field.to_string(), value.to_string()]
+                })
+                .collect(),
+        },
+        Table {
+            title: "Payload",
+            header: &["Claim", "Value"],
+            rows: vec![
+                vec!["Issuer".to_string(), payload.issuer.clone()],
+                vec!["Issued at".to_string(), payload.issued_at.to_string()],
+                vec!["Expires at".to_string(), payload.expires_at.to_string()],
+            ],
+        },
+    ];
+
+    if !certificate.v.is_empty() {
+        tables.push(Table {
+            title: "Vaccinations",
+            header: &[
+                "Dose",
+                "Date",
+                "Country",
+                "Disease",
+                "Vaccine",
+                "Product",
+                "Manufacturer",
+                "Issuer",
+                "Identifier",
+            ],
+            rows: certificate
+                .v
+                .iter()
+                .map(|v| {
+                    vec![
+                        format!("{} of {}", v.dn, v.sd),
+                        v.dt.clone(),
+                        display(COUNTRIES, &v.co),
+                        display(DISEASES, &v.tg),
+                        display(VACCINES, &v.vp),
+                        display(PRODUCTS, &v.mp),
+                        display(MANUFACTURERS, &v.ma),
+                        v.is.clone(),
+                        v.ci.clone(),
+                    ]
+                })
+                .collect(),
+        });
+    }
+    if !certificate.r.is_empty() {
+        tables.push(Table {
+            title: "Recoveries",
+            header: &[
+                "Positive on",
+                "Valid from",
+                "Valid until",
+                "Country",
+                "Disease",
+                "Issuer",
+                "Identifier",
+            ],
+            rows: certificate
+                .r
+                .iter()
+                .map(|r| {
+                    vec![
+                        r.fr.clone(),
+                        r.df.clone(),
+                        r.du.clone(),
+                        display(COUNTRIES, &r.co),
+                        display(DISEASES, &r.tg),
+                        r.is.clone(),
+                        r.ci.clone(),
+                    ]
+                })
+                .collect(),
+        });
+    }
+    if !certificate.t.is_empty() {
+        tables.push(Table {
+            title: "Tests",
+            header: &[
+                "Type",
+                "Result",
+                "Collected at",
+                "Country",
+                "Disease",
+                "Name",
+                "Manufacturer",
+                "Test centre",
+                "Issuer",
+                "Identifier",
+            ],
+            rows: certificate
+                .t
+                .iter()
+                .map(|t| {
+                    vec![
+                        display(TEST_TYPES, &t.tt),
+                        display(TEST_RESULTS, &t.tr),
+                        t.sc.clone(),
+                        display(COUNTRIES, &t.co),
+                        display(DISEASES, &t.tg),
+                        t.nm.clone(),
+                        t.ma.clone(),
+                        t.tc.clone(),
+                        t.is.clone(),
+                        t.ci.clone(),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    tables
+}
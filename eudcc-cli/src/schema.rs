@@ -0,0 +1,225 @@
+//! The JSON Schema of the JSON output, for consumers validating it.
+
+/// The version of the JSON output, in its `schema_version` member. It is
+/// only bumped by changes that can break consumers, like members removed,
+/// renamed or holding another type: new members leave it as it is.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The JSON Schema of the JSON output, version [`SCHEMA_VERSION`].
+///
+/// Every field is optional, as `--fields` leaves some out, and other
+/// members are allowed, as later versions of the same schema may add them.
+pub const SCHEMA: &str = r##"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "title": "eudccdec JSON output",
+  "description": "A decoded EU Digital COVID Certificate, alone or next to the path of its input, the pseudonym of its holder and the timings of its decoding.",
+  "type": "object",
+  "properties": {
+    "schema_version": {
+      "description": "The version of this schema.",
+      "const": 1
+    }
+  },
+  "required": ["schema_version"],
+  "if": {
+    "required": ["certificate"]
+  },
+  "then": {
+    "properties": {
+      "path": {
+        "description": "The path of the input, with its line in batches.",
+        "type": "string"
+      },
+      "certificate": {
+        "$ref": "#/$defs/certificate"
+      },
+      "pseudonym": {
+        "description": "The HMAC-SHA-256 of the standardised names and the date of birth, in hexadecimal.",
+        "type": "string",
+        "pattern": "^[0-9a-f]{64}$"
+      },
+      "timings": {
+        "description": "The time taken by each stage of decoding, in microseconds.",
+        "type": "object",
+        "additionalProperties": {
+          "type": "integer",
+          "minimum": 0
+        }
+      }
+    }
+  },
+  "else": {
+    "$ref": "#/$defs/certificate"
+  },
+  "$defs": {
+    "certificate": {
+      "type": "object",
+      "properties": {
+        "ver": { "type": "string" },
+        "nam": {
+          "type": "object",
+          "properties": {
+            "fn": { "type": "string" },
+            "fnt": { "type": "string" },
+            "gn": { "type": "string" },
+            "gnt": { "type": "string" }
+          }
+        },
+        "dob": { "type": "string" },
+        "v": {
+          "type": "array",
+          "items": { "$ref": "#/$defs/vaccination" }
+        },
+        "r": {
+          "type": "array",
+          "items": { "$ref": "#/$defs/recovery" }
+        },
+        "t": {
+          "type": "array",
+          "items": { "$ref": "#/$defs/test" }
+        }
+      }
+    },
+    "vaccination": {
+      "type": "object",
+      "properties": {
+        "tg": { "type": "string" },
+        "vp": { "type": "string" },
+        "mp": { "type": "string" },
+        "ma": { "type": "string" },
+        "dn": { "type": "integer" },
+        "sd": { "type": "integer" },
+        "dt": { "type": "string" },
+        "co": { "type": "string" },
+        "is": { "type": "string" },
+        "ci": { "type": "string" }
+      }
+    },
+    "recovery": {
+      "type": "object",
+      "properties": {
+        "tg": { "type": "string" },
+        "fr": { "type": "string" },
+        "co": { "type": "string" },
+        "is": { "type": "string" },
+        "df": { "type": "string" },
+        "du": { "type": "string" },
+        "ci": { "type": "string" }
+      }
+    },
+    "test": {
+      "type": "object",
+      "properties": {
+        "tg": { "type": "string" },
+        "tt": { "type": "string" },
+        "nm": { "type": "string" },
+        "ma": { "type": "string" },
+        "sc": { "type": "string" },
+        "dr": { "type": "string" },
+        "tr": { "type": "string" },
+        "tc": { "type": "string" },
+        "co": { "type": "string" },
+        "is": { "type": "string" },
+        "ci": { "type": "string" }
+      }
+    }
+  }
+}
+"##;
+
+#[test]
+fn schema_test() {
+    use ciborium::value::Value;
+    use eudcc_core::{
+        Certificate, Name, RecoveryRecord, TestRecord, VaccineRecord,
+    };
+
+    use crate::json;
+
+    let schema = json::parse(SCHEMA).unwrap();
+    let get = |value: &Value, path: &[&str]| {
+        path.iter().try_fold(value.clone(), |value, key| {
+            value
+                .as_map()?
+                .iter()
+                .find(|(k, _)| k.as_text() == Some(key))
+                .map(|(_, v)| v.clone())
+        })
+    };
+    let version = ["properties", "schema_version", "const"];
+    assert_eq!(get(&schema, &version), Some(SCHEMA_VERSION.into()));
+
+    // Every field of the certificates is in the schema, in their order.
+    let names = |value: Option<Value>| -> Vec<String> {
+        let entries = value.and_then(|v| v.as_map().cloned());
+        entries
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|(k, _)| k.as_text().map(str::to_string))
+            .collect()
+    };
+    let text = String::new;
+    let certificate = Certificate {
+        ver: text(),
+        nam: Name {
+            fn_: text(),
+            fnt: text(),
+            gn: text(),
+            gnt: text(),
+        },
+        dob: text(),
+        v: vec![VaccineRecord {
+            tg: text(),
+            vp: text(),
+            mp: text(),
+            ma: text(),
+            dn: 1,
+            sd: 1,
+            dt: text(),
+            co: text(),
+            is: text(),
+            ci: text(),
+        }],
+        r: vec![RecoveryRecord {
+            tg: text(),
+            fr: text(),
+            co: text(),
+            is: text(),
+            df: text(),
+            du: text(),
+            ci: text(),
+        }],
+        t: vec![TestRecord {
+            tg: text(),
+            tt: text(),
+            nm: text(),
+            ma: text(),
+            sc: text(),
+            dr: text(),
+            tr: text(),
+            tc: text(),
+            co: text(),
+            is: text(),
+            ci: text(),
+        }],
+    };
+    let value = Value::serialized(&certificate).unwrap();
+    let properties =
+        |name: &str| names(get(&schema, &["$defs", name, "properties"]));
+    let record = |group: &str| {
+        names(
+            get(&value, &[group]).and_then(|v| v.as_array()?.first().cloned()),
+        )
+    };
+    assert_eq!(properties("certificate"), names(Some(value.clone())));
+    assert_eq!(
+        names(get(
+            &schema,
+            &["$defs", "certificate", "properties", "nam", "properties"]
+        )),
+        names(get(&value, &["nam"]))
+    );
+    assert_eq!(properties("vaccination"), record("v"));
+    assert_eq!(properties("recovery"), record("r"));
+    assert_eq!(properties("test"), record("t"));
+}
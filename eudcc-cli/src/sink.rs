@@ -0,0 +1,127 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+
+use crate::http::{Client, Url, OFFLINE};
+
+/// Where results are written.
+///
+/// Results can be written more than once, one for each certificate, so
+/// sinks keep what was written before.
+pub trait Sink {
+    fn write(&mut self, output: &[u8]) -> io::Result<()>;
+}
+
+/// The standard output.
+pub struct Stdout;
+
+impl Sink for Stdout {
+    fn write(&mut self, output: &[u8]) -> io::Result<()> {
+        io::stdout().write_all(output)
+    }
+}
+
+/// A file, created or truncated when it is first written, then appended
+/// to.
+pub struct FileSink {
+    path: String,
+    file: Option<File>,
+}
+
+impl Sink for FileSink {
+    fn write(&mut self, output: &[u8]) -> io::Result<()> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => self.file.insert(
+                OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(&self.path)?,
+            ),
+        };
+        file.write_all(output)
+    }
+}
+
+/// An HTTP endpoint, every result POSTed to it on its own.
+pub struct HttpSink {
+    url: Url,
+    client: Client,
+}
+
+impl Sink for HttpSink {
+    fn write(&mut self, output: &[u8]) -> io::Result<()> {
+        self.client.post(&self.url, output)
+    }
+}
+
+/// Choose a sink by URI: `-` for stdout, `file:PATH` or a plain path for a
+/// file, `http://HOST[:PORT]/PATH` for an HTTP endpoint, reached through
+/// `client` and the proxy in the environment, unless it is offline.
+pub fn from_uri(uri: &str, client: &Client) -> Result<Box<dyn Sink>, String> {
+    if uri == "-" {
+        return Ok(Box::new(Stdout));
+    }
+    if uri.starts_with("http://") {
+        if client.offline {
+            return Err(format!("can't write to {}: {}", uri, OFFLINE));
+        }
+        return Ok(Box::new(HttpSink {
+            url: Url::parse(uri)?,
+            client: client.clone().proxy_from_env()?,
+        }));
+    }
+    let path = match uri.split_once(':') {
+        Some(("file", path)) => path,
+        // Like C:\ on Windows.
+        Some((scheme, _)) if scheme.len() == 1 => uri,
+        Some((scheme, _)) => {
+            return Err(format!(
+                "unsupported sink {}: only -, file: and http:// are",
+                scheme
+            ));
+        }
+        None => uri,
+    };
+
+    Ok(Box::new(FileSink {
+        path: path.to_string(),
+        file: None,
+    }))
+}
+
+#[test]
+fn from_uri_test() {
+    let mut client = Client::new();
+    client.offline = false;
+    assert!(from_uri("-", &client).is_ok());
+    assert!(from_uri("out.json", &client).is_ok());
+    assert!(from_uri("http://localhost:8080/certificates", &client).is_ok());
+    assert_eq!(
+        from_uri("http://localhost:x/", &client).err().unwrap(),
+        "invalid port in http://localhost:x/"
+    );
+    assert_eq!(
+        from_uri("s3://bucket/prefix", &client).err().unwrap(),
+        "unsupported sink s3: only -, file: and http:// are"
+    );
+
+    client.offline = true;
+    assert_eq!(
+        from_uri("http://localhost/", &client).err().unwrap(),
+        format!("can't write to http://localhost/: {}", OFFLINE)
+    );
+    assert!(from_uri("out.json", &client).is_ok());
+}
+
+#[test]
+fn file_sink_test() {
+    let path = std::env::temp_dir().join("eudccdec-file-sink-test");
+    let path = path.to_str().unwrap();
+    let uri = format!("file:{}", path);
+    let mut sink = from_uri(&uri, &Client::new()).unwrap();
+    sink.write(b"first\n").unwrap();
+    sink.write(b"second\n").unwrap();
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "first\nsecond\n");
+    std::fs::remove_file(path).unwrap();
+}
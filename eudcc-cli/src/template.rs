@@ -0,0 +1,187 @@
+use ciborium::value::Value;
+
+use crate::field;
+use crate::output::sanitize;
+
+/// A part of a template.
+#[derive(Debug, PartialEq)]
+enum Node {
+    Text(String),
+    /// `{{ path }}`, replaced by the value at the path.
+    Field(String),
+    /// `{{#each path}} ... {{/each}}`, repeated for every value of the
+    /// array at the path.
+    Each(String, Vec<Node>),
+}
+
+/// Parse a template, in a small subset of the Handlebars syntax: fields
+/// and `each` blocks.
+fn parse(template: &str) -> Result<Vec<Node>, String> {
+    // The nodes of the blocks still open, with the path they iterate on.
+    let mut stack: Vec<(String, Vec<Node>)> = vec![(String::new(), vec![])];
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let (text, tag) = rest.split_at(start);
+        let end = tag
+            .find("}}")
+            .ok_or_else(|| "unclosed template tag".to_string())?;
+        let (tag, tail) = tag.split_at(end);
+        rest = tail.get(2..).unwrap_or_default();
+
+        let mut nodes = match stack.pop() {
+            Some(block) => block,
+            None => return Err("unbalanced template blocks".to_string()),
+        };
+        if !text.is_empty() {
+            nodes.1.push(Node::Text(text.to_string()));
+        }
+        let tag = tag.trim_start_matches("{{").trim();
+        if let Some(path) = tag.strip_prefix("#each ") {
+            stack.push(nodes);
+            stack.push((path.trim().to_string(), vec![]));
+        } else if tag == "/each" {
+            // The root block is the only one without a path.
+            match stack.last_mut() {
+                Some(parent) if !nodes.0.is_empty() => {
+                    parent.1.push(Node::Each(nodes.0, nodes.1))
+                }
+                _ => return Err("{{/each}} without {{#each}}".to_string()),
+            }
+        } else if tag.is_empty() || tag.starts_with(&['#', '/'][..]) {
+            return Err(format!("unknown template tag {{{{{}}}}}", tag));
+        } else {
+            nodes.1.push(Node::Field(tag.to_string()));
+            stack.push(nodes);
+        }
+    }
+
+    match stack.pop() {
+        Some((path, mut nodes)) if path.is_empty() && stack.is_empty() => {
+            if !rest.is_empty() {
+                nodes.push(Node::Text(rest.to_string()));
+            }
+            Ok(nodes)
+        }
+        Some((path, _)) => Err(format!("unclosed {{{{#each {}}}}}", path)),
+        None => Err("unbalanced template blocks".to_string()),
+    }
+}
+
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path == "this" {
+        return Some(value);
+    }
+    field::lookup(value, path.strip_prefix("this.").unwrap_or(path))
+}
+
+/// Look a path up in the innermost scope having it.
+fn resolve<'a>(scopes: &[&'a Value], path: &str) -> Result<&'a Value, String> {
+    scopes
+        .iter()
+        .rev()
+        .find_map(|scope| lookup(scope, path))
+        .ok_or_else(|| format!("unknown template field {}", path))
+}
+
+fn render_nodes(
+    out: &mut String,
+    nodes: &[Node],
+    scopes: &mut Vec<&Value>,
+) -> Result<(), String> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Field(path) => match resolve(scopes, path)? {
+                Value::Text(text) => out.push_str(&sanitize(text)),
+                Value::Integer(i) => out.push_str(&i128::from(*i).to_string()),
+                Value::Float(f) => out.push_str(&f.to_string()),
+                Value::Bool(b) => out.push_str(&b.to_string()),
+                Value::Null => {}
+                _ => {
+                    return Err(format!(
+                        "template field {} is not a value",
+                        path
+                    ))
+                }
+            },
+            Node::Each(path, body) => {
+                let values = match resolve(scopes, path)? {
+                    Value::Array(values) => values,
+                    _ => {
+                        return Err(format!(
+                            "template field {} is not a list",
+                            path
+                        ))
+                    }
+                };
+                for value in values {
+                    scopes.push(value);
+                    let rendered = render_nodes(out, body, scopes);
+                    scopes.pop();
+                    rendered?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Render a template with the fields of `context`, a map.
+pub fn render(template: &str, context: &Value) -> Result<String, String> {
+    let nodes = parse(template)?;
+    let mut out = String::new();
+    render_nodes(&mut out, &nodes, &mut vec![context])?;
+
+    Ok(out)
+}
+
+#[test]
+fn render_test() {
+    let context = Value::Map(vec![
+        ("issuer".into(), "IT".into()),
+        (
+            "nam".into(),
+            Value::Map(vec![("gn".into(), "Marilù\x1b[2J".into())]),
+        ),
+        (
+            "v".into(),
+            Value::Array(vec![
+                Value::Map(vec![("dn".into(), 1.into())]),
+                Value::Map(vec![("dn".into(), 2.into())]),
+            ]),
+        ),
+    ]);
+
+    assert_eq!(
+        render("{{ nam.gn }} ({{issuer}})\n", &context).unwrap(),
+        "Marilù (IT)\n"
+    );
+    assert_eq!(
+        render("{{#each v}}dose {{dn}} by {{issuer}}; {{/each}}", &context)
+            .unwrap(),
+        "dose 1 by IT; dose 2 by IT; "
+    );
+    assert_eq!(render("{{v.1.dn}}}}", &context).unwrap(), "2}}");
+
+    assert_eq!(
+        render("{{ nam.xyz }}", &context).unwrap_err(),
+        "unknown template field nam.xyz"
+    );
+    assert_eq!(
+        render("{{ nam }}", &context).unwrap_err(),
+        "template field nam is not a value"
+    );
+    assert_eq!(
+        render("{{#each v}}", &context).unwrap_err(),
+        "unclosed {{#each v}}"
+    );
+    assert_eq!(
+        render("{{/each}}", &context).unwrap_err(),
+        "{{/each}} without {{#each}}"
+    );
+    assert_eq!(
+        render("{{ issuer", &context).unwrap_err(),
+        "unclosed template tag"
+    );
+}
@@ -0,0 +1,183 @@
+use std::time::{Duration, Instant};
+
+use ciborium::value::Value;
+use eudcc_core::eudcc::{self, DecodeError};
+use eudcc_core::{DecodeOptions, Payload};
+
+use crate::diagnostics::LogFormat;
+use crate::input::Source;
+use crate::json;
+
+/// Time spent in each stage of decoding, those before the COSE structure
+/// only when reading an HC1 string.
+#[derive(Debug, PartialEq)]
+pub struct Timings {
+    pub base45: Option<Duration>,
+    pub decompress: Option<Duration>,
+    pub cose: Duration,
+    pub claims: Duration,
+    pub mapping: Duration,
+}
+
+impl Timings {
+    fn stages(&self) -> Vec<(&'static str, Duration)> {
+        let hc1 = [("base45", self.base45), ("decompress", self.decompress)];
+        let mut stages: Vec<(&str, Duration)> = hc1
+            .iter()
+            .filter_map(|(name, t)| t.map(|t| (*name, t)))
+            .collect();
+        stages.extend([
+            ("cose", self.cose),
+            ("claims", self.claims),
+            ("mapping", self.mapping),
+        ]);
+        stages
+    }
+
+    /// The timings as a map of stages to microseconds.
+    pub fn to_value(&self) -> Value {
+        Value::Map(
+            self.stages()
+                .iter()
+                .map(|(name, t)| {
+                    ((*name).into(), (t.as_micros() as u64).into())
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Run a stage, timing it.
+fn timed<T>(
+    stage: impl FnOnce() -> Result<T, DecodeError>,
+) -> Result<(T, Duration), DecodeError> {
+    let start = Instant::now();
+    let output = stage()?;
+    Ok((output, start.elapsed()))
+}
+
+/// Decode the payload of a certificate and its certificates as controlled
+/// by `options`, like decoding does, timing each stage as it runs.
+///
+/// Decoding streams base45, inflate and CBOR into each other, so they are
+/// rather run one after the other here, each one on the whole output of
+/// the previous one. Signatures are not verified, so there is no
+/// verification stage.
+pub fn measure(
+    source: &Source,
+    options: &DecodeOptions,
+) -> Result<(Payload, Timings), DecodeError> {
+    let ((cose, cose_time), base45, decompress) = match source {
+        Source::Hc1(data) => {
+            let text = eudcc::base45_text_with(data.as_bytes(), options)?;
+            let (compressed, base45) = timed(|| eudcc::decode_base45(text))?;
+            let (data, decompress) =
+                timed(|| eudcc::decompress_with(&compressed, options))?;
+            let cose = timed(|| eudcc::parse_cose(&data))?;
+            (cose, Some(base45), Some(decompress))
+        }
+        Source::Cose(data) => (timed(|| eudcc::parse_cose(data))?, None, None),
+    };
+
+    let (payload, claims) =
+        timed(|| eudcc::parse_hcert_with(&cose.payload, options))?;
+
+    // The certificates are kept by the payload, so they are not mapped
+    // again when asked for later on.
+    let ((), mapping) = timed(|| payload.certs().map(|_| ()))?;
+
+    let timings = Timings {
+        base45,
+        decompress,
+        cose: cose_time,
+        claims,
+        mapping,
+    };
+
+    Ok((payload, timings))
+}
+
+/// Format the timings as a diagnostic line, in microseconds.
+pub fn format(format: LogFormat, timings: &Timings) -> String {
+    let stages = timings.stages();
+    match format {
+        LogFormat::Text => {
+            let stages: Vec<String> = stages
+                .iter()
+                .map(|(name, t)| format!("{} {}us", name, t.as_micros()))
+                .collect();
+            format!("Timings: {}", stages.join(", "))
+        }
+        LogFormat::Json => {
+            let stages: Vec<String> = stages
+                .iter()
+                .map(|(name, t)| {
+                    format!("{}:{}", json::string(name), t.as_micros())
+                })
+                .collect();
+            format!(
+                "{{\"level\":\"info\",\"timings_us\":{{{}}}}}",
+                stages.join(",")
+            )
+        }
+    }
+}
+
+#[test]
+fn timings_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/2.json
+    // It is licensed under Apache-2.0 License.
+    let recovery_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *MEB2B2JJ59J-9BC6:X9NECX0AKQC:3DCV4*XUA2P-FHT-H4SI/J9WVHWVH+ZEOV1J$HNTICZUBOM*LP$V25$0Q:J40IA3L/*84-5%:C92JN*4CY0*%9F/8J2P4.818T+:IX3M3.96RPVD9J-OZT1-NT0 2$$0$2PZX69B9VCDHI2/T9TU1BPIJKH/T7B-S-*O/Y41FD+X49+5Z-6%.HDD8R6W1FDJGJSFJ/4Q:T0.KJTNP8EFULNC:HA0K5HKRB4TD85LOLF92GF.3O.Z8CC7-2FQYG$%21 2O*4R60NM8JI0EUGP$I/XK$M8ZQE6YB9M66P8N31I.ROSK%IA1Q2N53Q-OQ2VC6E26T11ROSNK5W-*H+MJ%0RGZVGWNURI75RBSQSHLH1JG*CMH2.-S$7VX6N*Z1881J7G.F9I+SV06F+1M*93%D";
+    let options = DecodeOptions::new();
+    let hc1 = Source::Hc1(recovery_data.to_string());
+    let (payload, _) = measure(&hc1, &options).unwrap();
+    assert_eq!(payload, hc1.payload(&options).unwrap());
+    let cose = Source::Cose(hc1.cose_bytes().unwrap());
+    let (payload, _) = measure(&cose, &options).unwrap();
+    assert_eq!(payload, hc1.payload(&options).unwrap());
+    assert!(matches!(
+        measure(&Source::Hc1(recovery_data[4..].to_string()), &options),
+        Err(DecodeError::MissingPrefix)
+    ));
+    // The options of the decoding apply.
+    let options = DecodeOptions::new().reject_issuers(&["IT"]);
+    assert!(matches!(
+        measure(&cose, &options),
+        Err(DecodeError::IssuerRejected(_))
+    ));
+
+    let options = DecodeOptions::new().max_decompressed_len(100);
+    assert!(matches!(
+        measure(&hc1, &options),
+        Err(DecodeError::DecompressedTooLarge { limit: 100 })
+    ));
+    // Only HC1 strings go through base45 and inflate.
+    let (_, timings) = measure(&hc1, &DecodeOptions::new()).unwrap();
+    assert!(timings.base45.is_some() && timings.decompress.is_some());
+    let (_, timings) = measure(&cose, &DecodeOptions::new()).unwrap();
+    assert_eq!((timings.base45, timings.decompress), (None, None));
+
+    let mut timings = Timings {
+        base45: Some(Duration::from_micros(21)),
+        decompress: Some(Duration::from_micros(13)),
+        cose: Duration::from_micros(42),
+        claims: Duration::from_micros(5),
+        mapping: Duration::from_micros(8),
+    };
+    assert_eq!(
+        format(LogFormat::Text, &timings),
+        "Timings: base45 21us, decompress 13us, cose 42us, claims 5us, \
+         mapping 8us"
+    );
+    assert_eq!(
+        format(LogFormat::Json, &timings),
+        r#"{"level":"info","timings_us":{"base45":21,"decompress":13,"cose":42,"claims":5,"mapping":8}}"#
+    );
+    timings.base45 = None;
+    timings.decompress = None;
+    assert_eq!(
+        json::to_string(&timings.to_value(), false),
+        r#"{"cose":42,"claims":5,"mapping":8}"#
+    );
+}
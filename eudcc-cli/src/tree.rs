@@ -0,0 +1,282 @@
+use ciborium::{de::from_reader, ser::into_writer, value::Value};
+
+use crate::output::sanitize;
+use eudcc_core::eudcc::{algorithm_name, hex, CoseSign1, COSE_SIGN1_TAG};
+
+/// Where a CBOR value lives inside the certificate, used to pick labels.
+#[derive(Clone, Copy, PartialEq)]
+enum Context {
+    Header,
+    Claims,
+    Hcert,
+    Certificate,
+    Name,
+    Vaccination,
+    Recovery,
+    Test,
+    Algorithm,
+    Unknown,
+}
+
+impl Context {
+    fn label(self, key: &Value) -> Option<&'static str> {
+        if let Some(key) = key.as_integer() {
+            let key = i128::from(key);
+            return match (self, key) {
+                (Context::Header, 1) => Some("Algorithm"),
+                (Context::Header, 4) => Some("Key identifier"),
+                (Context::Header, 7) => Some("Counter signature"),
+                (Context::Header, 9) => Some("Counter signature 0"),
+                (Context::Header, 33) => Some("X.509 certificate chain"),
+                (Context::Claims, 1) => Some("Issuer"),
+                (Context::Claims, 4) => Some("Expiration time"),
+                (Context::Claims, 6) => Some("Issued at"),
+                (Context::Claims, -260) => Some("Health certificate"),
+                (Context::Hcert, 1) => Some("EU Digital COVID Certificate v1"),
+                _ => None,
+            };
+        }
+
+        let key = key.as_text()?;
+        match (self, key) {
+            (Context::Certificate, "ver") => Some("Schema version"),
+            (Context::Certificate, "nam") => Some("Name"),
+            (Context::Certificate, "dob") => Some("Date of birth"),
+            (Context::Certificate, "v") => Some("Vaccination group"),
+            (Context::Certificate, "r") => Some("Recovery group"),
+            (Context::Certificate, "t") => Some("Test group"),
+            (Context::Name, "fn") => Some("Surname"),
+            (Context::Name, "fnt") => Some("Standardised surname"),
+            (Context::Name, "gn") => Some("Forename"),
+            (Context::Name, "gnt") => Some("Standardised forename"),
+            (_, "tg") => Some("Disease or agent targeted"),
+            (Context::Vaccination, "vp") => Some("Vaccine or prophylaxis"),
+            (Context::Vaccination, "mp") => Some("Vaccine medicinal product"),
+            (Context::Vaccination, "ma") => Some("Vaccine manufacturer"),
+            (Context::Vaccination, "dn") => Some("Dose number"),
+            (Context::Vaccination, "sd") => Some("Total series of doses"),
+            (Context::Vaccination, "dt") => Some("Date of vaccination"),
+            (Context::Vaccination, "co") => Some("Country of vaccination"),
+            (Context::Recovery, "fr") => Some("Date of first positive result"),
+            (Context::Recovery, "co") => Some("Country of test"),
+            (Context::Recovery, "df") => Some("Certificate valid from"),
+            (Context::Recovery, "du") => Some("Certificate valid until"),
+            (Context::Test, "tt") => Some("Type of test"),
+            (Context::Test, "nm") => Some("Test name"),
+            (Context::Test, "ma") => Some("Test device identifier"),
+            (Context::Test, "sc") => Some("Sample collection time"),
+            (Context::Test, "dr") => Some("Test result time"),
+            (Context::Test, "tr") => Some("Test result"),
+            (Context::Test, "tc") => Some("Testing centre"),
+            (Context::Test, "co") => Some("Country of test"),
+            (_, "is") => Some("Certificate issuer"),
+            (_, "ci") => Some("Unique certificate identifier"),
+            _ => None,
+        }
+    }
+
+    fn child(self, key: &Value) -> Context {
+        if let Some(key) = key.as_integer() {
+            return match (self, i128::from(key)) {
+                (Context::Header, 1) => Context::Algorithm,
+                (Context::Claims, -260) => Context::Hcert,
+                (Context::Hcert, 1) => Context::Certificate,
+                _ => Context::Unknown,
+            };
+        }
+
+        match (self, key.as_text()) {
+            (Context::Certificate, Some("nam")) => Context::Name,
+            (Context::Certificate, Some("v")) => Context::Vaccination,
+            (Context::Certificate, Some("r")) => Context::Recovery,
+            (Context::Certificate, Some("t")) => Context::Test,
+            _ => Context::Unknown,
+        }
+    }
+}
+
+struct Node {
+    label: String,
+    children: Vec<Node>,
+}
+
+fn encoded_len(value: &Value) -> usize {
+    let mut buffer = Vec::new();
+    match into_writer(value, &mut buffer) {
+        Ok(()) => buffer.len(),
+        Err(_) => 0,
+    }
+}
+
+fn size(len: usize) -> String {
+    match len {
+        1 => "[1 byte]".to_string(),
+        n => format!("[{} bytes]", n),
+    }
+}
+
+fn raw(value: &Value) -> String {
+    match value {
+        Value::Integer(i) => i128::from(*i).to_string(),
+        Value::Bytes(b) => format!("h'{}'", hex(b)),
+        Value::Float(f) => f.to_string(),
+        Value::Text(t) => format!("{:?}", sanitize(t)),
+        Value::Bool(b) => b.to_string(),
+        Value::Null => "null".to_string(),
+        _ => String::new(),
+    }
+}
+
+fn key_label(key: &Value, context: Context) -> String {
+    let key_text = match key {
+        Value::Text(t) => sanitize(t),
+        other => raw(other),
+    };
+    match context.label(key) {
+        Some(label) => format!("{} ({})", key_text, label),
+        None => key_text,
+    }
+}
+
+fn value_node(label: String, value: &Value, context: Context) -> Node {
+    let size = size(encoded_len(value));
+    match value {
+        Value::Map(entries) => Node {
+            label: format!("{} {}", label, size),
+            children: entries
+                .iter()
+                .map(|(k, v)| {
+                    value_node(key_label(k, context), v, context.child(k))
+                })
+                .collect(),
+        },
+        Value::Array(items) => Node {
+            label: format!("{} {}", label, size),
+            children: items
+                .iter()
+                .enumerate()
+                .map(|(i, v)| value_node(format!("[{}]", i), v, context))
+                .collect(),
+        },
+        Value::Tag(tag, inner) => Node {
+            label: format!("{} (tag {}) {}", label, tag, size),
+            children: vec![value_node("content".to_string(), inner, context)],
+        },
+        Value::Integer(i) if context == Context::Algorithm => {
+            let alg = algorithm_name(i128::from(*i));
+            let annotation =
+                alg.map(|a| format!(" ({})", a)).unwrap_or_default();
+            Node {
+                label: format!(
+                    "{}: {}{} {}",
+                    label,
+                    raw(value),
+                    annotation,
+                    size
+                ),
+                children: vec![],
+            }
+        }
+        _ => Node {
+            label: format!("{}: {} {}", label, raw(value), size),
+            children: vec![],
+        },
+    }
+}
+
+/// Build a node for a byte string wrapping an embedded CBOR item, showing
+/// the decoded item as children.
+fn wrapped_node(label: &str, bytes: &[u8], context: Context) -> Node {
+    let wrapped = Value::Bytes(bytes.to_vec());
+    let size = size(encoded_len(&wrapped));
+    if bytes.is_empty() {
+        return Node {
+            label: format!("{}: empty {}", label, size),
+            children: vec![],
+        };
+    }
+
+    match from_reader::<Value, _>(bytes) {
+        Ok(inner) => {
+            let node = value_node(String::new(), &inner, context);
+            Node {
+                label: format!("{} {}", label, size),
+                children: node.children,
+            }
+        }
+        Err(_) => value_node(label.to_string(), &wrapped, context),
+    }
+}
+
+fn write_children(out: &mut String, children: &[Node], prefix: &str) {
+    for (i, child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        out.push_str(prefix);
+        out.push_str(if last { "└── " } else { "├── " });
+        out.push_str(&child.label);
+        out.push('\n');
+
+        let prefix =
+            format!("{}{}", prefix, if last { "    " } else { "│   " });
+        write_children(out, &child.children, &prefix);
+    }
+}
+
+/// Render the whole COSE structure as an annotated tree.
+pub fn render(cose: &CoseSign1) -> String {
+    let whole = Value::Tag(
+        COSE_SIGN1_TAG,
+        Box::new(Value::Array(vec![
+            Value::Bytes(cose.protected.clone()),
+            cose.unprotected.clone(),
+            Value::Bytes(cose.payload.clone()),
+            Value::Bytes(cose.signature.clone()),
+        ])),
+    );
+
+    let root = Node {
+        label: format!(
+            "COSE_Sign1 (tag {}) {}",
+            COSE_SIGN1_TAG,
+            size(encoded_len(&whole))
+        ),
+        children: vec![
+            wrapped_node("Protected header", &cose.protected, Context::Header),
+            value_node(
+                "Unprotected header".to_string(),
+                &cose.unprotected,
+                Context::Header,
+            ),
+            wrapped_node("Payload", &cose.payload, Context::Claims),
+            value_node(
+                "Signature".to_string(),
+                &Value::Bytes(cose.signature.clone()),
+                Context::Unknown,
+            ),
+        ],
+    };
+
+    let mut out = root.label.clone();
+    out.push('\n');
+    write_children(&mut out, &root.children, "");
+    out
+}
+
+#[test]
+fn render_tree_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/1.json
+    // It is licensed under Apache-2.0 License.
+    let vaccination_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II5XHC9B5G2+$N IOP-IA%NFQGRJPC%OQHIZC4.OI1RM8ZA.A5:S9MKN4NN3F85QNCY0O%0VZ001HOC9JU0D0HT0HB2PL/IB*09B9LW4T*8+DCMH0LDK2%K:XFE70*LP$V25$0Q:J:4MO1P0%0L0HD+9E/HY+4J6TH48S%4K.GJ2PT3QY:GQ3TE2I+-CPHN6D7LLK*2HG%89UV-0LZ 2ZJJ524-LH/CJTK96L6SR9MU9DHGZ%P WUQRENS431T1XCNCF+47AY0-IFO0500TGPN8F5G.41Q2E4T8ALW.INSV$ 07UV5SR+BNQHNML7 /KD3TU 4V*CAT3ZGLQMI/XI%ZJNSBBXK2:UG%UJMI:TU+MMPZ5$/PMX19UE:-PSR3/$NU44CBE6DQ3D7B0FBOFX0DV2DGMB$YPF62I$60/F$Z2I6IFX21XNI-LM%3/DF/U6Z9FEOJVRLVW6K$UG+BKK57:1+D10%4K83F+1VWD1NE";
+
+    let cose =
+        eudcc_core::eudcc::decode_cose(vaccination_data.to_string()).unwrap();
+    let tree = render(&cose);
+
+    assert!(tree.starts_with("COSE_Sign1 (tag 18) ["));
+    assert!(tree.contains("1 (Algorithm): -7 (ES256) [1 byte]"));
+    assert!(tree.contains("1 (Issuer): \"IT\" [3 bytes]"));
+    assert!(tree.contains("-260 (Health certificate) ["));
+    assert!(tree.contains("fn (Surname): \"Di Caprio\" [10 bytes]"));
+    assert!(tree.contains("dn (Dose number): 2 [1 byte]"));
+}
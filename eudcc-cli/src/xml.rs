@@ -0,0 +1,154 @@
+use ciborium::value::Value;
+use eudcc_core::eudcc::hex;
+
+const INDENT: &str = "  ";
+const HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+
+/// Escape text for element content and attribute values. Control
+/// characters are not allowed in XML 1.0 documents, not even as character
+/// references, so they are replaced.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c.is_control() => out.push('\u{fffd}'),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+fn is_name(text: &str) -> bool {
+    let mut chars = text.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => Some(String::new()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Integer(i) => Some(i128::from(*i).to_string()),
+        Value::Float(f) => Some(f.to_string()),
+        Value::Text(text) => Some(escape(text)),
+        Value::Bytes(bytes) => Some(hex(bytes)),
+        Value::Tag(_, value) => scalar(value),
+        _ => None,
+    }
+}
+
+fn indent(out: &mut String, level: usize) {
+    for _ in 0..level {
+        out.push_str(INDENT);
+    }
+}
+
+/// Write an element, given its opening tag content and its name.
+fn write_element(
+    out: &mut String,
+    open: &str,
+    name: &str,
+    value: &Value,
+    level: usize,
+) {
+    indent(out, level);
+    match (value, scalar(value)) {
+        (Value::Null, _) => {
+            out.push_str(&format!("<{}/>\n", open));
+            return;
+        }
+        (_, Some(text)) => {
+            out.push_str(&format!("<{}>{}</{}>\n", open, text, name));
+            return;
+        }
+        _ => {}
+    }
+
+    out.push_str(&format!("<{}>\n", open));
+    write_children(out, value, level + 1);
+    indent(out, level);
+    out.push_str(&format!("</{}>\n", name));
+}
+
+fn write_children(out: &mut String, value: &Value, level: usize) {
+    match value {
+        Value::Map(entries) => {
+            for (key, value) in entries {
+                let (open, name) = match key.as_text() {
+                    Some(text) if is_name(text) => (text.to_string(), text),
+                    _ => {
+                        let key = scalar(key).unwrap_or_default();
+                        (format!("entry key=\"{}\"", key), "entry")
+                    }
+                };
+                match value.as_array() {
+                    // Every item of an array is an element named after it.
+                    Some(items) => {
+                        for item in items {
+                            write_element(out, &open, name, item, level);
+                        }
+                    }
+                    None => write_element(out, &open, name, value, level),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                write_element(out, "item", "item", item, level);
+            }
+        }
+        Value::Tag(_, value) => write_children(out, value, level),
+        _ => {}
+    }
+}
+
+/// Write a CBOR value as an XML document with the given root element.
+pub fn to_string(root: &str, value: &Value) -> String {
+    let mut out = HEADER.to_string();
+    write_element(&mut out, root, root, value, 0);
+
+    out
+}
+
+#[test]
+fn xml_test() {
+    let record = Value::Map(vec![("tg".into(), "840539006".into())]);
+    let value = Value::Map(vec![
+        ("ver".into(), "1.0.0".into()),
+        (
+            "nam".into(),
+            Value::Map(vec![("fn".into(), "<Di> & \u{1b}Caprio".into())]),
+        ),
+        ("v".into(), Value::Array(vec![record.clone(), record])),
+        ("r".into(), Value::Array(vec![])),
+        (Value::from(-260), Value::Null),
+        ("a key".into(), Value::Bytes(vec![0xde, 0xad])),
+    ]);
+
+    assert_eq!(
+        to_string("certificate", &value),
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<certificate>
+  <ver>1.0.0</ver>
+  <nam>
+    <fn>&lt;Di&gt; &amp; \u{fffd}Caprio</fn>
+  </nam>
+  <v>
+    <tg>840539006</tg>
+  </v>
+  <v>
+    <tg>840539006</tg>
+  </v>
+  <entry key=\"-260\"/>
+  <entry key=\"a key\">dead</entry>
+</certificate>
+"
+    );
+}
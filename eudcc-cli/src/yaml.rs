@@ -0,0 +1,169 @@
+use ciborium::value::Value;
+use eudcc_core::eudcc::hex;
+
+use crate::json;
+
+const INDENT: &str = "  ";
+// Plain scalars YAML parsers would read as booleans or null.
+const RESERVED: &[&str] = &[
+    "y", "n", "yes", "no", "on", "off", "true", "false", "null", "~",
+];
+
+fn indent(out: &mut String, level: usize) {
+    for _ in 0..level {
+        out.push_str(INDENT);
+    }
+}
+
+fn is_plain(text: &str) -> bool {
+    let mut chars = text.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        && !RESERVED.contains(&text.to_ascii_lowercase().as_str())
+}
+
+fn key(key: &Value) -> String {
+    match key {
+        Value::Text(text) if is_plain(text) => text.clone(),
+        Value::Text(text) => json::string(text),
+        Value::Integer(i) => i128::from(*i).to_string(),
+        key => json::string(&scalar(key)),
+    }
+}
+
+/// Strings are always double quoted, with the same escapes as JSON, so
+/// that codes like `840539006` stay strings.
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Integer(i) => i128::from(*i).to_string(),
+        Value::Float(f) if f.is_nan() => ".nan".to_string(),
+        Value::Float(f) if f.is_infinite() && *f > 0.0 => ".inf".to_string(),
+        Value::Float(f) if f.is_infinite() => "-.inf".to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Text(text) => json::string(text),
+        Value::Bytes(bytes) => json::string(&hex(bytes)),
+        Value::Tag(_, value) => scalar(value),
+        Value::Array(_) => "[]".to_string(),
+        Value::Map(_) => "{}".to_string(),
+        _ => "null".to_string(),
+    }
+}
+
+fn untagged(value: &Value) -> &Value {
+    match value {
+        Value::Tag(_, value) => untagged(value),
+        value => value,
+    }
+}
+
+/// Write the value of a mapping entry, or of a sequence item, after its
+/// key or dash.
+fn write_child(out: &mut String, value: &Value, level: usize) {
+    match untagged(value) {
+        Value::Map(entries) if !entries.is_empty() => {
+            out.push('\n');
+            write_map(out, entries, level, false);
+        }
+        Value::Array(items) if !items.is_empty() => {
+            out.push('\n');
+            write_sequence(out, items, level);
+        }
+        value => {
+            out.push(' ');
+            out.push_str(&scalar(value));
+            out.push('\n');
+        }
+    }
+}
+
+fn write_map(
+    out: &mut String,
+    entries: &[(Value, Value)],
+    level: usize,
+    inline_first: bool,
+) {
+    for (i, (k, v)) in entries.iter().enumerate() {
+        if !(inline_first && i == 0) {
+            indent(out, level);
+        }
+        out.push_str(&key(k));
+        out.push(':');
+        write_child(out, v, level + 1);
+    }
+}
+
+fn write_sequence(out: &mut String, items: &[Value], level: usize) {
+    for item in items {
+        indent(out, level);
+        out.push('-');
+        match untagged(item) {
+            // The first entry of a mapping goes on the line of the dash.
+            Value::Map(entries) if !entries.is_empty() => {
+                out.push(' ');
+                write_map(out, entries, level + 1, true);
+            }
+            item => write_child(out, item, level + 1),
+        }
+    }
+}
+
+/// Write a CBOR value as a block style YAML document.
+pub fn to_string(value: &Value) -> String {
+    let mut out = String::new();
+    match untagged(value) {
+        Value::Map(entries) if !entries.is_empty() => {
+            write_map(&mut out, entries, 0, false)
+        }
+        Value::Array(items) if !items.is_empty() => {
+            write_sequence(&mut out, items, 0)
+        }
+        value => {
+            out.push_str(&scalar(value));
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+#[test]
+fn yaml_test() {
+    let record = Value::Map(vec![
+        ("tg".into(), "840539006".into()),
+        ("dn".into(), 2.into()),
+    ]);
+    let value = Value::Map(vec![
+        ("ver".into(), "1.0.0".into()),
+        (
+            "nam".into(),
+            Value::Map(vec![("fn".into(), "Di \"Caprio\"\n".into())]),
+        ),
+        ("v".into(), Value::Array(vec![record.clone(), record])),
+        ("r".into(), Value::Array(vec![])),
+        ("no".into(), Value::Array(vec![1.into(), Value::Null])),
+        (Value::from(-260), Value::Bytes(vec![0xde, 0xad])),
+        ("a key".into(), true.into()),
+    ]);
+
+    assert_eq!(
+        to_string(&value),
+        r#"ver: "1.0.0"
+nam:
+  fn: "Di \"Caprio\"\n"
+v:
+  - tg: "840539006"
+    dn: 2
+  - tg: "840539006"
+    dn: 2
+r: []
+"no":
+  - 1
+  - null
+-260: "dead"
+"a key": true
+"#
+    );
+    assert_eq!(to_string(&Value::Text("ok".into())), "\"ok\"\n");
+}
@@ -0,0 +1,440 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use eudcc_verify::date::Date;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// A UTC offset, in seconds, with its abbreviation, like `CEST`.
+#[derive(Clone, Debug, PartialEq)]
+struct LocalTime {
+    offset: i64,
+    abbreviation: String,
+}
+
+/// When daylight saving time starts or ends: the given weekday, 0 being
+/// Sunday, of the given week of a month, 5 being the last one, at a time
+/// of the day.
+#[derive(Clone, Debug, PartialEq)]
+struct Change {
+    month: u8,
+    week: u8,
+    weekday: u8,
+    time: i64,
+}
+
+impl Change {
+    /// The Unix timestamp of the change in a year, for a zone `offset`
+    /// seconds ahead of UTC before it.
+    fn instant(&self, year: i32, offset: i64) -> i64 {
+        let first = Date {
+            year,
+            month: self.month,
+            day: 1,
+        };
+        let next = match self.month {
+            12 => Date {
+                year: year + 1,
+                month: 1,
+                day: 1,
+            },
+            month => Date {
+                year,
+                month: month + 1,
+                day: 1,
+            },
+        };
+        let days_in_month = next.days_since(first);
+
+        // 1970-01-01 was a Thursday.
+        let first_weekday = (first.days_since_epoch() + 4).rem_euclid(7);
+        let mut day = (i64::from(self.weekday) - first_weekday).rem_euclid(7)
+            + 7 * (i64::from(self.week) - 1);
+        while day >= days_in_month {
+            day -= 7;
+        }
+
+        (first.days_since_epoch() + day) * SECONDS_PER_DAY + self.time - offset
+    }
+}
+
+/// A POSIX TZ rule, like `CET-1CEST,M3.5.0,M10.5.0/3`, with the standard
+/// time and, if any, the daylight saving one with when it starts and
+/// ends. Only the `Mm.w.d` form of the changes is supported.
+#[derive(Clone, Debug, PartialEq)]
+struct Rule {
+    standard: LocalTime,
+    daylight: Option<(LocalTime, Change, Change)>,
+}
+
+struct Parser<'a>(&'a str);
+
+impl<'a> Parser<'a> {
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let end = self.0.find(|c| !f(c)).unwrap_or(self.0.len());
+        let (taken, rest) = self.0.split_at(end);
+        self.0 = rest;
+        taken
+    }
+
+    fn expect(&mut self, c: char) -> Option<()> {
+        self.0 = self.0.strip_prefix(c)?;
+        Some(())
+    }
+
+    fn number(&mut self) -> Option<i64> {
+        self.take_while(|c| c.is_ascii_digit()).parse().ok()
+    }
+
+    /// An abbreviation, alphabetic or quoted between `<` and `>`.
+    fn name(&mut self) -> Option<String> {
+        let name = if self.expect('<').is_some() {
+            let name = self.take_while(|c| c != '>');
+            self.expect('>')?;
+            name
+        } else {
+            self.take_while(|c| c.is_ascii_alphabetic())
+        };
+        if name.len() < 3 {
+            return None;
+        }
+
+        Some(name.to_string())
+    }
+
+    /// A signed `hh[:mm[:ss]]` time, in seconds, of at most 167 hours as
+    /// POSIX allows for the times of the changes.
+    fn time(&mut self) -> Option<i64> {
+        let sign = if self.expect('-').is_some() {
+            -1
+        } else {
+            let _ = self.expect('+');
+            1
+        };
+        let mut seconds = self.number()?.checked_mul(3600)?;
+        for unit in [60, 1] {
+            if self.expect(':').is_none() {
+                break;
+            }
+            seconds = seconds.checked_add(self.number()?.checked_mul(unit)?)?;
+        }
+        if seconds >= 168 * 3600 {
+            return None;
+        }
+
+        Some(sign * seconds)
+    }
+
+    fn change(&mut self) -> Option<Change> {
+        self.expect('M')?;
+        let month = self.number()?;
+        self.expect('.')?;
+        let week = self.number()?;
+        self.expect('.')?;
+        let weekday = self.number()?;
+        let time = match self.expect('/') {
+            Some(()) => self.time()?,
+            None => 2 * 3600,
+        };
+        if !(1..=12).contains(&month) || !(1..=5).contains(&week) || weekday > 6
+        {
+            return None;
+        }
+
+        Some(Change {
+            month: month as u8,
+            week: week as u8,
+            weekday: weekday as u8,
+            time,
+        })
+    }
+}
+
+impl Rule {
+    fn parse(rule: &str) -> Option<Rule> {
+        let mut parser = Parser(rule);
+        // POSIX offsets are the time to add to get to UTC.
+        let standard = LocalTime {
+            abbreviation: parser.name()?,
+            offset: -parser.time()?,
+        };
+        if parser.0.is_empty() {
+            return Some(Rule {
+                standard,
+                daylight: None,
+            });
+        }
+
+        let abbreviation = parser.name()?;
+        let offset = if parser.0.starts_with(',') {
+            standard.offset + 3600
+        } else {
+            -parser.time()?
+        };
+        parser.expect(',')?;
+        let start = parser.change()?;
+        parser.expect(',')?;
+        let end = parser.change()?;
+        if !parser.0.is_empty() {
+            return None;
+        }
+
+        let daylight = LocalTime {
+            offset,
+            abbreviation,
+        };
+        Some(Rule {
+            standard,
+            daylight: Some((daylight, start, end)),
+        })
+    }
+
+    fn at(&self, timestamp: i64) -> &LocalTime {
+        let (daylight, start, end) = match &self.daylight {
+            Some(daylight) => daylight,
+            None => return &self.standard,
+        };
+        let year = Date::from_timestamp(timestamp + self.standard.offset).year;
+        let start = start.instant(year, self.standard.offset);
+        let end = end.instant(year, daylight.offset);
+        // South of the equator daylight saving time spans the new year.
+        let in_daylight = if start < end {
+            start <= timestamp && timestamp < end
+        } else {
+            !(end <= timestamp && timestamp < start)
+        };
+
+        if in_daylight {
+            daylight
+        } else {
+            &self.standard
+        }
+    }
+}
+
+/// The transitions of a TZif data block, each a time and the index of the
+/// local time type starting then, and the local time types.
+type Block = (Vec<(i64, usize)>, Vec<LocalTime>);
+
+/// Reads the big endian fields of a TZif file.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if n > self.0.len() {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    fn integer(&mut self, size: usize) -> Option<i64> {
+        let bytes = self.take(size)?;
+        let first = i64::from(*bytes.first()? as i8);
+        Some(bytes[1..].iter().fold(first, |n, b| n << 8 | i64::from(*b)))
+    }
+
+    /// The version and the counts of the header: UT/local indicators,
+    /// standard/wall indicators, leap seconds, transitions, local time
+    /// types and abbreviation bytes.
+    fn header(&mut self) -> Option<(u8, [usize; 6])> {
+        if self.take(4)? != b"TZif" {
+            return None;
+        }
+        let version = *self.take(16)?.first()?;
+        let mut counts = [0; 6];
+        for count in counts.iter_mut() {
+            *count = usize::try_from(self.integer(4)?).ok()?;
+        }
+
+        Some((version, counts))
+    }
+
+    /// A data block, whose times are `size` bytes long.
+    fn block(&mut self, counts: [usize; 6], size: usize) -> Option<Block> {
+        let [ut_count, std_count, leap_count, time_count, type_count, chars] =
+            counts;
+        let mut times = Vec::with_capacity(time_count);
+        for _ in 0..time_count {
+            times.push(self.integer(size)?);
+        }
+        let indices = self.take(time_count)?;
+        let mut types = Vec::with_capacity(type_count);
+        for _ in 0..type_count {
+            let offset = self.integer(4)?;
+            let info = self.take(2)?;
+            types.push((offset, usize::from(info[1])));
+        }
+        let chars = self.take(chars)?;
+        self.take(leap_count * (size + 4))?;
+        self.take(std_count + ut_count)?;
+
+        let types = types
+            .into_iter()
+            .map(|(offset, start)| {
+                let name = chars.get(start..).unwrap_or_default();
+                let end = name.iter().position(|&b| b == 0);
+                let name = &name[..end.unwrap_or(name.len())];
+                LocalTime {
+                    offset,
+                    abbreviation: String::from_utf8_lossy(name).into_owned(),
+                }
+            })
+            .collect();
+        let transitions = times
+            .into_iter()
+            .zip(indices.iter().map(|&i| usize::from(i)))
+            .collect();
+
+        Some((transitions, types))
+    }
+}
+
+/// A time zone, with its past transitions and the rule for the times
+/// after them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeZone {
+    transitions: Vec<(i64, usize)>,
+    types: Vec<LocalTime>,
+    rule: Option<Rule>,
+}
+
+impl TimeZone {
+    /// Parse a TZif file, as found in `/usr/share/zoneinfo`.
+    fn parse(data: &[u8]) -> Option<TimeZone> {
+        let mut reader = Reader(data);
+        let (version, counts) = reader.header()?;
+        if version == 0 {
+            let (transitions, types) = reader.block(counts, 4)?;
+            return Some(TimeZone {
+                transitions,
+                types,
+                rule: None,
+            });
+        }
+
+        // Newer versions repeat the data with 64-bit times, followed by a
+        // rule between newlines.
+        reader.block(counts, 4)?;
+        let (_, counts) = reader.header()?;
+        let (transitions, types) = reader.block(counts, 8)?;
+        let footer = String::from_utf8_lossy(reader.0);
+        let rule = footer.split('\n').nth(1).and_then(Rule::parse);
+
+        Some(TimeZone {
+            transitions,
+            types,
+            rule,
+        })
+    }
+
+    /// The local time zone: the one named by `TZ`, or by a rule in it,
+    /// else `/etc/localtime`.
+    pub fn local() -> Option<TimeZone> {
+        let tz = match env::var("TZ") {
+            Ok(tz) => tz,
+            Err(_) => {
+                return TimeZone::parse(&fs::read("/etc/localtime").ok()?)
+            }
+        };
+        let name = tz.strip_prefix(':').unwrap_or(&tz);
+        let path = if name.starts_with('/') {
+            PathBuf::from(name)
+        } else {
+            Path::new("/usr/share/zoneinfo").join(name)
+        };
+        match fs::read(path) {
+            Ok(data) => TimeZone::parse(&data),
+            Err(_) => TimeZone::from_rule(name),
+        }
+    }
+
+    /// A time zone following a POSIX TZ rule, like
+    /// `CET-1CEST,M3.5.0,M10.5.0/3`.
+    pub fn from_rule(rule: &str) -> Option<TimeZone> {
+        Some(TimeZone {
+            transitions: vec![],
+            types: vec![],
+            rule: Some(Rule::parse(rule)?),
+        })
+    }
+
+    /// The UTC offset, in seconds, and the abbreviation of the zone at a
+    /// Unix timestamp.
+    pub fn at(&self, timestamp: i64) -> (i64, &str) {
+        if let Some(rule) = &self.rule {
+            match self.transitions.last() {
+                Some((last, _)) if timestamp < *last => {}
+                _ => {
+                    let local = rule.at(timestamp);
+                    return (local.offset, &local.abbreviation);
+                }
+            }
+        }
+        let index = self
+            .transitions
+            .iter()
+            .rev()
+            .find(|(time, _)| *time <= timestamp)
+            .map_or(0, |(_, index)| *index);
+
+        match self.types.get(index).or_else(|| self.types.first()) {
+            Some(local) => (local.offset, &local.abbreviation),
+            None => (0, "UTC"),
+        }
+    }
+}
+
+#[test]
+fn rule_test() {
+    let zone = TimeZone::from_rule("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+    // 2021-05-03T10:27:15Z, and the changes of 2021, on 28 March at 01:00
+    // UTC and on 31 October at 01:00 UTC.
+    assert_eq!(zone.at(1_620_037_635), (7200, "CEST"));
+    assert_eq!(zone.at(1_610_000_000), (3600, "CET"));
+    assert_eq!(zone.at(1_616_893_199), (3600, "CET"));
+    assert_eq!(zone.at(1_616_893_200), (7200, "CEST"));
+    assert_eq!(zone.at(1_635_641_999), (7200, "CEST"));
+    assert_eq!(zone.at(1_635_642_000), (3600, "CET"));
+
+    let sydney = Rule::parse("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+    assert_eq!(sydney.at(1_610_000_000).abbreviation, "AEDT");
+    assert_eq!(sydney.at(1_620_037_635).abbreviation, "AEST");
+
+    let india = Rule::parse("<+0530>-5:30").unwrap();
+    assert_eq!(india.at(0).offset, 19_800);
+    assert_eq!(india.at(0).abbreviation, "+0530");
+
+    // Times too large to be offsets, or even to be held.
+    assert!(Rule::parse("CET-168").is_none());
+    assert!(Rule::parse("CET-9999999999999999").is_none());
+    assert!(Rule::parse("CET-1:9999999999999999").is_none());
+    assert!(Rule::parse("CET-1CEST,M3.5.0/9999999999999999,M10.5.0").is_none());
+    assert!(Rule::parse("CET").is_none());
+    assert!(Rule::parse("CET-1CEST").is_none());
+    assert!(Rule::parse("CET-1CEST,J60,J300").is_none());
+}
+
+#[test]
+fn tzif_test() {
+    let mut data = b"TZif".to_vec();
+    data.extend_from_slice(&[0; 16]);
+    for count in [0u32, 0, 0, 1, 2, 9] {
+        data.extend_from_slice(&count.to_be_bytes());
+    }
+    data.extend_from_slice(&1_616_893_200u32.to_be_bytes());
+    data.push(1);
+    data.extend_from_slice(&3600u32.to_be_bytes());
+    data.extend_from_slice(&[0, 0]);
+    data.extend_from_slice(&7200u32.to_be_bytes());
+    data.extend_from_slice(&[1, 4]);
+    data.extend_from_slice(b"CET\0CEST\0");
+
+    let zone = TimeZone::parse(&data).unwrap();
+    assert_eq!(zone.at(1_616_893_199), (3600, "CET"));
+    assert_eq!(zone.at(1_616_893_200), (7200, "CEST"));
+    assert!(TimeZone::parse(&data[..data.len() - 1]).is_none());
+    assert!(TimeZone::parse(b"TZ").is_none());
+}
@@ -0,0 +1,35 @@
+//! The minimal binary gets embedded in kiosk images, so it has to stay
+//! small. Check it with:
+//!
+//! ```text
+//! cargo test -p eudcc-cli --profile minimal --no-default-features --test size
+//! ```
+use std::fs;
+
+// Size budget of the minimal binary, in bytes.
+const SIZE_BUDGET: u64 = 768 * 1024;
+
+#[test]
+#[cfg_attr(
+    any(
+        debug_assertions,
+        feature = "formats",
+        feature = "corpus",
+        feature = "validity",
+        feature = "network",
+        feature = "encode"
+    ),
+    ignore
+)]
+fn minimal_size_test() {
+    let path = env!("CARGO_BIN_EXE_eudccdec");
+    let size = fs::metadata(path).unwrap().len();
+
+    assert!(
+        size <= SIZE_BUDGET,
+        "{} is {} bytes, over the budget of {}",
+        path,
+        size,
+        SIZE_BUDGET
+    );
+}
@@ -0,0 +1,323 @@
+//! Base45 decoding, with the table-driven decoder of the `fast-base45`
+//! feature, or with the `base45` crate without it.
+
+#[cfg(not(feature = "fast-base45"))]
+use std::io::Cursor;
+use std::io::{self, Read};
+
+use crate::error::Position;
+
+const ALPHABET: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+#[cfg(feature = "fast-base45")]
+const CHUNK_SIZE: usize = 3;
+#[cfg(feature = "fast-base45")]
+const INPUT_BUFFER_SIZE: usize = 64 * CHUNK_SIZE;
+#[cfg(feature = "fast-base45")]
+const INVALID: u8 = 0xff;
+#[cfg(feature = "fast-base45")]
+const DECODE_TABLE: [u8; 256] = decode_table();
+
+/// Map every byte to its base45 digit, or to INVALID.
+// Evaluated at compile time, so indexing cannot panic at run time.
+#[cfg(feature = "fast-base45")]
+#[allow(clippy::indexing_slicing)]
+const fn decode_table() -> [u8; 256] {
+    let mut table = [INVALID; 256];
+    let mut i = 0;
+    while i < ALPHABET.len() {
+        table[ALPHABET[i] as usize] = i as u8;
+        i += 1;
+    }
+
+    table
+}
+
+/// Streaming base45 decoder.
+///
+/// Reads base45 text from the inner reader and yields the decoded bytes,
+/// using only fixed-size buffers so that memory usage does not depend on
+/// the size of the input.
+#[cfg(feature = "fast-base45")]
+pub struct Base45Reader<R> {
+    inner: R,
+    input: [u8; INPUT_BUFFER_SIZE],
+    // Input consumed before the start of the input buffer.
+    consumed: u64,
+    start: usize,
+    end: usize,
+    eof: bool,
+    output: [u8; 2],
+    output_start: usize,
+    output_end: usize,
+}
+
+#[cfg(feature = "fast-base45")]
+impl<R: Read> Base45Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Base45Reader {
+            inner,
+            input: [0; INPUT_BUFFER_SIZE],
+            consumed: 0,
+            start: 0,
+            end: 0,
+            eof: false,
+            output: [0; 2],
+            output_start: 0,
+            output_end: 0,
+        }
+    }
+
+    /// Make sure at least a whole chunk is buffered, unless the inner
+    /// reader is exhausted.
+    fn fill(&mut self) -> io::Result<()> {
+        while !self.eof && self.end - self.start < CHUNK_SIZE {
+            if self.start > 0 {
+                self.input.copy_within(self.start..self.end, 0);
+                self.end -= self.start;
+                self.consumed += self.start as u64;
+                self.start = 0;
+            }
+            // Never empty: less than a chunk is buffered at this point.
+            let free = self.input.get_mut(self.end..).unwrap_or_default();
+            match self.inner.read(free) {
+                Ok(0) => self.eof = true,
+                Ok(n) => self.end += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The buffered input not decoded yet.
+    fn pending(&self) -> &[u8] {
+        self.input.get(self.start..self.end).unwrap_or_default()
+    }
+
+    /// Decode the next chunk into the output buffer, returning false at
+    /// the end of the input.
+    fn decode_chunk(&mut self) -> io::Result<bool> {
+        self.fill()?;
+
+        let pending = self.pending();
+        let chunk = pending.get(..CHUNK_SIZE).unwrap_or(pending);
+        let len = chunk.len();
+        let value = chunk_value(chunk)?;
+
+        // The chunk is only consumed once decoded, so that errors point at
+        // its start.
+        match len {
+            0 => return Ok(false),
+            1 => return Err(invalid_data("invalid base45 length")),
+            2 if value <= 0xff => {
+                self.output = [value as u8, 0];
+                self.output_end = 1;
+            }
+            3 if value <= 0xffff => {
+                self.output = (value as u16).to_be_bytes();
+                self.output_end = 2;
+            }
+            _ => return Err(invalid_data("invalid base45 chunk")),
+        }
+        self.start += len;
+        self.output_start = 0;
+
+        Ok(true)
+    }
+
+    /// Copy pending output into `buf`, returning the number of bytes copied.
+    fn drain_output(&mut self, buf: &mut [u8]) -> usize {
+        let pending = self
+            .output
+            .get(self.output_start..self.output_end)
+            .unwrap_or_default();
+        let n = pending.len().min(buf.len());
+        if let (Some(to), Some(from)) = (buf.get_mut(..n), pending.get(..n)) {
+            to.copy_from_slice(from);
+        }
+        self.output_start += n;
+
+        n
+    }
+}
+
+#[cfg(feature = "fast-base45")]
+impl<R: Read> Read for Base45Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut written = self.drain_output(buf);
+        while written < buf.len() {
+            self.fill()?;
+
+            // Decode whole chunks straight into the caller's buffer.
+            while let (Some(chunk), Some(out)) = (
+                self.pending().get(..CHUNK_SIZE),
+                buf.get_mut(written..written + 2),
+            ) {
+                let value = chunk_value(chunk)?;
+                if value > 0xffff {
+                    return Err(invalid_data("invalid base45 chunk"));
+                }
+                out.copy_from_slice(&(value as u16).to_be_bytes());
+                self.start += CHUNK_SIZE;
+                written += 2;
+            }
+            if written == buf.len() {
+                break;
+            }
+
+            // The trailing chunk, and chunks not fitting in the caller's
+            // buffer, go through the output buffer.
+            if !self.decode_chunk()? {
+                break;
+            }
+            let rest = buf.get_mut(written..).unwrap_or_default();
+            written += self.drain_output(rest);
+        }
+
+        Ok(written)
+    }
+}
+
+#[cfg(feature = "fast-base45")]
+impl<R> Position for Base45Reader<R> {
+    fn position(&self) -> u64 {
+        self.consumed + self.start as u64
+    }
+}
+
+/// Base45 decoder going through the `base45` crate.
+///
+/// The crate decodes whole strings, so the whole input is read and decoded
+/// at the first read, and errors are at offset 0.
+#[cfg(not(feature = "fast-base45"))]
+pub struct Base45Reader<R> {
+    inner: Option<R>,
+    decoded: Cursor<Vec<u8>>,
+}
+
+#[cfg(not(feature = "fast-base45"))]
+impl<R: Read> Base45Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Base45Reader {
+            inner: Some(inner),
+            decoded: Cursor::new(Vec::new()),
+        }
+    }
+}
+
+#[cfg(not(feature = "fast-base45"))]
+impl<R: Read> Read for Base45Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(mut inner) = self.inner.take() {
+            let mut text = String::new();
+            inner
+                .read_to_string(&mut text)
+                .map_err(|_| invalid_data("invalid base45 character"))?;
+            let decoded = base45::decode(&text)
+                .map_err(|e| invalid_data(&e.to_string()))?;
+            self.decoded = Cursor::new(decoded);
+        }
+
+        self.decoded.read(buf)
+    }
+}
+
+#[cfg(not(feature = "fast-base45"))]
+impl<R> Position for Base45Reader<R> {
+    fn position(&self) -> u64 {
+        0
+    }
+}
+
+#[cfg(feature = "fast-base45")]
+fn chunk_value(chunk: &[u8]) -> io::Result<u32> {
+    chunk.iter().rev().try_fold(0, |acc, &c| {
+        match DECODE_TABLE.get(usize::from(c)) {
+            Some(&digit) if digit != INVALID => Ok(acc * 45 + u32::from(digit)),
+            _ => Err(invalid_data("invalid base45 character")),
+        }
+    })
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Encode some bytes as base45.
+pub(crate) fn encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len() / 2 * 3 + 2);
+    for chunk in data.chunks(2) {
+        let (mut value, len) = match *chunk {
+            [a, b] => (u32::from(a) << 8 | u32::from(b), 3),
+            [a] => (u32::from(a), 2),
+            _ => continue,
+        };
+        for _ in 0..len {
+            // The remainder is always a digit of the alphabet.
+            let digit = ALPHABET.get((value % 45) as usize);
+            encoded.extend(digit.map(|&b| char::from(b)));
+            value /= 45;
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+fn decode_all(data: &str) -> io::Result<Vec<u8>> {
+    let mut decoded = Vec::new();
+    Base45Reader::new(data.as_bytes()).read_to_end(&mut decoded)?;
+    Ok(decoded)
+}
+
+#[test]
+fn base45_reader_test() {
+    // Examples taken from draft-faltstrom-base45.
+    assert_eq!(decode_all("BB8").unwrap(), b"AB");
+    assert_eq!(decode_all("%69 VD92EX0").unwrap(), b"Hello!!");
+    assert_eq!(decode_all("UJCLQE7W581").unwrap(), b"base-45");
+    assert_eq!(decode_all("QED8WEX0").unwrap(), b"ietf!");
+    assert_eq!(decode_all("").unwrap(), b"");
+
+    assert_eq!(encode(b"ietf!"), "QED8WEX0");
+    assert_eq!(decode_all(&encode(b"Hello!!")).unwrap(), b"Hello!!");
+}
+
+#[test]
+fn base45_reader_long_input_test() {
+    // Longer than the input buffer, to exercise refilling.
+    let data = "BB8".repeat(100);
+    assert_eq!(decode_all(&data).unwrap(), b"AB".repeat(100));
+}
+
+#[test]
+fn base45_reader_small_reads_test() {
+    // Reading one byte at a time always goes through the output buffer.
+    let mut reader = Base45Reader::new("%69 VD92EX0".as_bytes());
+    let mut decoded = Vec::new();
+    let mut byte = [0; 1];
+    while reader.read(&mut byte).unwrap() == 1 {
+        decoded.push(byte[0]);
+    }
+    assert_eq!(decoded, b"Hello!!");
+}
+
+#[test]
+fn base45_reader_invalid_test() {
+    assert!(decode_all("BB8B").is_err());
+    assert!(decode_all("bb8").is_err());
+    // The base45 crate accepts chunks up to 65792, past a u16.
+    #[cfg(feature = "fast-base45")]
+    assert!(decode_all("GGW").is_err());
+    assert!(decode_all(":::").is_err());
+}
+
+#[cfg(feature = "fast-base45")]
+#[test]
+fn base45_reader_position_test() {
+    let data = format!("{}bb8", "BB8".repeat(100));
+    let mut reader = Base45Reader::new(data.as_bytes());
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+    assert_eq!(reader.position(), 300);
+}
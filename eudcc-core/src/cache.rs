@@ -0,0 +1,184 @@
+//! A cache of decoded payloads, for programs decoding the same
+//! certificates again and again, like re-validation sweeps.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::error::{DecodeError, Result};
+use crate::eudcc::{
+    base45_text_with, check_payload, decode_base45, decompress_with,
+    parse_cose, parse_hcert, DecodeOptions, Payload,
+};
+use crate::sha256::sha256;
+
+struct Entry {
+    payload: Rc<Payload>,
+    decompressed_len: u64,
+    used: u64,
+}
+
+/// A least recently used cache of decoded payloads, keyed by the SHA-256
+/// of their base45 text, so that decoding a certificate again skips the
+/// base45, zlib and CBOR stages.
+///
+/// Only the stages are skipped: the options are applied to every decoding,
+/// hit or not, and failures are never cached.
+///
+/// ```no_run
+/// use eudcc_core::{DecodeCache, DecodeOptions};
+///
+/// let data = std::fs::read("certificate.txt").unwrap();
+/// let mut cache = DecodeCache::new(1024);
+/// let options = DecodeOptions::new().now(1_640_995_200);
+/// for _ in 0..2 {
+///     // The second time, only the claims are checked again.
+///     let payload = cache.decode_payload_with(&data, &options).unwrap();
+///     println!("{}", payload.issuer);
+/// }
+/// ```
+pub struct DecodeCache {
+    capacity: usize,
+    entries: HashMap<[u8; 32], Entry>,
+    uses: u64,
+}
+
+impl DecodeCache {
+    /// A cache keeping up to `capacity` payloads, none if 0.
+    pub fn new(capacity: usize) -> Self {
+        DecodeCache {
+            capacity,
+            entries: HashMap::new(),
+            uses: 0,
+        }
+    }
+
+    /// The number of payloads in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Decode the whole CWT payload, given as raw bytes, as controlled by
+    /// `options`, as [`decode_payload_with`](crate::decode_payload_with)
+    /// does, or take it from the cache if it was decoded before.
+    pub fn decode_payload_with(
+        &mut self,
+        data: &[u8],
+        options: &DecodeOptions,
+    ) -> Result<Rc<Payload>> {
+        let text = base45_text_with(data, options)?;
+        let key = sha256(text);
+        self.uses += 1;
+
+        if let Some(entry) = self.entries.get_mut(&key) {
+            entry.used = self.uses;
+            let limit = options.max_decompressed_len.unwrap_or(u64::MAX);
+            if entry.decompressed_len > limit {
+                return Err(DecodeError::DecompressedTooLarge { limit });
+            }
+            check_payload(&entry.payload, options)?;
+            return Ok(Rc::clone(&entry.payload));
+        }
+
+        let decompressed = decompress_with(&decode_base45(text)?, options)?;
+        let payload = parse_hcert(&parse_cose(&decompressed)?.payload)?;
+        check_payload(&payload, options)?;
+        let payload = Rc::new(payload);
+        self.insert(key, &payload, decompressed.len() as u64);
+
+        Ok(payload)
+    }
+
+    fn insert(&mut self, key: [u8; 32], payload: &Rc<Payload>, len: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        let entry = Entry {
+            payload: Rc::clone(payload),
+            decompressed_len: len,
+            used: self.uses,
+        };
+        self.entries.insert(key, entry);
+    }
+}
+
+#[test]
+fn decode_cache_test() {
+    use crate::eudcc::decode_payload_with;
+
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/2.json
+    // It is licensed under Apache-2.0 License.
+    let recovery_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *MEB2B2JJ59J-9BC6:X9NECX0AKQC:3DCV4*XUA2P-FHT-H4SI/J9WVHWVH+ZEOV1J$HNTICZUBOM*LP$V25$0Q:J40IA3L/*84-5%:C92JN*4CY0*%9F/8J2P4.818T+:IX3M3.96RPVD9J-OZT1-NT0 2$$0$2PZX69B9VCDHI2/T9TU1BPIJKH/T7B-S-*O/Y41FD+X49+5Z-6%.HDD8R6W1FDJGJSFJ/4Q:T0.KJTNP8EFULNC:HA0K5HKRB4TD85LOLF92GF.3O.Z8CC7-2FQYG$%21 2O*4R60NM8JI0EUGP$I/XK$M8ZQE6YB9M66P8N31I.ROSK%IA1Q2N53Q-OQ2VC6E26T11ROSNK5W-*H+MJ%0RGZVGWNURI75RBSQSHLH1JG*CMH2.-S$7VX6N*Z1881J7G.F9I+SV06F+1M*93%D";
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/1.json
+    // It is licensed under Apache-2.0 License.
+    let vaccination_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II5XHC9B5G2+$N IOP-IA%NFQGRJPC%OQHIZC4.OI1RM8ZA.A5:S9MKN4NN3F85QNCY0O%0VZ001HOC9JU0D0HT0HB2PL/IB*09B9LW4T*8+DCMH0LDK2%K:XFE70*LP$V25$0Q:J:4MO1P0%0L0HD+9E/HY+4J6TH48S%4K.GJ2PT3QY:GQ3TE2I+-CPHN6D7LLK*2HG%89UV-0LZ 2ZJJ524-LH/CJTK96L6SR9MU9DHGZ%P WUQRENS431T1XCNCF+47AY0-IFO0500TGPN8F5G.41Q2E4T8ALW.INSV$ 07UV5SR+BNQHNML7 /KD3TU 4V*CAT3ZGLQMI/XI%ZJNSBBXK2:UG%UJMI:TU+MMPZ5$/PMX19UE:-PSR3/$NU44CBE6DQ3D7B0FBOFX0DV2DGMB$YPF62I$60/F$Z2I6IFX21XNI-LM%3/DF/U6Z9FEOJVRLVW6K$UG+BKK57:1+D10%4K83F+1VWD1NE";
+    let options = DecodeOptions::new();
+
+    let mut cache = DecodeCache::new(1);
+    let recovery = cache
+        .decode_payload_with(recovery_data.as_bytes(), &options)
+        .unwrap();
+    assert_eq!(
+        *recovery,
+        decode_payload_with(recovery_data.as_bytes(), &options).unwrap()
+    );
+    assert_eq!(cache.len(), 1);
+
+    // Hits are the very same payload, trailing whitespace aside.
+    let data = format!("{}\n", recovery_data);
+    let hit = cache
+        .decode_payload_with(data.as_bytes(), &options)
+        .unwrap();
+    assert!(Rc::ptr_eq(&hit, &recovery));
+
+    // But they are still checked against the options.
+    let expired = options.clone().now(recovery.expires_at + 1);
+    let result = cache.decode_payload_with(recovery_data.as_bytes(), &expired);
+    assert!(matches!(result, Err(DecodeError::Expired { .. })));
+    let small = options.clone().max_decompressed_len(100);
+    let result = cache.decode_payload_with(recovery_data.as_bytes(), &small);
+    assert!(matches!(
+        result,
+        Err(DecodeError::DecompressedTooLarge { .. })
+    ));
+    let result = cache.decode_payload_with(b"6BFOXN", &options);
+    assert!(matches!(result, Err(DecodeError::MissingPrefix)));
+
+    // The least recently used payload makes room for the new ones.
+    cache
+        .decode_payload_with(vaccination_data.as_bytes(), &options)
+        .unwrap();
+    assert_eq!(cache.len(), 1);
+    let miss = cache
+        .decode_payload_with(recovery_data.as_bytes(), &options)
+        .unwrap();
+    assert!(!Rc::ptr_eq(&miss, &recovery));
+
+    // Failures are not kept, and nothing is without room.
+    assert!(cache.decode_payload_with(b"HC1:ABC", &options).is_err());
+    assert_eq!(cache.len(), 1);
+    let mut cache = DecodeCache::new(0);
+    cache
+        .decode_payload_with(recovery_data.as_bytes(), &options)
+        .unwrap();
+    assert!(cache.is_empty());
+}
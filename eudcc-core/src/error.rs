@@ -0,0 +1,375 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read};
+
+use ciborium::{de, value};
+use flate2::read::ZlibDecoder;
+
+use crate::eudcc::ExtensionKey;
+use crate::schema::Violation;
+
+pub type Result<T, E = DecodeError> = std::result::Result<T, E>;
+
+/// Error raised while decoding a certificate, one variant for every stage
+/// of the decoding pipeline.
+///
+/// Stages working on a known input report its length, in bytes, and the
+/// offset where they failed, so that failures can be diagnosed from logs.
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The data does not start with the `HC1:` prefix.
+    MissingPrefix,
+    /// The data is longer than allowed by the decoding options.
+    InputTooLarge { input_len: usize, limit: usize },
+    /// The inflated data is longer than allowed by the decoding options.
+    DecompressedTooLarge { limit: u64 },
+    /// The data is not valid base45.
+    ///
+    /// The offset is the one of the failing chunk in the base45 text.
+    Base45 {
+        error: io::Error,
+        input_len: usize,
+        offset: u64,
+    },
+    /// The base45 decoded data is not a valid zlib stream.
+    ///
+    /// The offset is the number of compressed bytes consumed so far.
+    Zlib {
+        error: io::Error,
+        input_len: usize,
+        offset: u64,
+    },
+    /// The inflated data is not valid CBOR.
+    Cbor {
+        error: de::Error<io::Error>,
+        offset: Option<usize>,
+    },
+    /// The CBOR data is not a valid COSE Single Signer Data Object.
+    Cose(String),
+    /// The COSE payload does not hold valid CWT claims.
+    Cwt {
+        error: de::Error<io::Error>,
+        input_len: usize,
+        offset: Option<usize>,
+    },
+    /// The hcert claim does not hold valid certificates.
+    Hcert(value::Error),
+    /// The hcert claim has no EU Digital COVID Certificate v1.
+    MissingCertificate,
+    /// The certificate has a field unknown to the schema, and the decoding
+    /// options reject them.
+    UnknownField(String),
+    /// The certificate expired before the time given in the decoding
+    /// options.
+    Expired { expires_at: u64, now: u64 },
+    /// The issuer is not accepted by the decoding options.
+    IssuerRejected(String),
+    /// The certificate violates its schema, and the decoding options are
+    /// strict. Every violation found is reported.
+    Invalid(Vec<Violation>),
+    /// The decoder of an extension failed.
+    Extension {
+        key: ExtensionKey,
+        error: Box<dyn Error + Send + Sync>,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::MissingPrefix => {
+                write!(f, "data must start with HC1: prefix")
+            }
+            DecodeError::InputTooLarge { input_len, limit } => write!(
+                f,
+                "input of {} bytes exceeds the limit of {} bytes",
+                input_len, limit
+            ),
+            DecodeError::DecompressedTooLarge { limit } => {
+                write!(f, "inflated data exceeds the limit of {} bytes", limit)
+            }
+            DecodeError::Base45 {
+                input_len, offset, ..
+            } => write!(
+                f,
+                "invalid base45 data at offset {} of {} input bytes",
+                offset, input_len
+            ),
+            DecodeError::Zlib {
+                input_len, offset, ..
+            } => write!(
+                f,
+                "invalid zlib data at offset {} of {} input bytes",
+                offset, input_len
+            ),
+            DecodeError::Cbor {
+                offset: Some(offset),
+                ..
+            } => write!(f, "invalid CBOR data at offset {}", offset),
+            DecodeError::Cbor { offset: None, .. } => {
+                write!(f, "invalid CBOR data")
+            }
+            DecodeError::Cose(message) => write!(f, "{}", message),
+            DecodeError::Cwt {
+                input_len,
+                offset: Some(offset),
+                ..
+            } => write!(
+                f,
+                "invalid CWT claims at offset {} of {} input bytes",
+                offset, input_len
+            ),
+            DecodeError::Cwt {
+                input_len,
+                offset: None,
+                ..
+            } => write!(f, "invalid CWT claims in {} input bytes", input_len),
+            DecodeError::Hcert(_) => write!(f, "invalid hcert claim"),
+            DecodeError::MissingCertificate => write!(
+                f,
+                "Can't find the EU Digital COVID Certificate v1 claim!"
+            ),
+            DecodeError::UnknownField(field) => {
+                write!(f, "unknown certificate field {}", field)
+            }
+            DecodeError::Expired { expires_at, now } => write!(
+                f,
+                "certificate expired at {}, before {}",
+                expires_at, now
+            ),
+            DecodeError::IssuerRejected(issuer) => {
+                write!(f, "certificates issued by {} are not accepted", issuer)
+            }
+            DecodeError::Invalid(violations) => match violations.len() {
+                1 => write!(f, "certificate has 1 schema violation"),
+                n => write!(f, "certificate has {} schema violations", n),
+            },
+            DecodeError::Extension { key, .. } => {
+                write!(f, "invalid extension in {}", key)
+            }
+        }
+    }
+}
+
+impl Error for DecodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DecodeError::Base45 { error, .. } => Some(error),
+            DecodeError::Zlib { error, .. } => Some(error),
+            DecodeError::Cbor { error, .. } => Some(error),
+            DecodeError::Cwt { error, .. } => Some(error),
+            DecodeError::Hcert(error) => Some(error),
+            DecodeError::Extension { error, .. } => Some(&**error),
+            _ => None,
+        }
+    }
+}
+
+impl DecodeError {
+    /// Name of the pipeline stage that failed.
+    pub fn stage(&self) -> &'static str {
+        match self {
+            DecodeError::MissingPrefix => "prefix",
+            DecodeError::InputTooLarge { .. } => "input",
+            DecodeError::Base45 { .. } => "base45",
+            DecodeError::Zlib { .. } => "zlib",
+            DecodeError::DecompressedTooLarge { .. } => "zlib",
+            DecodeError::Cbor { .. } => "cbor",
+            DecodeError::Cose(_) => "cose",
+            DecodeError::Cwt { .. }
+            | DecodeError::Expired { .. }
+            | DecodeError::IssuerRejected(_) => "cwt",
+            DecodeError::Hcert(_)
+            | DecodeError::MissingCertificate
+            | DecodeError::UnknownField(_)
+            | DecodeError::Invalid(_) => "hcert",
+            DecodeError::Extension {
+                key: ExtensionKey::Claim(_),
+                ..
+            } => "cwt",
+            DecodeError::Extension {
+                key: ExtensionKey::Hcert(_),
+                ..
+            } => "hcert",
+        }
+    }
+
+    /// Wrap an error raised while parsing a COSE structure.
+    pub(crate) fn from_cbor(error: de::Error<io::Error>) -> Self {
+        let offset = cbor_offset(&error);
+        DecodeError::Cbor { error, offset }
+    }
+
+    /// Find out which stage an error raised while reading the COSE
+    /// structure through the streaming pipeline comes from, given the
+    /// length of the base45 text and the limit on the inflated data.
+    pub(crate) fn from_stages(
+        error: de::Error<io::Error>,
+        base45_len: usize,
+        limit: u64,
+    ) -> Self {
+        let error = match error {
+            de::Error::Io(error) if is_tagged(&error) => error,
+            error => return DecodeError::from_cbor(error),
+        };
+
+        match error.into_inner().map(|e| e.downcast::<StageError>()) {
+            Some(Ok(e)) => match e.stage {
+                Stage::Base45 => DecodeError::Base45 {
+                    error: e.error,
+                    input_len: base45_len,
+                    offset: e.offset,
+                },
+                Stage::Zlib => DecodeError::Zlib {
+                    error: e.error,
+                    input_len: base45_decoded_len(base45_len),
+                    offset: e.offset,
+                },
+                Stage::Limit => DecodeError::DecompressedTooLarge { limit },
+            },
+            // Not reached, as the error was checked to be a StageError, but
+            // kept as a CBOR error rather than panicking.
+            Some(Err(e)) => DecodeError::from_cbor(de::Error::Io(
+                io::Error::new(io::ErrorKind::InvalidData, e),
+            )),
+            None => DecodeError::from_cbor(de::Error::Io(
+                io::ErrorKind::InvalidData.into(),
+            )),
+        }
+    }
+
+    /// Wrap an error raised while reading the CWT claims of a payload.
+    pub(crate) fn from_cwt(
+        error: de::Error<io::Error>,
+        input_len: usize,
+    ) -> Self {
+        let offset = cbor_offset(&error);
+        DecodeError::Cwt {
+            error,
+            input_len,
+            offset,
+        }
+    }
+}
+
+fn cbor_offset(error: &de::Error<io::Error>) -> Option<usize> {
+    match error {
+        de::Error::Syntax(offset) => Some(*offset),
+        de::Error::Semantic(offset, _) => *offset,
+        _ => None,
+    }
+}
+
+/// Number of bytes encoded by some base45 text, trailing partial chunk
+/// included.
+fn base45_decoded_len(len: usize) -> usize {
+    len / 3 * 2 + len % 3 / 2
+}
+
+/// Streaming stage of the decoding pipeline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Stage {
+    Base45,
+    Zlib,
+    Limit,
+}
+
+/// Readers able to tell how much of their input they have consumed.
+pub(crate) trait Position {
+    fn position(&self) -> u64;
+}
+
+impl<R: Read> Position for ZlibDecoder<R> {
+    fn position(&self) -> u64 {
+        self.total_in()
+    }
+}
+
+/// Reader failing once more than a given number of bytes are read from
+/// the inner reader.
+pub(crate) struct LimitReader<R> {
+    inner: R,
+    limit: u64,
+    read: u64,
+}
+
+impl<R: Read> LimitReader<R> {
+    pub(crate) fn new(inner: R, limit: u64) -> Self {
+        LimitReader {
+            inner,
+            limit,
+            read: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for LimitReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        if self.read > self.limit {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "size limit exceeded",
+            ));
+        }
+
+        Ok(n)
+    }
+}
+
+impl<R> Position for LimitReader<R> {
+    fn position(&self) -> u64 {
+        self.read
+    }
+}
+
+/// An I/O error tagged with the stage that raised it, so it can be told
+/// apart once it surfaces from the CBOR reader.
+#[derive(Debug)]
+struct StageError {
+    stage: Stage,
+    offset: u64,
+    error: io::Error,
+}
+
+impl fmt::Display for StageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl Error for StageError {}
+
+fn is_tagged(error: &io::Error) -> bool {
+    matches!(error.get_ref(), Some(e) if e.is::<StageError>())
+}
+
+/// Reader tagging the errors of the inner reader with a stage and the
+/// position reached, unless they were already tagged by an earlier stage.
+pub(crate) struct StageReader<R> {
+    inner: R,
+    stage: Stage,
+}
+
+impl<R: Read + Position> StageReader<R> {
+    pub(crate) fn new(inner: R, stage: Stage) -> Self {
+        StageReader { inner, stage }
+    }
+}
+
+impl<R: Read + Position> Read for StageReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.inner.read(buf) {
+            Err(error) if !is_tagged(&error) => {
+                let tagged = StageError {
+                    stage: self.stage,
+                    offset: self.inner.position(),
+                    error,
+                };
+                Err(io::Error::new(tagged.error.kind(), tagged))
+            }
+            result => result,
+        }
+    }
+}
@@ -0,0 +1,1747 @@
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Read};
+
+use ciborium::{de::from_reader, value::Value};
+use flate2::read::{ZlibDecoder, ZlibEncoder};
+use flate2::Compression;
+use serde::de::{self, Deserializer, MapAccess, Visitor};
+use serde::{Deserialize, Serialize};
+
+use crate::base45::{self, Base45Reader};
+pub use crate::error::DecodeError;
+use crate::error::{LimitReader, Position, Result, Stage, StageReader};
+pub use crate::schema::{certificate_violations, Violation};
+use crate::sha256::sha256;
+
+pub const CLAIM_KEY_DCCV1: usize = 1; // EU Digital Covid Certificate v1
+const CLAIM_KEY_EXPIRETION_TIME: i16 = 4;
+pub const CLAIM_KEY_HCERT: i16 = -260;
+const CLAIM_KEY_ISSUED_AT: i16 = 6;
+pub const CLAIM_KEY_ISSUER: i16 = 1;
+pub const COSE_HEADER_ALG: i16 = 1;
+pub const COSE_HEADER_COUNTER_SIGNATURE: i16 = 7;
+pub const COSE_HEADER_COUNTER_SIGNATURE0: i16 = 9;
+pub const COSE_HEADER_KID: i16 = 4;
+pub const COSE_HEADER_X5CHAIN: i16 = 33;
+pub const COSE_SIGN1_TAG: u64 = 18;
+const HC1_FIELD: &str = "HC1:";
+const CERTIFICATE_FIELDS: &[&str] = &["ver", "nam", "dob", "v", "r", "t"];
+const NAME_FIELDS: &[&str] = &["fn", "fnt", "gn", "gnt"];
+const VACCINATION_FIELDS: &[&str] =
+    &["tg", "vp", "mp", "ma", "dn", "sd", "dt", "co", "is", "ci"];
+const RECOVERY_FIELDS: &[&str] = &["tg", "fr", "co", "is", "df", "du", "ci"];
+const TEST_FIELDS: &[&str] = &[
+    "tg", "tt", "nm", "ma", "sc", "dr", "tr", "tc", "co", "is", "ci",
+];
+
+/// A vaccination entry.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct VaccineRecord {
+    /// Disease or agent targeted.
+    pub tg: String,
+    /// Vaccine or prophylaxis.
+    pub vp: String,
+    /// Vaccine medicinal product.
+    pub mp: String,
+    /// Marketing authorisation holder or manufacturer.
+    pub ma: String,
+    /// Number in a series of doses.
+    pub dn: i32,
+    /// Overall number of doses in the series.
+    pub sd: i32,
+    /// Date of vaccination.
+    pub dt: String,
+    /// Country of vaccination.
+    pub co: String,
+    /// Certificate issuer.
+    pub is: String,
+    /// Unique certificate identifier.
+    pub ci: String,
+}
+
+/// A recovery entry.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RecoveryRecord {
+    /// Disease or agent the holder has recovered from.
+    pub tg: String,
+    /// Date of the first positive test result.
+    pub fr: String,
+    /// Country of the test.
+    pub co: String,
+    /// Certificate issuer.
+    pub is: String,
+    /// Certificate valid from.
+    pub df: String,
+    /// Certificate valid until.
+    pub du: String,
+    /// Unique certificate identifier.
+    pub ci: String,
+}
+
+/// A test entry.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TestRecord {
+    /// Disease or agent targeted.
+    pub tg: String,
+    /// Type of test.
+    pub tt: String,
+    /// Test name, for NAAT tests.
+    #[serde(default)]
+    pub nm: String,
+    /// Test device identifier, for RAT tests.
+    #[serde(default)]
+    pub ma: String,
+    /// Date and time of the sample collection.
+    pub sc: String,
+    /// Date and time of the test result.
+    #[serde(default)]
+    pub dr: String,
+    /// Test result.
+    pub tr: String,
+    /// Testing centre or facility, optional since version 1.3.0.
+    #[serde(default)]
+    pub tc: String,
+    /// Country of the test.
+    pub co: String,
+    /// Certificate issuer.
+    pub is: String,
+    /// Unique certificate identifier.
+    pub ci: String,
+}
+
+/// Name of the certificate holder.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Name {
+    /// Surname.
+    #[serde(default, rename = "fn")]
+    pub fn_: String,
+    /// Standardised surname, the only name the schema requires.
+    pub fnt: String,
+    /// Forename.
+    #[serde(default)]
+    pub gn: String,
+    /// Standardised forename.
+    #[serde(default)]
+    pub gnt: String,
+}
+
+/// An EU Digital COVID Certificate.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Certificate {
+    /// Schema version.
+    pub ver: String,
+    /// Name of the holder.
+    pub nam: Name,
+    /// Date of birth.
+    pub dob: String,
+    /// Vaccination group.
+    #[serde(default)]
+    pub v: Vec<VaccineRecord>,
+    /// Recovery group.
+    #[serde(default)]
+    pub r: Vec<RecoveryRecord>,
+    /// Test group.
+    #[serde(default)]
+    pub t: Vec<TestRecord>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Payload {
+    pub expires_at: u64,
+    pub issued_at: u64,
+    pub issuer: String,
+    // The hcert claim is only mapped to certificates when they are asked
+    // for, so that looking at the other claims costs no more than parsing,
+    // and then only once.
+    #[serde(skip)]
+    hcert: Value,
+    /// The other claims, for the extensions.
+    #[serde(skip)]
+    claims: Vec<(i16, Value)>,
+    #[serde(skip)]
+    certs: OnceCell<BTreeMap<usize, Certificate>>,
+}
+
+impl PartialEq for Payload {
+    // Whether the certificates were deserialized yet doesn't matter.
+    fn eq(&self, other: &Self) -> bool {
+        self.expires_at == other.expires_at
+            && self.issued_at == other.issued_at
+            && self.issuer == other.issuer
+            && self.hcert == other.hcert
+            && self.claims == other.claims
+    }
+}
+
+impl Payload {
+    /// The certificates in the hcert claim, deserialized on the first call.
+    pub fn certs(&self) -> Result<&BTreeMap<usize, Certificate>> {
+        if let Some(certs) = self.certs.get() {
+            return Ok(certs);
+        }
+        let certs = self.hcert.deserialized().map_err(DecodeError::Hcert)?;
+
+        Ok(self.certs.get_or_init(|| certs))
+    }
+
+    /// The EU Digital COVID Certificate v1 in the hcert claim.
+    pub fn certificate(&self) -> Result<Certificate> {
+        self.certs()?
+            .get(&CLAIM_KEY_DCCV1)
+            .cloned()
+            .ok_or(DecodeError::MissingCertificate)
+    }
+
+    /// The extension `E`, decoded from its claim or its entry of the hcert
+    /// claim, or `None` if the payload has none.
+    pub fn extension<E: Extension>(&self) -> Option<Result<E>> {
+        let value = match E::KEY {
+            ExtensionKey::Claim(key) => {
+                self.claims.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+            }
+            ExtensionKey::Hcert(key) => self
+                .hcert
+                .as_map()
+                .into_iter()
+                .flatten()
+                .find(|(k, _)| k.as_integer() == Some(key.into()))
+                .map(|(_, v)| v),
+        }?;
+
+        Some(
+            E::decode(value)
+                .map_err(|error| DecodeError::Extension { key: E::KEY, error }),
+        )
+    }
+}
+
+/// Where an [`Extension`] is in the CWT claims.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExtensionKey {
+    /// A CWT claim of its own, other than those decoded already: the
+    /// issuer, the times and the hcert.
+    Claim(i16),
+    /// An entry of the hcert claim other than the EU Digital COVID
+    /// Certificate v1, like a national certificate.
+    Hcert(i64),
+}
+
+impl fmt::Display for ExtensionKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExtensionKey::Claim(key) => write!(f, "claim {}", key),
+            ExtensionKey::Hcert(key) => write!(f, "hcert entry {}", key),
+        }
+    }
+}
+
+/// Data that regional extensions add to certificates, decoded by other
+/// crates from the raw CBOR value at their key, so that they can be read
+/// without forking this one: implementing the trait for a type registers
+/// its decoder, and [`Payload::extension`] runs it.
+///
+/// ```
+/// use ciborium::value::Value;
+/// use eudcc_core::{Extension, ExtensionKey};
+///
+/// struct Booster(String);
+///
+/// impl Extension for Booster {
+///     const KEY: ExtensionKey = ExtensionKey::Hcert(2);
+///
+///     fn decode(
+///         value: &Value,
+///     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+///         let text = value.as_text().ok_or("not a text")?;
+///         Ok(Booster(text.to_string()))
+///     }
+/// }
+/// ```
+pub trait Extension: Sized {
+    /// Where the extension is.
+    const KEY: ExtensionKey;
+
+    /// Decode the extension from the value at its key.
+    fn decode(
+        value: &Value,
+    ) -> std::result::Result<Self, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+fn field_name(key: &Value) -> String {
+    match (key.as_text(), key.as_integer()) {
+        (Some(text), _) => text.to_string(),
+        (_, Some(i)) => i128::from(i).to_string(),
+        _ => format!("{:?}", key),
+    }
+}
+
+fn unknown_fields(
+    value: &Value,
+    known: &[&str],
+    path: &str,
+    found: &mut Vec<String>,
+) {
+    for (key, _) in value.as_map().into_iter().flatten() {
+        let name = field_name(key);
+        if !known.contains(&name.as_str()) {
+            found.push(format!("{}{}", path, name));
+        }
+    }
+}
+
+/// Fields of a certificate, and of its name and records, that are not part
+/// of the schema, like `nam.xyz` or `v.xyz`.
+pub fn certificate_unknown_fields(certificate: &Value) -> Vec<String> {
+    let mut found = Vec::new();
+    unknown_fields(certificate, CERTIFICATE_FIELDS, "", &mut found);
+
+    for (key, value) in certificate.as_map().into_iter().flatten() {
+        let (known, path): (&[&str], &str) = match key.as_text() {
+            Some("nam") => (NAME_FIELDS, "nam."),
+            Some("v") => (VACCINATION_FIELDS, "v."),
+            Some("r") => (RECOVERY_FIELDS, "r."),
+            Some("t") => (TEST_FIELDS, "t."),
+            _ => continue,
+        };
+        match value.as_array() {
+            Some(records) => {
+                for record in records {
+                    unknown_fields(record, known, path, &mut found);
+                }
+            }
+            None => unknown_fields(value, known, path, &mut found),
+        }
+    }
+
+    found
+}
+
+impl Payload {
+    /// Fields of the EU Digital COVID Certificate v1 that are not part of
+    /// the schema.
+    pub fn unknown_fields(&self) -> Vec<String> {
+        match self.dccv1() {
+            Some(certificate) => certificate_unknown_fields(certificate),
+            None => vec![],
+        }
+    }
+
+    /// Violations of the schema of the EU Digital COVID Certificate v1.
+    pub fn violations(&self) -> Vec<Violation> {
+        match self.dccv1() {
+            Some(certificate) => certificate_violations(certificate),
+            None => vec![],
+        }
+    }
+
+    fn dccv1(&self) -> Option<&Value> {
+        self.hcert
+            .as_map()
+            .into_iter()
+            .flatten()
+            .find(|(k, _)| k.as_integer() == Some(CLAIM_KEY_DCCV1.into()))
+            .map(|(_, v)| v)
+    }
+}
+
+/// The CWT claims of a payload, the hcert only when it is asked for.
+struct Claims {
+    issuer: String,
+    issued_at: u64,
+    expires_at: u64,
+    hcert: Option<Value>,
+    claims: Vec<(i16, Value)>,
+}
+
+struct ClaimsVisitor {
+    // Otherwise the hcert, and the claims of the extensions, are skipped,
+    // without building their values.
+    hcert: bool,
+}
+
+impl<'de> Visitor<'de> for ClaimsVisitor {
+    type Value = Claims;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("struct Payload")
+    }
+
+    fn visit_map<V>(self, mut map: V) -> Result<Claims, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        let mut issued_at = None;
+        let mut issuer = None;
+        let mut expires_at = None;
+        let mut hcert = None;
+        let mut claims = Vec::new();
+
+        while let Some(key) = map.next_key()? {
+            match key {
+                CLAIM_KEY_ISSUER => {
+                    if issuer.is_some() {
+                        return Err(de::Error::duplicate_field("issuer"));
+                    }
+                    issuer = Some(map.next_value()?);
+                }
+                CLAIM_KEY_ISSUED_AT => {
+                    if issued_at.is_some() {
+                        return Err(de::Error::duplicate_field("issued_at"));
+                    }
+                    issued_at = Some(map.next_value()?);
+                }
+                CLAIM_KEY_EXPIRETION_TIME => {
+                    if expires_at.is_some() {
+                        return Err(de::Error::duplicate_field("expires_at"));
+                    }
+                    expires_at = Some(map.next_value()?);
+                }
+                CLAIM_KEY_HCERT if self.hcert => {
+                    if hcert.is_some() {
+                        return Err(de::Error::duplicate_field("hcert"));
+                    }
+                    hcert = Some(map.next_value()?);
+                }
+                _ if self.hcert => claims.push((key, map.next_value()?)),
+                _ => {
+                    // Ignore the rest.
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        let issuer =
+            issuer.ok_or_else(|| de::Error::missing_field("issuer"))?;
+        let issued_at =
+            issued_at.ok_or_else(|| de::Error::missing_field("issued_at"))?;
+        let expires_at =
+            expires_at.ok_or_else(|| de::Error::missing_field("expire_at"))?;
+        if self.hcert && hcert.is_none() {
+            return Err(de::Error::missing_field("hcert"));
+        }
+        Ok(Claims {
+            issuer,
+            issued_at,
+            expires_at,
+            hcert,
+            claims,
+        })
+    }
+}
+
+const CLAIM_FIELDS: &[&str] = &["issuer", "issued_at"];
+
+impl<'de> Deserialize<'de> for Payload {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let visitor = ClaimsVisitor { hcert: true };
+        let claims = deserializer.deserialize_struct(
+            "Payload",
+            CLAIM_FIELDS,
+            visitor,
+        )?;
+        let hcert = claims
+            .hcert
+            .ok_or_else(|| de::Error::missing_field("hcert"))?;
+
+        Ok(Payload {
+            issuer: claims.issuer,
+            issued_at: claims.issued_at,
+            expires_at: claims.expires_at,
+            hcert,
+            claims: claims.claims,
+            certs: OnceCell::new(),
+        })
+    }
+}
+
+/// The claims of [`Metadata`], read without the hcert.
+struct MetadataClaims(Claims);
+
+impl<'de> Deserialize<'de> for MetadataClaims {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let visitor = ClaimsVisitor { hcert: false };
+        deserializer
+            .deserialize_struct("Payload", CLAIM_FIELDS, visitor)
+            .map(MetadataClaims)
+    }
+}
+
+/// Describe the issuer claims that are not ISO 3166-1 country codes: `EU`,
+/// used for certificates going through the EU gateway, and `1`, used by
+/// some pilots.
+pub fn special_issuer(issuer: &str) -> Option<&'static str> {
+    match issuer {
+        "EU" => Some("European Union, not a member state"),
+        "1" => Some("pilot issuer, not a country"),
+        _ => None,
+    }
+}
+
+/// CWT claims describing a certificate, without its personal data.
+#[derive(Debug, PartialEq)]
+pub struct Metadata {
+    pub expires_at: u64,
+    pub issued_at: u64,
+    pub issuer: String,
+    pub kid: Option<Vec<u8>>,
+}
+
+/// The four parts of a COSE Single Signer Data Object.
+#[derive(Debug, PartialEq)]
+pub struct CoseSign1 {
+    pub protected: Vec<u8>,
+    pub unprotected: Value,
+    pub payload: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+/// Name of a COSE signature algorithm identifier.
+pub fn algorithm_name(alg: i128) -> Option<&'static str> {
+    match alg {
+        -7 => Some("ES256"),
+        -35 => Some("ES384"),
+        -36 => Some("ES512"),
+        -37 => Some("PS256"),
+        -38 => Some("PS384"),
+        -39 => Some("PS512"),
+        -257 => Some("RS256"),
+        _ => None,
+    }
+}
+
+/// A COSE counter signature, signing the same content as the main
+/// signature.
+#[derive(Debug, PartialEq)]
+pub struct CounterSignature {
+    pub protected: Vec<u8>,
+    pub unprotected: Value,
+    pub signature: Vec<u8>,
+}
+
+impl CounterSignature {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value.as_array()?.as_slice() {
+            [protected, unprotected, signature] => Some(CounterSignature {
+                protected: protected.as_bytes()?.clone(),
+                unprotected: unprotected.clone(),
+                signature: signature.as_bytes()?.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl CoseSign1 {
+    /// Look up a header parameter, preferring the protected header.
+    pub fn header(&self, label: i16) -> Option<Value> {
+        let protected = from_reader::<Value, _>(&self.protected[..]).ok();
+        [protected.as_ref(), Some(&self.unprotected)]
+            .iter()
+            .flatten()
+            .filter_map(|header| header.as_map())
+            .flat_map(|header| header.iter())
+            .find(|(k, _)| k.as_integer() == Some(label.into()))
+            .map(|(_, v)| v.clone())
+    }
+
+    /// DER encoded X.509 certificates embedded in the x5chain header, the
+    /// signing one first.
+    pub fn x5chain(&self) -> Vec<Vec<u8>> {
+        match self.header(COSE_HEADER_X5CHAIN) {
+            Some(Value::Bytes(certificate)) => vec![certificate],
+            Some(Value::Array(chain)) => chain
+                .iter()
+                .filter_map(|certificate| certificate.as_bytes().cloned())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Counter signatures found in the counter signature header, which
+    /// holds either a single COSE_Signature or an array of them.
+    pub fn counter_signatures(&self) -> Vec<CounterSignature> {
+        let value = match self.header(COSE_HEADER_COUNTER_SIGNATURE) {
+            Some(value) => value,
+            None => return vec![],
+        };
+        match value.as_array().and_then(|v| v.first()) {
+            Some(Value::Array(_)) => value
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(CounterSignature::from_value)
+                .collect(),
+            _ => CounterSignature::from_value(&value).into_iter().collect(),
+        }
+    }
+
+    /// The signature in the abbreviated counter signature header, if any.
+    pub fn counter_signature0(&self) -> Option<Vec<u8>> {
+        match self.header(COSE_HEADER_COUNTER_SIGNATURE0) {
+            Some(Value::Bytes(signature)) => Some(signature),
+            _ => None,
+        }
+    }
+
+    /// The signature hash revocation lists identify the certificate with,
+    /// which doesn't depend on the UVCI, malformed ones included: the first
+    /// 128 bits of the SHA-256 digest of the signature, of only its `r`
+    /// half for ECDSA.
+    pub fn signature_hash(&self) -> [u8; 16] {
+        let ecdsa = matches!(
+            self.header(COSE_HEADER_ALG).and_then(|alg| alg.as_integer()),
+            Some(alg) if matches!(i128::from(alg), -7 | -35 | -36)
+        );
+        let signed = if ecdsa {
+            let (r, _) = self.signature.split_at(self.signature.len() / 2);
+            r
+        } else {
+            &self.signature[..]
+        };
+
+        let mut hash = [0; 16];
+        for (out, byte) in hash.iter_mut().zip(sha256(signed)) {
+            *out = byte;
+        }
+
+        hash
+    }
+}
+
+/// Lowercase hexadecimal representation of some bytes.
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn cose_bytes(value: &Value, name: &str) -> Result<Vec<u8>> {
+    match value.as_bytes() {
+        Some(bytes) => Ok(bytes.clone()),
+        None => Err(DecodeError::Cose(format!(
+            "COSE {} must be a byte string!",
+            name
+        ))),
+    }
+}
+
+/// Map a CBOR value to the COSE Single Signer Data Object it holds.
+fn cose_from_value(value: Value) -> Result<CoseSign1> {
+    if let Value::Tag(COSE_SIGN1_TAG, content) = value {
+        if let Value::Array(arr) = *content {
+            // We have 4 part of a CBOR Web Token:
+            // 1. protected header;
+            // 2. unprotected header;
+            // 3. payload;
+            // 4. signature.
+            if let [protected, unprotected, payload, signature] = &arr[..] {
+                return Ok(CoseSign1 {
+                    protected: cose_bytes(protected, "protected header")?,
+                    unprotected: unprotected.clone(),
+                    payload: cose_bytes(payload, "payload")?,
+                    signature: cose_bytes(signature, "signature")?,
+                });
+            }
+        }
+    } else {
+        return Err(DecodeError::Cose(
+            "Not a COSE Single Signer Data Object Tag!".to_string(),
+        ));
+    }
+
+    Err(DecodeError::Cose(
+        "Can't decode the EU Digital COVID Certificate payload!".to_string(),
+    ))
+}
+
+/// Decode base45 text, without the `HC1:` prefix.
+pub fn decode_base45(data: &[u8]) -> Result<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(data.len() / 3 * 2 + 1);
+    let mut reader = Base45Reader::new(data);
+    match reader.read_to_end(&mut decoded) {
+        Ok(_) => Ok(decoded),
+        Err(error) => Err(DecodeError::Base45 {
+            error,
+            input_len: data.len(),
+            offset: reader.position(),
+        }),
+    }
+}
+
+/// Inflate a zlib stream, such as the one encoded by the base45 text.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    let mut reader = ZlibDecoder::new(data);
+    match reader.read_to_end(&mut decompressed) {
+        Ok(_) => Ok(decompressed),
+        Err(error) => Err(DecodeError::Zlib {
+            error,
+            input_len: data.len(),
+            offset: reader.total_in(),
+        }),
+    }
+}
+
+/// Inflate a zlib stream, as [`decompress`] does, failing once past the
+/// decompressed length limit of `options`.
+pub fn decompress_with(
+    data: &[u8],
+    options: &DecodeOptions,
+) -> Result<Vec<u8>> {
+    let limit = options.max_decompressed_len.unwrap_or(u64::MAX);
+    let mut decompressed = Vec::new();
+    let mut reader = ZlibDecoder::new(data);
+    match (&mut reader)
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut decompressed)
+    {
+        Ok(_) if decompressed.len() as u64 > limit => {
+            Err(DecodeError::DecompressedTooLarge { limit })
+        }
+        Ok(_) => Ok(decompressed),
+        Err(error) => Err(DecodeError::Zlib {
+            error,
+            input_len: data.len(),
+            offset: reader.total_in(),
+        }),
+    }
+}
+
+/// Parse the CBOR encoded COSE Single Signer Data Object of a certificate.
+pub fn parse_cose(data: &[u8]) -> Result<CoseSign1> {
+    let value = from_reader(data).map_err(DecodeError::from_cbor)?;
+    cose_from_value(value)
+}
+
+/// Parse the CWT claims of a COSE payload, hcert included.
+pub fn parse_hcert(payload: &[u8]) -> Result<Payload> {
+    from_reader(payload).map_err(|e| DecodeError::from_cwt(e, payload.len()))
+}
+
+/// Encode the CBOR encoded COSE structure of a certificate as an `HC1:`
+/// string, the way QR codes carry it: compressed with zlib, then base45
+/// encoded. The structure is checked to be a COSE Single Signer Data
+/// Object first.
+pub fn encode_cose_bytes(data: &[u8]) -> Result<String> {
+    parse_cose(data)?;
+
+    let mut compressed = Vec::new();
+    let mut reader = ZlibEncoder::new(data, Compression::best());
+    // Compressing into memory only fails when running out of it.
+    match reader.read_to_end(&mut compressed) {
+        Ok(_) => Ok(format!("{}{}", HC1_FIELD, base45::encode(&compressed))),
+        Err(error) => Err(DecodeError::Zlib {
+            error,
+            input_len: data.len(),
+            offset: reader.total_in(),
+        }),
+    }
+}
+
+/// Decode an `HC1:` prefixed certificate, given as raw bytes, into its
+/// COSE structure.
+///
+/// This is the core of the decoder: it performs no I/O and keeps no
+/// state, so it can be used as it is from any environment. It runs the
+/// same stages as [`decode_base45`], [`decompress`] and [`parse_cose`],
+/// streaming the data from one to the next.
+pub fn decode_cose_bytes(data: &[u8]) -> Result<CoseSign1> {
+    decode_cose_with(data, &DecodeOptions::default())
+}
+
+/// Decode an `HC1:` prefixed certificate into its COSE structure, as
+/// [`decode_cose_bytes`] does, as controlled by the input options of
+/// `options`, like the prefix and the length limits.
+pub fn decode_cose_with(
+    data: &[u8],
+    options: &DecodeOptions,
+) -> Result<CoseSign1> {
+    let base45_data = base45_text_with(data, options)?;
+
+    // Every stage reads from the previous one through fixed-size buffers,
+    // so only the decoded COSE structure is ever kept in memory.
+    // Errors are tagged with their stage on the way, as they all reach the
+    // CBOR reader as I/O errors.
+    let base45_decoder =
+        StageReader::new(Base45Reader::new(base45_data), Stage::Base45);
+    let zlib_decoder =
+        StageReader::new(ZlibDecoder::new(base45_decoder), Stage::Zlib);
+    let limit = options.max_decompressed_len.unwrap_or(u64::MAX);
+    let mut limit_reader =
+        StageReader::new(LimitReader::new(zlib_decoder, limit), Stage::Limit);
+    let from_stages = |e| DecodeError::from_stages(e, base45_data.len(), limit);
+    let value = from_reader(&mut limit_reader).map_err(from_stages)?;
+    // Read the stream to its end, as the zlib checksum is only verified
+    // there: a truncated stream would go unnoticed otherwise.
+    io::copy(&mut limit_reader, &mut io::sink())
+        .map_err(|e| from_stages(ciborium::de::Error::Io(e)))?;
+
+    cose_from_value(value)
+}
+
+/// The base45 text of an `HC1:` prefixed certificate, given as raw bytes,
+/// without its prefix and trailing whitespace, checked as controlled by
+/// the input options of `options`, like the prefix and the length limit.
+pub fn base45_text_with<'a>(
+    data: &'a [u8],
+    options: &DecodeOptions,
+) -> Result<&'a [u8]> {
+    if let Some(limit) = options.max_input_len {
+        if data.len() > limit {
+            return Err(DecodeError::InputTooLarge {
+                input_len: data.len(),
+                limit,
+            });
+        }
+    }
+
+    let end = data
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(0, |i| i + 1);
+    let data = data.get(..end).unwrap_or_default();
+
+    match data.strip_prefix(HC1_FIELD.as_bytes()) {
+        Some(data) => Ok(data),
+        None if !options.require_prefix => Ok(data),
+        None => Err(DecodeError::MissingPrefix),
+    }
+}
+
+pub fn decode_cose(data: String) -> Result<CoseSign1> {
+    decode_cose_bytes(data.as_bytes())
+}
+
+/// Decode only the CWT metadata and the key identifier, skipping the
+/// hcert, personal data included, without even parsing it.
+pub fn decode_metadata(data: String) -> Result<Metadata> {
+    decode_metadata_with(data.as_bytes(), &DecodeOptions::default())
+}
+
+/// Decode only the CWT metadata and the key identifier, as
+/// [`decode_metadata`] does, as controlled by `options`.
+pub fn decode_metadata_with(
+    data: &[u8],
+    options: &DecodeOptions,
+) -> Result<Metadata> {
+    parse_metadata_with(&decode_cose_with(data, options)?, options)
+}
+
+/// Read the CWT metadata and the key identifier of a COSE structure, then
+/// check them as controlled by `options`.
+///
+/// Only the options about the claims apply, and those about the hcert,
+/// like strict validation, don't: it is skipped.
+pub fn parse_metadata_with(
+    cose: &CoseSign1,
+    options: &DecodeOptions,
+) -> Result<Metadata> {
+    let payload = &cose.payload[..];
+    let MetadataClaims(claims) = from_reader(payload)
+        .map_err(|e| DecodeError::from_cwt(e, payload.len()))?;
+    check_claims(claims.expires_at, &claims.issuer, options)?;
+    let kid = match cose.header(COSE_HEADER_KID) {
+        Some(Value::Bytes(kid)) => Some(kid),
+        _ => None,
+    };
+
+    Ok(Metadata {
+        expires_at: claims.expires_at,
+        issued_at: claims.issued_at,
+        issuer: claims.issuer,
+        kid,
+    })
+}
+
+/// Decode the whole CWT payload: issuer, issued-at and expiry claims,
+/// together with the hcert.
+pub fn decode_payload(data: String) -> Result<Payload> {
+    parse_hcert(&decode_cose(data)?.payload)
+}
+
+pub fn decode(data: String) -> Result<Certificate> {
+    decode_payload(data)?.certificate()
+}
+
+/// How fields unknown to the certificate schema are handled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnknownFields {
+    Ignore,
+    Reject,
+}
+
+/// Options controlling how strict decoding is, for [`decode_with`].
+///
+/// The default options behave like [`decode`], and every option can be
+/// changed in a builder style:
+///
+/// ```
+/// use eudcc_core::{DecodeOptions, UnknownFields};
+///
+/// let options = DecodeOptions::new()
+///     .require_prefix(false)
+///     .unknown_fields(UnknownFields::Reject)
+///     .max_input_len(4096);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodeOptions {
+    require_prefix: bool,
+    unknown_fields: UnknownFields,
+    max_input_len: Option<usize>,
+    pub(crate) max_decompressed_len: Option<u64>,
+    now: Option<u64>,
+    accept_issuers: Option<Vec<String>>,
+    reject_issuers: Vec<String>,
+    strict: bool,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        DecodeOptions {
+            require_prefix: true,
+            unknown_fields: UnknownFields::Ignore,
+            max_input_len: None,
+            max_decompressed_len: None,
+            now: None,
+            accept_issuers: None,
+            reject_issuers: Vec::new(),
+            strict: false,
+        }
+    }
+}
+
+impl DecodeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the `HC1:` prefix is required, or base45 text without it is
+    /// accepted too.
+    pub fn require_prefix(mut self, require: bool) -> Self {
+        self.require_prefix = require;
+        self
+    }
+
+    /// How fields unknown to the certificate schema are handled.
+    pub fn unknown_fields(mut self, policy: UnknownFields) -> Self {
+        self.unknown_fields = policy;
+        self
+    }
+
+    /// Maximum length of the data, in bytes.
+    pub fn max_input_len(mut self, limit: usize) -> Self {
+        self.max_input_len = Some(limit);
+        self
+    }
+
+    /// Maximum length of the inflated data, in bytes.
+    pub fn max_decompressed_len(mut self, limit: u64) -> Self {
+        self.max_decompressed_len = Some(limit);
+        self
+    }
+
+    /// Reject certificates expired before `now`, in seconds since the Unix
+    /// epoch. Expiry is not checked otherwise.
+    pub fn now(mut self, now: u64) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    /// Only accept certificates issued by one of these countries, as found
+    /// in the issuer claim. Every issuer is accepted otherwise.
+    pub fn accept_issuers(mut self, issuers: &[&str]) -> Self {
+        let issuers = issuers.iter().map(|i| i.to_string()).collect();
+        self.accept_issuers = Some(issuers);
+        self
+    }
+
+    /// Reject certificates issued by one of these countries, even when
+    /// they are accepted by [`accept_issuers`](Self::accept_issuers).
+    pub fn reject_issuers(mut self, issuers: &[&str]) -> Self {
+        self.reject_issuers = issuers.iter().map(|i| i.to_string()).collect();
+        self
+    }
+
+    /// Check the certificate against its schema, failing with every
+    /// violation found rather than with the first one.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    fn is_accepted(&self, issuer: &str) -> bool {
+        issuer_accepted(
+            issuer,
+            self.accept_issuers.as_deref(),
+            &self.reject_issuers,
+        )
+    }
+}
+
+/// Whether an issuer is accepted: among the `accepted` ones, if only some
+/// are, and not among the `rejected` ones, country codes being compared
+/// ignoring case.
+pub fn issuer_accepted(
+    issuer: &str,
+    accepted: Option<&[String]>,
+    rejected: &[String],
+) -> bool {
+    let listed = |issuers: &[String]| {
+        issuers.iter().any(|i| i.eq_ignore_ascii_case(issuer))
+    };
+    let accepted = match accepted {
+        Some(issuers) => listed(issuers),
+        None => true,
+    };
+
+    accepted && !listed(rejected)
+}
+
+/// Decode the whole CWT payload, given as raw bytes, as controlled by
+/// `options`.
+pub fn decode_payload_with(
+    data: &[u8],
+    options: &DecodeOptions,
+) -> Result<Payload> {
+    parse_hcert_with(&decode_cose_with(data, options)?.payload, options)
+}
+
+/// Parse the CWT claims of a COSE payload, as [`parse_hcert`] does, then
+/// check them as controlled by `options`, as [`decode_payload_with`] does.
+///
+/// Only the options about the claims apply: those about the input, like
+/// the prefix and its length, are left to whoever decoded the payload.
+pub fn parse_hcert_with(
+    payload: &[u8],
+    options: &DecodeOptions,
+) -> Result<Payload> {
+    let payload = parse_hcert(payload)?;
+    check_payload(&payload, options)?;
+
+    Ok(payload)
+}
+
+/// Check the claims of a parsed payload as controlled by `options`, as
+/// [`parse_hcert_with`] does.
+pub(crate) fn check_payload(
+    payload: &Payload,
+    options: &DecodeOptions,
+) -> Result<()> {
+    if options.strict {
+        let violations = payload.violations();
+        if !violations.is_empty() {
+            return Err(DecodeError::Invalid(violations));
+        }
+    }
+    if options.unknown_fields == UnknownFields::Reject {
+        if let Some(field) = payload.unknown_fields().into_iter().next() {
+            return Err(DecodeError::UnknownField(field));
+        }
+    }
+    check_claims(payload.expires_at, &payload.issuer, options)
+}
+
+/// Check the claims every decoding checks, as controlled by `options`:
+/// the expiry and the issuer.
+fn check_claims(
+    expires_at: u64,
+    issuer: &str,
+    options: &DecodeOptions,
+) -> Result<()> {
+    if let Some(now) = options.now {
+        if expires_at < now {
+            return Err(DecodeError::Expired { expires_at, now });
+        }
+    }
+    if !options.is_accepted(issuer) {
+        return Err(DecodeError::IssuerRejected(issuer.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Decode a certificate, given as raw bytes, as controlled by `options`.
+pub fn decode_with(
+    data: &[u8],
+    options: &DecodeOptions,
+) -> Result<Certificate> {
+    decode_payload_with(data, options)?.certificate()
+}
+
+#[test]
+fn decode_vaccination_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/1.json
+    // It is licensed under Apache-2.0 License.
+    let vaccination_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II5XHC9B5G2+$N IOP-IA%NFQGRJPC%OQHIZC4.OI1RM8ZA.A5:S9MKN4NN3F85QNCY0O%0VZ001HOC9JU0D0HT0HB2PL/IB*09B9LW4T*8+DCMH0LDK2%K:XFE70*LP$V25$0Q:J:4MO1P0%0L0HD+9E/HY+4J6TH48S%4K.GJ2PT3QY:GQ3TE2I+-CPHN6D7LLK*2HG%89UV-0LZ 2ZJJ524-LH/CJTK96L6SR9MU9DHGZ%P WUQRENS431T1XCNCF+47AY0-IFO0500TGPN8F5G.41Q2E4T8ALW.INSV$ 07UV5SR+BNQHNML7 /KD3TU 4V*CAT3ZGLQMI/XI%ZJNSBBXK2:UG%UJMI:TU+MMPZ5$/PMX19UE:-PSR3/$NU44CBE6DQ3D7B0FBOFX0DV2DGMB$YPF62I$60/F$Z2I6IFX21XNI-LM%3/DF/U6Z9FEOJVRLVW6K$UG+BKK57:1+D10%4K83F+1VWD1NE";
+    let expected = Certificate {
+        ver: "1.0.0".to_string(),
+        nam: Name {
+            fn_: "Di Caprio".to_string(),
+            fnt: "DI<CAPRIO".to_string(),
+            gn: "Marilù Teresa".to_string(),
+            gnt: "MARILU<TERESA".to_string(),
+        },
+        dob: "1977-06-16".to_string(),
+        v: vec![VaccineRecord {
+            tg: "840539006".to_string(),
+            vp: "1119349007".to_string(),
+            mp: "EU/1/20/1528".to_string(),
+            ma: "ORG-100030215".to_string(),
+            dn: 2,
+            sd: 2,
+            dt: "2021-04-10".to_string(),
+            co: "IT".to_string(),
+            is: "IT".to_string(),
+            ci: "01ITE7300E1AB2A84C719004F103DCB1F70A#6".to_string(),
+        }],
+        r: vec![],
+        t: vec![],
+    };
+
+    let c = decode(vaccination_data.to_string()).unwrap();
+    assert_eq!(c, expected);
+}
+
+#[test]
+fn special_issuer_test() {
+    assert!(special_issuer("EU").is_some());
+    assert!(special_issuer("1").is_some());
+    assert_eq!(special_issuer("IT"), None);
+    assert_eq!(special_issuer("eu"), None);
+}
+
+#[test]
+fn decode_recovery_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/2.json
+    // It is licensed under Apache-2.0 License.
+    let recovery_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *MEB2B2JJ59J-9BC6:X9NECX0AKQC:3DCV4*XUA2P-FHT-H4SI/J9WVHWVH+ZEOV1J$HNTICZUBOM*LP$V25$0Q:J40IA3L/*84-5%:C92JN*4CY0*%9F/8J2P4.818T+:IX3M3.96RPVD9J-OZT1-NT0 2$$0$2PZX69B9VCDHI2/T9TU1BPIJKH/T7B-S-*O/Y41FD+X49+5Z-6%.HDD8R6W1FDJGJSFJ/4Q:T0.KJTNP8EFULNC:HA0K5HKRB4TD85LOLF92GF.3O.Z8CC7-2FQYG$%21 2O*4R60NM8JI0EUGP$I/XK$M8ZQE6YB9M66P8N31I.ROSK%IA1Q2N53Q-OQ2VC6E26T11ROSNK5W-*H+MJ%0RGZVGWNURI75RBSQSHLH1JG*CMH2.-S$7VX6N*Z1881J7G.F9I+SV06F+1M*93%D";
+    let expected = Certificate {
+        ver: "1.0.0".to_string(),
+        nam: Name {
+            fn_: "Di Caprio".to_string(),
+            fnt: "DI<CAPRIO".to_string(),
+            gn: "Marilù Teresa".to_string(),
+            gnt: "MARILU<TERESA".to_string(),
+        },
+        dob: "1977-06-16".to_string(),
+        v: vec![],
+        r: vec![RecoveryRecord {
+            tg: "840539006".to_string(),
+            fr: "2021-05-02".to_string(),
+            co: "IT".to_string(),
+            is: "IT".to_string(),
+            df: "2021-05-04".to_string(),
+            du: "2021-10-31".to_string(),
+            ci: "01ITA65E2BD36C9E4900B0273D2E7C92EEB9#1".to_string(),
+        }],
+        t: vec![],
+    };
+
+    let c = decode(recovery_data.to_string()).unwrap();
+    assert_eq!(c, expected);
+}
+
+#[test]
+fn decode_test_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/3.json
+    // It is licensed under Apache-2.0 License.
+    let test_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0IIE 1C9B5G2+$NP-OP-IA%N%QHRJPC%OQHIZC4.OI:OIG/Q80P2W4VZ0K1H$$0CNN62PK.G +AG5T01HJCAMKNAB5S.8%*8Z95%9EMP8N22MM42WFCD9C2AKIJKIJM1MQIAY.D-7A4KE0PLV1ARKF.GH5$C4-9GGIUEC0QE1JAF.714NTPINRQ3.VR+P0$J2*N$*SB-G9+RT*QFNI2X02%KYZPQV6YP8412HOA-I0+M9GPEGPEMH0SJ4OM9*1B+M96K1HK2YJ2PI0P:65:41ZSW$P*CM-NT0 2$88L/II 05B9.Z8T*8Y1VM:KCY07LPMIH-O9XZQ4H9IZBP%D2U3+KGP2W2UQNG6-E6+WJTK1%J6/UI2YUELE+W35T7+H8NH8DRG+PG.UIZ$U%UF*QHOOENBU621TW5XW5HS9+I010H% 0R%0ZD5CC9T0HP8TCNNI:CQ:G172DX8FZV3U9W-HNPPQ N2KV 2VHDHO:2XAV:FB+18DRR%%VQ F60LF6K 38GK8LGG4U7UP6*S4QBR-F97FRONPKZS+P9$5W1CAV37KD48ERCRH";
+    let expected = Certificate {
+        ver: "1.0.0".to_string(),
+        nam: Name {
+            fn_: "Di Caprio".to_string(),
+            fnt: "DI<CAPRIO".to_string(),
+            gn: "Marilù Teresa".to_string(),
+            gnt: "MARILU<TERESA".to_string(),
+        },
+        dob: "1977-06-16".to_string(),
+        v: vec![],
+        r: vec![],
+        t: vec![TestRecord {
+            tg: "840539006".to_string(),
+            tt: "LP6464-4".to_string(),
+            nm: "Roche LightCycler qPCR".to_string(),
+            ma: "1232".to_string(),
+            sc: "2021-05-03T10:27:15Z".to_string(),
+            dr: "2021-05-11T12:27:15Z".to_string(),
+            tr: "260415000".to_string(),
+            tc: "Policlinico Umberto I".to_string(),
+            co: "IT".to_string(),
+            is: "IT".to_string(),
+            ci: "01IT053059F7676042D9BEE9F874C4901F9B#3".to_string(),
+        }],
+    };
+
+    let c = decode(test_data.to_string()).unwrap();
+    assert_eq!(c, expected);
+
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/4.json
+    // It is licensed under Apache-2.0 License.
+    let test_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II*%6C9B5G2+$NEJPP-IA%NGRIRJPC%OQHIZC4.OI:OIG/Q80P2W4VZ0K1H$$05QN*Y0K.G +AG5T01HJCAMKN$71Z95Z11VTO.L8YBJ-B93:GQBGZHHBIH5C99.B4DBF:F0.8ELG:.CC-8LQECKEBLDSH8XAG.6A-JE:GQA KX-SZDG0$JO+SW*PR+PHXF8IQV$K%OKOUFBBQR-S3D1PI0/7Q.H0807-L9CL62/2JJ11K2919GI1X1DDM8RMA0/41:6Z.2:NC-%CN$KJLCLF9+FJE 4Y3LL/II 05B9.Z8M+8:Y001HCY0R%0IGF5JNCPIGSUNG6YS75XJ/J0/V7.UI$RU8ZB.W2FI28LHUZUYZQNI9Y FQQGQ$FP DDVBDVBBX33UQLTU8L20H6/*12SADB9:G9J+9Y 5LJA8JF8JFHJP7NVDEBK3JQ7TI 05QNT+CCZ1ZA2I+T*R9XZ6/:COTJCURIF8CZPCJ4EF5LU5I-Q:.N$P9DX5NAM*PJYD3L2V0GBG.JL4LESU72S1CM%5OC%VSTJ8NC1TGO:QS02V505GJUTH";
+    let expected = Certificate {
+        ver: "1.0.0".to_string(),
+        nam: Name {
+            fn_: "Di Caprio".to_string(),
+            fnt: "DI<CAPRIO".to_string(),
+            gn: "Marilù Teresa".to_string(),
+            gnt: "MARILU<TERESA".to_string(),
+        },
+        dob: "1977-06-16".to_string(),
+        v: vec![],
+        r: vec![],
+        t: vec![TestRecord {
+            tg: "840539006".to_string(),
+            tt: "LP6464-4".to_string(),
+            nm: "Roche LightCycler qPCR".to_string(),
+            ma: "".to_string(),
+            sc: "2021-05-10T10:27:15Z".to_string(),
+            dr: "2021-05-11T12:27:15Z".to_string(),
+            tr: "260415000".to_string(),
+            tc: "Policlinico Umberto I".to_string(),
+            co: "IT".to_string(),
+            is: "IT".to_string(),
+            ci: "01IT0BFC9866D3854EAC82C21654B6F6DE32#1".to_string(),
+        }],
+    };
+
+    let c = decode(test_data.to_string()).unwrap();
+    assert_eq!(c, expected);
+}
+
+#[test]
+fn test_record_optional_fields_test() {
+    let record = Value::Map(vec![
+        ("tg".into(), "840539006".into()),
+        ("tt".into(), "LP6464-4".into()),
+        ("sc".into(), "2021-05-10T10:27:15Z".into()),
+        ("tr".into(), "260415000".into()),
+        ("co".into(), "IT".into()),
+        ("is".into(), "IT".into()),
+        ("ci".into(), "01IT0BFC9866D3854EAC82C21654B6F6DE32#1".into()),
+    ]);
+    let t: TestRecord = record.deserialized().unwrap();
+    assert_eq!((t.nm, t.ma, t.dr, t.tc), Default::default());
+
+    // What can be missing from the record is optional in the schema too.
+    let certificate = Value::Map(vec![
+        ("ver".into(), "1.3.0".into()),
+        (
+            "nam".into(),
+            Value::Map(vec![("fnt".into(), "DI<CAPRIO".into())]),
+        ),
+        ("dob".into(), "1977-06-16".into()),
+        ("t".into(), Value::Array(vec![record])),
+    ]);
+    assert_eq!(certificate_violations(&certificate), vec![]);
+    // And a certificate like that one decodes as well.
+    let c: Certificate = certificate.deserialized().unwrap();
+    assert_eq!(c.nam.fnt, "DI<CAPRIO");
+    assert_eq!((c.nam.fn_, c.nam.gn, c.nam.gnt), Default::default());
+    assert_eq!(c.t.len(), 1);
+}
+
+#[test]
+fn decode_payload_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/1.json
+    // It is licensed under Apache-2.0 License.
+    let vaccination_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II5XHC9B5G2+$N IOP-IA%NFQGRJPC%OQHIZC4.OI1RM8ZA.A5:S9MKN4NN3F85QNCY0O%0VZ001HOC9JU0D0HT0HB2PL/IB*09B9LW4T*8+DCMH0LDK2%K:XFE70*LP$V25$0Q:J:4MO1P0%0L0HD+9E/HY+4J6TH48S%4K.GJ2PT3QY:GQ3TE2I+-CPHN6D7LLK*2HG%89UV-0LZ 2ZJJ524-LH/CJTK96L6SR9MU9DHGZ%P WUQRENS431T1XCNCF+47AY0-IFO0500TGPN8F5G.41Q2E4T8ALW.INSV$ 07UV5SR+BNQHNML7 /KD3TU 4V*CAT3ZGLQMI/XI%ZJNSBBXK2:UG%UJMI:TU+MMPZ5$/PMX19UE:-PSR3/$NU44CBE6DQ3D7B0FBOFX0DV2DGMB$YPF62I$60/F$Z2I6IFX21XNI-LM%3/DF/U6Z9FEOJVRLVW6K$UG+BKK57:1+D10%4K83F+1VWD1NE";
+
+    let p = decode_payload(vaccination_data.to_string()).unwrap();
+    assert_eq!(p.issuer, "IT");
+    assert_eq!(p.issued_at, 1621593224);
+    assert_eq!(p.expires_at, 1637148824);
+    assert_eq!(
+        p.certificate().unwrap(),
+        decode(vaccination_data.to_string()).unwrap()
+    );
+    assert_eq!(p.certs().unwrap().len(), 1);
+    // The certificates are deserialized once, then lent on every call.
+    assert!(std::ptr::eq(p.certs().unwrap(), p.certs().unwrap()));
+    assert_eq!(p, decode_payload(vaccination_data.to_string()).unwrap());
+}
+
+#[test]
+fn extension_test() {
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    struct Booster(String);
+    impl Extension for Booster {
+        const KEY: ExtensionKey = ExtensionKey::Hcert(2);
+        fn decode(value: &Value) -> std::result::Result<Self, Error> {
+            Ok(Booster(value.as_text().ok_or("not a text")?.to_string()))
+        }
+    }
+    struct Region(i128);
+    impl Extension for Region {
+        const KEY: ExtensionKey = ExtensionKey::Claim(-261);
+        fn decode(value: &Value) -> std::result::Result<Self, Error> {
+            Ok(Region(value.as_integer().ok_or("not an integer")?.into()))
+        }
+    }
+    struct Missing;
+    impl Extension for Missing {
+        const KEY: ExtensionKey = ExtensionKey::Hcert(3);
+        fn decode(_: &Value) -> std::result::Result<Self, Error> {
+            Ok(Missing)
+        }
+    }
+    struct Invalid;
+    impl Extension for Invalid {
+        const KEY: ExtensionKey = ExtensionKey::Hcert(2);
+        fn decode(_: &Value) -> std::result::Result<Self, Error> {
+            Err("not a booster".into())
+        }
+    }
+
+    let hcert = Value::Map(vec![
+        (1.into(), Value::Map(vec![])),
+        (2.into(), "third dose".into()),
+    ]);
+    let claims = Value::Map(vec![
+        (1.into(), "IT".into()),
+        (4.into(), 1637148824.into()),
+        (6.into(), 1621593224.into()),
+        ((-260).into(), hcert),
+        ((-261).into(), 39.into()),
+    ]);
+    let mut cbor = Vec::new();
+    ciborium::ser::into_writer(&claims, &mut cbor).unwrap();
+    let payload = parse_hcert(&cbor).unwrap();
+
+    assert_eq!(
+        payload.extension::<Booster>().unwrap().unwrap().0,
+        "third dose"
+    );
+    assert_eq!(payload.extension::<Region>().unwrap().unwrap().0, 39);
+    assert!(payload.extension::<Missing>().is_none());
+    let error = payload.extension::<Invalid>().unwrap().err().unwrap();
+    assert_eq!(error.to_string(), "invalid extension in hcert entry 2");
+    assert_eq!(error.stage(), "hcert");
+    assert_eq!(
+        std::error::Error::source(&error).unwrap().to_string(),
+        "not a booster"
+    );
+}
+
+#[test]
+fn decode_metadata_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/1.json
+    // It is licensed under Apache-2.0 License.
+    let vaccination_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II5XHC9B5G2+$N IOP-IA%NFQGRJPC%OQHIZC4.OI1RM8ZA.A5:S9MKN4NN3F85QNCY0O%0VZ001HOC9JU0D0HT0HB2PL/IB*09B9LW4T*8+DCMH0LDK2%K:XFE70*LP$V25$0Q:J:4MO1P0%0L0HD+9E/HY+4J6TH48S%4K.GJ2PT3QY:GQ3TE2I+-CPHN6D7LLK*2HG%89UV-0LZ 2ZJJ524-LH/CJTK96L6SR9MU9DHGZ%P WUQRENS431T1XCNCF+47AY0-IFO0500TGPN8F5G.41Q2E4T8ALW.INSV$ 07UV5SR+BNQHNML7 /KD3TU 4V*CAT3ZGLQMI/XI%ZJNSBBXK2:UG%UJMI:TU+MMPZ5$/PMX19UE:-PSR3/$NU44CBE6DQ3D7B0FBOFX0DV2DGMB$YPF62I$60/F$Z2I6IFX21XNI-LM%3/DF/U6Z9FEOJVRLVW6K$UG+BKK57:1+D10%4K83F+1VWD1NE";
+    let expected = Metadata {
+        expires_at: 1637148824,
+        issued_at: 1621593224,
+        issuer: "IT".to_string(),
+        kid: Some(vec![0x39, 0x30, 0x17, 0x68, 0xcd, 0xda, 0x05, 0x13]),
+    };
+
+    let m = decode_metadata(vaccination_data.to_string()).unwrap();
+    assert_eq!(m, expected);
+
+    // The hcert is skipped, so a broken one doesn't get in the way.
+    let claims = Value::Map(vec![
+        (CLAIM_KEY_ISSUER.into(), "IT".into()),
+        (CLAIM_KEY_ISSUED_AT.into(), 1621593224.into()),
+        (CLAIM_KEY_EXPIRETION_TIME.into(), 1637148824.into()),
+        (CLAIM_KEY_HCERT.into(), "not an hcert".into()),
+    ]);
+    let mut payload = Vec::new();
+    ciborium::ser::into_writer(&claims, &mut payload).unwrap();
+    let cose = CoseSign1 {
+        protected: vec![],
+        unprotected: Value::Map(vec![]),
+        payload,
+        signature: vec![],
+    };
+    let options = DecodeOptions::new();
+    assert_eq!(parse_metadata_with(&cose, &options).unwrap().issuer, "IT");
+    assert!(parse_hcert(&cose.payload).unwrap().certificate().is_err());
+
+    let options = options.now(1637148825);
+    assert!(matches!(
+        parse_metadata_with(&cose, &options),
+        Err(DecodeError::Expired { .. })
+    ));
+    let options = DecodeOptions::new().reject_issuers(&["IT"]);
+    let data = vaccination_data.as_bytes();
+    assert!(matches!(
+        decode_metadata_with(data, &options),
+        Err(DecodeError::IssuerRejected(_))
+    ));
+}
+
+#[test]
+fn decode_cose_bytes_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/1.json
+    // It is licensed under Apache-2.0 License.
+    let vaccination_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II5XHC9B5G2+$N IOP-IA%NFQGRJPC%OQHIZC4.OI1RM8ZA.A5:S9MKN4NN3F85QNCY0O%0VZ001HOC9JU0D0HT0HB2PL/IB*09B9LW4T*8+DCMH0LDK2%K:XFE70*LP$V25$0Q:J:4MO1P0%0L0HD+9E/HY+4J6TH48S%4K.GJ2PT3QY:GQ3TE2I+-CPHN6D7LLK*2HG%89UV-0LZ 2ZJJ524-LH/CJTK96L6SR9MU9DHGZ%P WUQRENS431T1XCNCF+47AY0-IFO0500TGPN8F5G.41Q2E4T8ALW.INSV$ 07UV5SR+BNQHNML7 /KD3TU 4V*CAT3ZGLQMI/XI%ZJNSBBXK2:UG%UJMI:TU+MMPZ5$/PMX19UE:-PSR3/$NU44CBE6DQ3D7B0FBOFX0DV2DGMB$YPF62I$60/F$Z2I6IFX21XNI-LM%3/DF/U6Z9FEOJVRLVW6K$UG+BKK57:1+D10%4K83F+1VWD1NE";
+
+    let data = format!("{}\r\n", vaccination_data);
+    let cose = decode_cose_bytes(data.as_bytes()).unwrap();
+    assert_eq!(cose, decode_cose(vaccination_data.to_string()).unwrap());
+
+    assert!(decode_cose_bytes(b"").is_err());
+    assert!(decode_cose_bytes(b"HC1:\xff\xfe").is_err());
+}
+
+#[test]
+fn cose_optional_headers_test() {
+    let signature = Value::Array(vec![
+        Value::Bytes(vec![0xa0]),
+        Value::Map(vec![]),
+        Value::Bytes(vec![1, 2, 3]),
+    ]);
+    let expected = CounterSignature {
+        protected: vec![0xa0],
+        unprotected: Value::Map(vec![]),
+        signature: vec![1, 2, 3],
+    };
+    let cose = |unprotected: Vec<(Value, Value)>| CoseSign1 {
+        protected: vec![],
+        unprotected: Value::Map(unprotected),
+        payload: vec![],
+        signature: vec![],
+    };
+    let label = |label: i16| Value::from(label);
+
+    let empty = cose(vec![]);
+    assert!(empty.x5chain().is_empty());
+    assert!(empty.counter_signatures().is_empty());
+    assert_eq!(empty.counter_signature0(), None);
+
+    let single = cose(vec![
+        (label(COSE_HEADER_X5CHAIN), Value::Bytes(vec![0x30, 0x01])),
+        (label(COSE_HEADER_COUNTER_SIGNATURE), signature.clone()),
+        (
+            label(COSE_HEADER_COUNTER_SIGNATURE0),
+            Value::Bytes(vec![4, 5]),
+        ),
+    ]);
+    assert_eq!(single.x5chain(), vec![vec![0x30, 0x01]]);
+    assert_eq!(single.counter_signatures(), vec![expected]);
+    assert_eq!(single.counter_signature0(), Some(vec![4, 5]));
+
+    let chain = Value::Array(vec![
+        Value::Bytes(vec![0x30, 0x01]),
+        Value::Bytes(vec![0x30, 0x02]),
+    ]);
+    let multiple = cose(vec![
+        (label(COSE_HEADER_X5CHAIN), chain),
+        (
+            label(COSE_HEADER_COUNTER_SIGNATURE),
+            Value::Array(vec![signature.clone(), signature]),
+        ),
+    ]);
+    assert_eq!(multiple.x5chain(), vec![vec![0x30, 0x01], vec![0x30, 0x02]]);
+    assert_eq!(multiple.counter_signatures().len(), 2);
+}
+
+#[test]
+fn signature_hash_test() {
+    let cose = |alg: i16| CoseSign1 {
+        protected: vec![],
+        unprotected: Value::Map(vec![(
+            Value::from(COSE_HEADER_ALG),
+            Value::from(alg),
+        )]),
+        payload: vec![],
+        signature: vec![1, 2, 3, 4],
+    };
+
+    // Only r, the first half, for ECDSA.
+    assert_eq!(
+        hex(&cose(-7).signature_hash()),
+        "a12871fee210fb8619291eaea194581c"
+    );
+    assert_eq!(
+        hex(&cose(-37).signature_hash()),
+        "9f64a747e1b97f131fabb6b447296c9b"
+    );
+}
+
+#[cfg(test)]
+fn encode(value: &Value) -> String {
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+
+    let mut cbor = Vec::new();
+    ciborium::ser::into_writer(value, &mut cbor).unwrap();
+    let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+    zlib.write_all(&cbor).unwrap();
+
+    format!(
+        "{}{}",
+        HC1_FIELD,
+        crate::base45::encode(&zlib.finish().unwrap())
+    )
+}
+
+#[test]
+fn encode_cose_bytes_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/1.json
+    // It is licensed under Apache-2.0 License.
+    let vaccination_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II5XHC9B5G2+$N IOP-IA%NFQGRJPC%OQHIZC4.OI1RM8ZA.A5:S9MKN4NN3F85QNCY0O%0VZ001HOC9JU0D0HT0HB2PL/IB*09B9LW4T*8+DCMH0LDK2%K:XFE70*LP$V25$0Q:J:4MO1P0%0L0HD+9E/HY+4J6TH48S%4K.GJ2PT3QY:GQ3TE2I+-CPHN6D7LLK*2HG%89UV-0LZ 2ZJJ524-LH/CJTK96L6SR9MU9DHGZ%P WUQRENS431T1XCNCF+47AY0-IFO0500TGPN8F5G.41Q2E4T8ALW.INSV$ 07UV5SR+BNQHNML7 /KD3TU 4V*CAT3ZGLQMI/XI%ZJNSBBXK2:UG%UJMI:TU+MMPZ5$/PMX19UE:-PSR3/$NU44CBE6DQ3D7B0FBOFX0DV2DGMB$YPF62I$60/F$Z2I6IFX21XNI-LM%3/DF/U6Z9FEOJVRLVW6K$UG+BKK57:1+D10%4K83F+1VWD1NE";
+    let base45_data = vaccination_data.strip_prefix(HC1_FIELD).unwrap();
+    let cose = decompress(&decode_base45(base45_data.as_bytes()).unwrap());
+    let cose = cose.unwrap();
+
+    let encoded = encode_cose_bytes(&cose).unwrap();
+    assert!(encoded.starts_with(HC1_FIELD));
+    let decoded = decode_cose_bytes(encoded.as_bytes()).unwrap();
+    assert_eq!(decoded, parse_cose(&cose).unwrap());
+
+    assert!(matches!(
+        encode_cose_bytes(b"not cose"),
+        Err(DecodeError::Cbor { .. })
+    ));
+}
+
+#[test]
+fn decode_error_test() {
+    use std::error::Error;
+
+    let cose = |payload: Value| {
+        let mut claims = Vec::new();
+        ciborium::ser::into_writer(&payload, &mut claims).unwrap();
+        let content = Value::Array(vec![
+            Value::Bytes(vec![0xa0]),
+            Value::Map(vec![]),
+            Value::Bytes(claims),
+            Value::Bytes(vec![]),
+        ]);
+        encode(&Value::Tag(COSE_SIGN1_TAG, Box::new(content)))
+    };
+    let claims = |hcert: Value| {
+        Value::Map(vec![
+            (CLAIM_KEY_ISSUER.into(), "IT".into()),
+            (CLAIM_KEY_EXPIRETION_TIME.into(), 0.into()),
+            (CLAIM_KEY_ISSUED_AT.into(), 0.into()),
+            (CLAIM_KEY_HCERT.into(), hcert),
+        ])
+    };
+
+    let error = decode_cose_bytes(b"6BFOXN%TS3DH0").unwrap_err();
+    assert!(matches!(error, DecodeError::MissingPrefix));
+    let error = decode_cose_bytes(b"HC1:bb8").unwrap_err();
+    assert!(matches!(error, DecodeError::Base45 { .. }));
+    let error = decode_cose_bytes(b"HC1:BB8").unwrap_err();
+    assert!(matches!(error, DecodeError::Zlib { .. }));
+    assert_eq!(
+        error.to_string(),
+        "invalid zlib data at offset 2 of 2 input bytes"
+    );
+    let data = format!("{}{}", HC1_FIELD, crate::base45::encode(&[0x78]));
+    let error = decode_cose_bytes(data.as_bytes()).unwrap_err();
+    assert!(matches!(error, DecodeError::Zlib { .. }));
+    let error = decode_cose_bytes(encode(&1.into()).as_bytes()).unwrap_err();
+    assert!(matches!(error, DecodeError::Cose(_)));
+
+    let error = decode_payload(cose(1.into())).unwrap_err();
+    assert!(matches!(error, DecodeError::Cwt { .. }));
+    let error = decode(cose(claims(1.into()))).unwrap_err();
+    assert!(matches!(error, DecodeError::Hcert(_)));
+    let error = decode(cose(claims(Value::Map(vec![])))).unwrap_err();
+    assert!(matches!(error, DecodeError::MissingCertificate));
+    assert!(error.source().is_none());
+
+    let error = decode_cose_bytes(b"HC1:BB8BB8bb8").unwrap_err();
+    assert_eq!(error.stage(), "base45");
+    // Only the fast decoder knows where the error is.
+    #[cfg(feature = "fast-base45")]
+    assert_eq!(
+        error.to_string(),
+        "invalid base45 data at offset 6 of 9 input bytes"
+    );
+    #[cfg(feature = "fast-base45")]
+    assert_eq!(
+        error.source().unwrap().to_string(),
+        "invalid base45 character"
+    );
+}
+
+#[test]
+fn stage_functions_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/1.json
+    // It is licensed under Apache-2.0 License.
+    let vaccination_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II5XHC9B5G2+$N IOP-IA%NFQGRJPC%OQHIZC4.OI1RM8ZA.A5:S9MKN4NN3F85QNCY0O%0VZ001HOC9JU0D0HT0HB2PL/IB*09B9LW4T*8+DCMH0LDK2%K:XFE70*LP$V25$0Q:J:4MO1P0%0L0HD+9E/HY+4J6TH48S%4K.GJ2PT3QY:GQ3TE2I+-CPHN6D7LLK*2HG%89UV-0LZ 2ZJJ524-LH/CJTK96L6SR9MU9DHGZ%P WUQRENS431T1XCNCF+47AY0-IFO0500TGPN8F5G.41Q2E4T8ALW.INSV$ 07UV5SR+BNQHNML7 /KD3TU 4V*CAT3ZGLQMI/XI%ZJNSBBXK2:UG%UJMI:TU+MMPZ5$/PMX19UE:-PSR3/$NU44CBE6DQ3D7B0FBOFX0DV2DGMB$YPF62I$60/F$Z2I6IFX21XNI-LM%3/DF/U6Z9FEOJVRLVW6K$UG+BKK57:1+D10%4K83F+1VWD1NE";
+
+    let base45_data = vaccination_data.strip_prefix(HC1_FIELD).unwrap();
+    let compressed = decode_base45(base45_data.as_bytes()).unwrap();
+    let cose = parse_cose(&decompress(&compressed).unwrap()).unwrap();
+    assert_eq!(cose, decode_cose(vaccination_data.to_string()).unwrap());
+    assert_eq!(
+        parse_hcert(&cose.payload).unwrap(),
+        decode_payload(vaccination_data.to_string()).unwrap()
+    );
+
+    let error = decode_base45(b"BB8bb8").unwrap_err();
+    #[cfg(feature = "fast-base45")]
+    assert!(matches!(error, DecodeError::Base45 { offset: 3, .. }));
+    assert!(matches!(error, DecodeError::Base45 { .. }));
+    let error = decompress(b"AB").unwrap_err();
+    assert!(matches!(error, DecodeError::Zlib { .. }));
+    let error = parse_cose(&[0xff]).unwrap_err();
+    assert!(matches!(error, DecodeError::Cbor { .. }));
+    let error = parse_hcert(&[0xff]).unwrap_err();
+    assert!(matches!(error, DecodeError::Cwt { .. }));
+
+    let options = DecodeOptions::new().reject_issuers(&["IT"]);
+    let error = parse_hcert_with(&cose.payload, &options).unwrap_err();
+    assert!(matches!(error, DecodeError::IssuerRejected(_)));
+}
+
+#[test]
+fn decode_with_test() {
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/1.json
+    // It is licensed under Apache-2.0 License.
+    let vaccination_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II5XHC9B5G2+$N IOP-IA%NFQGRJPC%OQHIZC4.OI1RM8ZA.A5:S9MKN4NN3F85QNCY0O%0VZ001HOC9JU0D0HT0HB2PL/IB*09B9LW4T*8+DCMH0LDK2%K:XFE70*LP$V25$0Q:J:4MO1P0%0L0HD+9E/HY+4J6TH48S%4K.GJ2PT3QY:GQ3TE2I+-CPHN6D7LLK*2HG%89UV-0LZ 2ZJJ524-LH/CJTK96L6SR9MU9DHGZ%P WUQRENS431T1XCNCF+47AY0-IFO0500TGPN8F5G.41Q2E4T8ALW.INSV$ 07UV5SR+BNQHNML7 /KD3TU 4V*CAT3ZGLQMI/XI%ZJNSBBXK2:UG%UJMI:TU+MMPZ5$/PMX19UE:-PSR3/$NU44CBE6DQ3D7B0FBOFX0DV2DGMB$YPF62I$60/F$Z2I6IFX21XNI-LM%3/DF/U6Z9FEOJVRLVW6K$UG+BKK57:1+D10%4K83F+1VWD1NE";
+    let data = vaccination_data.as_bytes();
+    let expected = decode(vaccination_data.to_string()).unwrap();
+
+    let options = DecodeOptions::new();
+    assert_eq!(decode_with(data, &options).unwrap(), expected);
+
+    let unprefixed = &data[HC1_FIELD.len()..];
+    let error = decode_with(unprefixed, &options).unwrap_err();
+    assert!(matches!(error, DecodeError::MissingPrefix));
+    let options = DecodeOptions::new().require_prefix(false);
+    assert_eq!(decode_with(unprefixed, &options).unwrap(), expected);
+
+    let options = DecodeOptions::new().max_input_len(100);
+    let error = decode_with(data, &options).unwrap_err();
+    assert!(matches!(
+        error,
+        DecodeError::InputTooLarge { limit: 100, .. }
+    ));
+    let options = DecodeOptions::new().max_decompressed_len(100);
+    let error = decode_with(data, &options).unwrap_err();
+    assert!(matches!(
+        error,
+        DecodeError::DecompressedTooLarge { limit: 100 }
+    ));
+    // The same limits hold for the stages run one at a time.
+    let text = base45_text_with(data, &DecodeOptions::new()).unwrap();
+    let compressed = decode_base45(text).unwrap();
+    let error = decompress_with(&compressed, &options).unwrap_err();
+    assert!(matches!(
+        error,
+        DecodeError::DecompressedTooLarge { limit: 100 }
+    ));
+    let decompressed = decompress(&compressed).unwrap();
+    let len = decompressed.len() as u64;
+    let options = DecodeOptions::new().max_decompressed_len(len);
+    assert_eq!(
+        decompress_with(&compressed, &options).unwrap(),
+        decompressed
+    );
+
+    let payload = decode_payload(vaccination_data.to_string()).unwrap();
+    let options = DecodeOptions::new().now(payload.expires_at);
+    assert_eq!(decode_with(data, &options).unwrap(), expected);
+    let options = DecodeOptions::new().now(payload.expires_at + 1);
+    let error = decode_with(data, &options).unwrap_err();
+    assert!(matches!(error, DecodeError::Expired { .. }));
+
+    let options = DecodeOptions::new().accept_issuers(&["de", "it"]);
+    assert_eq!(decode_with(data, &options).unwrap(), expected);
+    let options = DecodeOptions::new().accept_issuers(&["DE"]);
+    let error = decode_with(data, &options).unwrap_err();
+    assert!(
+        matches!(error, DecodeError::IssuerRejected(issuer) if issuer == "IT")
+    );
+    let options = DecodeOptions::new().reject_issuers(&["IT"]);
+    let error = decode_with(data, &options).unwrap_err();
+    assert!(matches!(error, DecodeError::IssuerRejected(_)));
+
+    let options = DecodeOptions::new().unknown_fields(UnknownFields::Reject);
+    assert_eq!(decode_with(data, &options).unwrap(), expected);
+    let options = DecodeOptions::new().strict(true);
+    assert_eq!(decode_with(data, &options).unwrap(), expected);
+    let certificate = Value::Map(vec![
+        ("ver".into(), "1.0.0".into()),
+        ("xyz".into(), 1.into()),
+        (
+            "nam".into(),
+            Value::Map(vec![
+                ("fn".into(), "".into()),
+                ("abc".into(), 1.into()),
+            ]),
+        ),
+    ]);
+    assert_eq!(
+        certificate_unknown_fields(&certificate),
+        vec!["xyz".to_string(), "nam.abc".to_string()]
+    );
+}
+
+#[test]
+fn malformed_input_test() {
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/1.json
+    // It is licensed under Apache-2.0 License.
+    let vaccination_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II5XHC9B5G2+$N IOP-IA%NFQGRJPC%OQHIZC4.OI1RM8ZA.A5:S9MKN4NN3F85QNCY0O%0VZ001HOC9JU0D0HT0HB2PL/IB*09B9LW4T*8+DCMH0LDK2%K:XFE70*LP$V25$0Q:J:4MO1P0%0L0HD+9E/HY+4J6TH48S%4K.GJ2PT3QY:GQ3TE2I+-CPHN6D7LLK*2HG%89UV-0LZ 2ZJJ524-LH/CJTK96L6SR9MU9DHGZ%P WUQRENS431T1XCNCF+47AY0-IFO0500TGPN8F5G.41Q2E4T8ALW.INSV$ 07UV5SR+BNQHNML7 /KD3TU 4V*CAT3ZGLQMI/XI%ZJNSBBXK2:UG%UJMI:TU+MMPZ5$/PMX19UE:-PSR3/$NU44CBE6DQ3D7B0FBOFX0DV2DGMB$YPF62I$60/F$Z2I6IFX21XNI-LM%3/DF/U6Z9FEOJVRLVW6K$UG+BKK57:1+D10%4K83F+1VWD1NE";
+    let data = vaccination_data.as_bytes();
+
+    // Every truncation of the certificate.
+    for end in 0..data.len() {
+        assert!(decode_with(&data[..end], &DecodeOptions::new()).is_err());
+    }
+
+    // Pseudorandom bytes, at every stage of the pipeline.
+    let mut state: u32 = 0x2545_f491;
+    let mut random = |len: usize| -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    };
+    for len in 0..256 {
+        let bytes = random(len);
+        let text = format!("{}{}", HC1_FIELD, crate::base45::encode(&bytes));
+        assert!(decode(text).is_err());
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(&bytes).unwrap();
+        let compressed = zlib.finish().unwrap();
+        let text =
+            format!("{}{}", HC1_FIELD, crate::base45::encode(&compressed));
+        assert!(decode(text).is_err());
+        assert!(decode_with(&bytes, &DecodeOptions::new()).is_err());
+    }
+
+    // Every byte of the COSE structure flipped: some of them only change
+    // the signature, so decoding may succeed, but never panics.
+    let base45 = decode_base45(&data[HC1_FIELD.len()..]).unwrap();
+    let cose = decompress(&base45).unwrap();
+    for i in 0..cose.len() {
+        let mut mutated = cose.clone();
+        mutated[i] ^= 0xff;
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(&mutated).unwrap();
+        let compressed = zlib.finish().unwrap();
+        let text =
+            format!("{}{}", HC1_FIELD, crate::base45::encode(&compressed));
+        let _ = decode(text);
+    }
+}
@@ -0,0 +1,53 @@
+//! Decoder for the EU Digital COVID Certificate (EUDCC).
+//!
+//! This is the core of eudccdec, with no dependency on the command line
+//! interface, so that it can be embedded anywhere.
+//!
+//! It ignores COSE signing and extracts the EUDCC payload:
+//!
+//! ```no_run
+//! let data = std::fs::read_to_string("certificate.txt").unwrap();
+//! let certificate = eudcc_core::decode(data).unwrap();
+//! println!("{:#?}", certificate);
+//! ```
+//!
+//! Decoding never panics, whatever the input: errors are always returned,
+//! and the lints below keep indexing, unwrapping and panicking out of the
+//! decoding code.
+
+#![forbid(unsafe_code)]
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::expect_used,
+        clippy::indexing_slicing,
+        clippy::panic,
+        clippy::unreachable,
+        clippy::unwrap_used
+    )
+)]
+
+#[cfg(not(any(feature = "fast-base45", feature = "base45")))]
+compile_error!("a base45 decoder is needed: enable fast-base45 or base45");
+
+mod base45;
+mod cache;
+mod error;
+pub mod eudcc;
+mod metrics;
+mod schema;
+mod sha256;
+
+pub use cache::DecodeCache;
+pub use eudcc::{
+    base45_text_with, decode, decode_base45, decode_cose_bytes,
+    decode_cose_with, decode_metadata, decode_metadata_with, decode_payload,
+    decode_payload_with, decode_with, decompress, decompress_with,
+    encode_cose_bytes, issuer_accepted, parse_cose, parse_hcert,
+    parse_hcert_with, parse_metadata_with, Certificate, CoseSign1,
+    CounterSignature, DecodeError, DecodeOptions, Extension, ExtensionKey,
+    Name, Payload, RecoveryRecord, TestRecord, UnknownFields, VaccineRecord,
+    Violation,
+};
+pub use metrics::{metered, Counters, Metrics};
+pub use sha256::{hmac_sha256, sha256};
@@ -0,0 +1,174 @@
+//! Hooks for the telemetry of embedding applications: how many
+//! certificates were decoded and verified, and how long decoding took.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::error::Result;
+
+/// Receiver of the outcome of every decoding and verification, shared by
+/// all the threads doing them.
+///
+/// Durations are `None` on targets without a clock, like
+/// `wasm32-unknown-unknown`.
+pub trait Metrics: Send + Sync {
+    /// A certificate was decoded, in `elapsed`.
+    fn decoded(&self, elapsed: Option<Duration>);
+
+    /// Decoding failed at `stage`, as [`DecodeError::stage`] names it,
+    /// after `elapsed`.
+    ///
+    /// [`DecodeError::stage`]: crate::DecodeError::stage
+    fn failed(&self, stage: &'static str, elapsed: Option<Duration>);
+
+    /// A decoded certificate was verified, and found `valid` or not.
+    fn verified(&self, valid: bool) {
+        let _ = valid;
+    }
+}
+
+/// Run a decoding, like [`decode_payload_with`](crate::decode_payload_with),
+/// reporting its outcome to `metrics`.
+///
+/// ```
+/// use eudcc_core::{metered, Counters, DecodeOptions};
+///
+/// let counters = Counters::new();
+/// let options = DecodeOptions::new();
+/// let result = metered(&counters, || {
+///     eudcc_core::decode_payload_with(b"HC1:", &options)
+/// });
+/// assert!(result.is_err());
+/// assert_eq!(counters.failed(), 1);
+/// ```
+pub fn metered<T>(
+    metrics: &dyn Metrics,
+    decode: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    let start = clock::now();
+    let result = decode();
+    let elapsed = start.map(|start| start.elapsed());
+    match &result {
+        Ok(_) => metrics.decoded(elapsed),
+        Err(error) => metrics.failed(error.stage(), elapsed),
+    }
+
+    result
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+mod clock {
+    pub fn now() -> Option<std::time::Instant> {
+        Some(std::time::Instant::now())
+    }
+}
+
+// Asking for the time panics there.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+mod clock {
+    pub fn now() -> Option<std::time::Instant> {
+        None
+    }
+}
+
+/// Metrics kept as atomic counters, for applications that only poll them.
+#[derive(Debug, Default)]
+pub struct Counters {
+    decoded: AtomicU64,
+    failed: AtomicU64,
+    valid: AtomicU64,
+    invalid: AtomicU64,
+    decode_nanos: AtomicU64,
+}
+
+impl Counters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of certificates decoded.
+    pub fn decoded(&self) -> u64 {
+        self.decoded.load(Ordering::Relaxed)
+    }
+
+    /// The number of decodings that failed.
+    pub fn failed(&self) -> u64 {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// The number of certificates verified and found valid.
+    pub fn valid(&self) -> u64 {
+        self.valid.load(Ordering::Relaxed)
+    }
+
+    /// The number of certificates verified and found invalid.
+    pub fn invalid(&self) -> u64 {
+        self.invalid.load(Ordering::Relaxed)
+    }
+
+    /// The time spent decoding, failures included.
+    pub fn decode_time(&self) -> Duration {
+        Duration::from_nanos(self.decode_nanos.load(Ordering::Relaxed))
+    }
+
+    fn add_time(&self, elapsed: Option<Duration>) {
+        if let Some(elapsed) = elapsed {
+            let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+            self.decode_nanos.fetch_add(nanos, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Metrics for Counters {
+    fn decoded(&self, elapsed: Option<Duration>) {
+        self.decoded.fetch_add(1, Ordering::Relaxed);
+        self.add_time(elapsed);
+    }
+
+    fn failed(&self, _stage: &'static str, elapsed: Option<Duration>) {
+        self.failed.fetch_add(1, Ordering::Relaxed);
+        self.add_time(elapsed);
+    }
+
+    fn verified(&self, valid: bool) {
+        let counter = if valid { &self.valid } else { &self.invalid };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn metered_test() {
+    use std::sync::Mutex;
+
+    use crate::eudcc::{decode_payload_with, DecodeOptions};
+
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/2.json
+    // It is licensed under Apache-2.0 License.
+    let recovery_data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *MEB2B2JJ59J-9BC6:X9NECX0AKQC:3DCV4*XUA2P-FHT-H4SI/J9WVHWVH+ZEOV1J$HNTICZUBOM*LP$V25$0Q:J40IA3L/*84-5%:C92JN*4CY0*%9F/8J2P4.818T+:IX3M3.96RPVD9J-OZT1-NT0 2$$0$2PZX69B9VCDHI2/T9TU1BPIJKH/T7B-S-*O/Y41FD+X49+5Z-6%.HDD8R6W1FDJGJSFJ/4Q:T0.KJTNP8EFULNC:HA0K5HKRB4TD85LOLF92GF.3O.Z8CC7-2FQYG$%21 2O*4R60NM8JI0EUGP$I/XK$M8ZQE6YB9M66P8N31I.ROSK%IA1Q2N53Q-OQ2VC6E26T11ROSNK5W-*H+MJ%0RGZVGWNURI75RBSQSHLH1JG*CMH2.-S$7VX6N*Z1881J7G.F9I+SV06F+1M*93%D";
+    let options = DecodeOptions::new();
+    let decode = |data: &str| decode_payload_with(data.as_bytes(), &options);
+
+    let counters = Counters::new();
+    assert!(metered(&counters, || decode(recovery_data)).is_ok());
+    assert!(metered(&counters, || decode("HC1:ABC")).is_err());
+    counters.verified(false);
+    assert_eq!(counters.decoded(), 1);
+    assert_eq!(counters.failed(), 1);
+    assert_eq!((counters.valid(), counters.invalid()), (0, 1));
+    assert!(counters.decode_time() > Duration::ZERO);
+
+    // Implementations get the stage of failures.
+    #[derive(Default)]
+    struct Stages(Mutex<Vec<&'static str>>);
+    impl Metrics for Stages {
+        fn decoded(&self, _elapsed: Option<Duration>) {}
+        fn failed(&self, stage: &'static str, _elapsed: Option<Duration>) {
+            self.0.lock().unwrap().push(stage);
+        }
+    }
+    let stages = Stages::default();
+    let _ = metered(&stages, || decode("6BFOXN"));
+    let _ = metered(&stages, || decode("HC1:ABC"));
+    assert_eq!(*stages.0.lock().unwrap(), ["prefix", "zlib"]);
+}
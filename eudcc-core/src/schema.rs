@@ -0,0 +1,433 @@
+use std::fmt;
+
+use ciborium::value::Value;
+
+// Longest text the schema allows in free text fields.
+const MAX_TEXT_LEN: usize = 80;
+
+/// A part of a certificate breaking its schema, or the rules it implies,
+/// found at a JSON path like `$.v[0].dt`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// What a field holds.
+#[derive(Clone, Copy)]
+enum Kind {
+    /// A code of a value set.
+    Code,
+    /// Free text.
+    Text,
+    /// A semantic version.
+    Version,
+    /// A date of birth, which can be partial or empty.
+    Birth,
+    /// A date, as YYYY-MM-DD.
+    Date,
+    /// A date and time, as an RFC 3339 timestamp.
+    DateTime,
+    /// An ISO 3166-1 alpha-2 country code.
+    Country,
+    /// A name standardised as in machine readable travel documents.
+    Standardised,
+    /// A number of doses.
+    Dose,
+    /// A map, checked on its own.
+    Map,
+    /// A group of records, checked on its own.
+    Group,
+}
+
+struct Field {
+    name: &'static str,
+    kind: Kind,
+    required: bool,
+}
+
+const fn required(name: &'static str, kind: Kind) -> Field {
+    Field {
+        name,
+        kind,
+        required: true,
+    }
+}
+
+const fn optional(name: &'static str, kind: Kind) -> Field {
+    Field {
+        name,
+        kind,
+        required: false,
+    }
+}
+
+const CERTIFICATE: &[Field] = &[
+    required("ver", Kind::Version),
+    required("nam", Kind::Map),
+    required("dob", Kind::Birth),
+    optional("v", Kind::Group),
+    optional("r", Kind::Group),
+    optional("t", Kind::Group),
+];
+const NAME: &[Field] = &[
+    optional("fn", Kind::Text),
+    required("fnt", Kind::Standardised),
+    optional("gn", Kind::Text),
+    optional("gnt", Kind::Standardised),
+];
+const VACCINATION: &[Field] = &[
+    required("tg", Kind::Code),
+    required("vp", Kind::Code),
+    required("mp", Kind::Code),
+    required("ma", Kind::Code),
+    required("dn", Kind::Dose),
+    required("sd", Kind::Dose),
+    required("dt", Kind::Date),
+    required("co", Kind::Country),
+    required("is", Kind::Text),
+    required("ci", Kind::Text),
+];
+const RECOVERY: &[Field] = &[
+    required("tg", Kind::Code),
+    required("fr", Kind::Date),
+    required("co", Kind::Country),
+    required("is", Kind::Text),
+    required("df", Kind::Date),
+    required("du", Kind::Date),
+    required("ci", Kind::Text),
+];
+const TEST: &[Field] = &[
+    required("tg", Kind::Code),
+    required("tt", Kind::Code),
+    optional("nm", Kind::Text),
+    optional("ma", Kind::Code),
+    required("sc", Kind::DateTime),
+    optional("dr", Kind::DateTime),
+    required("tr", Kind::Code),
+    optional("tc", Kind::Text),
+    required("co", Kind::Country),
+    required("is", Kind::Text),
+    required("ci", Kind::Text),
+];
+
+fn digits(text: &str) -> Option<u32> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+fn is_date(text: &str) -> bool {
+    let mut parts = text.split('-');
+    let (year, month, day) = match (
+        parts.next().filter(|p| p.len() == 4).and_then(digits),
+        parts.next().filter(|p| p.len() == 2).and_then(digits),
+        parts.next().filter(|p| p.len() == 2).and_then(digits),
+        parts.next(),
+    ) {
+        (Some(year), Some(month), Some(day), None) => (year, month, day),
+        _ => return false,
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+
+    (1..=days).contains(&day)
+}
+
+fn is_birth(text: &str) -> bool {
+    match text.len() {
+        0 => true,
+        4 => digits(text).is_some(),
+        7 => is_date(&format!("{}-01", text)),
+        _ => is_date(text),
+    }
+}
+
+fn is_date_time(text: &str) -> bool {
+    let (date, time) = match text.split_once('T') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    let time = time.trim_end_matches('Z');
+    let time = match time.find(&['+', '-'][..]) {
+        Some(zone) => time.get(..zone).unwrap_or_default(),
+        None => time,
+    };
+    let time = time.split('.').next().unwrap_or_default();
+    let mut parts = time.split(':').map(|p| match p.len() {
+        2 => digits(p),
+        _ => None,
+    });
+    let valid_time = matches!(
+        (parts.next(), parts.next(), parts.next(), parts.next()),
+        (Some(Some(h)), Some(Some(m)), Some(Some(s)), None)
+            if h < 24 && m < 60 && s < 61
+    );
+
+    is_date(date) && valid_time
+}
+
+fn is_version(text: &str) -> bool {
+    let parts: Vec<&str> = text.split('.').collect();
+    parts.len() == 3 && parts.iter().all(|p| digits(p).is_some())
+}
+
+/// Check a field against its kind, telling what is wrong with it.
+fn check(kind: Kind, value: &Value) -> Option<&'static str> {
+    if let Kind::Dose = kind {
+        return match value.as_integer().map(i128::from) {
+            Some(dose) if (1..=9).contains(&dose) => None,
+            Some(_) => Some("must be a number of doses from 1 to 9"),
+            None => Some("must be an integer"),
+        };
+    }
+    if let Kind::Map = kind {
+        return value.as_map().map_or(Some("must be an object"), |_| None);
+    }
+    if let Kind::Group = kind {
+        return value.as_array().map_or(Some("must be an array"), |_| None);
+    }
+
+    let text = match value.as_text() {
+        Some(text) => text,
+        None => return Some("must be a string"),
+    };
+    let valid = match kind {
+        Kind::Code => !text.is_empty(),
+        Kind::Text => text.chars().count() <= MAX_TEXT_LEN,
+        Kind::Version => is_version(text),
+        Kind::Birth => is_birth(text),
+        Kind::Date => is_date(text),
+        Kind::DateTime => is_date_time(text),
+        Kind::Country => {
+            text.len() == 2 && text.bytes().all(|b| b.is_ascii_uppercase())
+        }
+        Kind::Standardised => {
+            text.len() <= MAX_TEXT_LEN
+                && text.bytes().all(|b| b.is_ascii_uppercase() || b == b'<')
+        }
+        Kind::Dose | Kind::Map | Kind::Group => true,
+    };
+    if valid {
+        return None;
+    }
+
+    Some(match kind {
+        Kind::Code => "must not be empty",
+        Kind::Text => "must be at most 80 characters long",
+        Kind::Version => "must be a version, like 1.3.0",
+        Kind::Birth => "must be a date, as YYYY-MM-DD, YYYY-MM or YYYY",
+        Kind::Date => "must be a date, as YYYY-MM-DD",
+        Kind::DateTime => "must be a date and time, as RFC 3339 requires",
+        Kind::Country => "must be an ISO 3166-1 alpha-2 country code",
+        Kind::Standardised => {
+            "must be at most 80 uppercase letters, with < for spaces"
+        }
+        Kind::Dose | Kind::Map | Kind::Group => "is invalid",
+    })
+}
+
+fn violation(found: &mut Vec<Violation>, path: String, message: &str) {
+    found.push(Violation {
+        path,
+        message: message.to_string(),
+    });
+}
+
+fn field<'a>(entries: &'a [(Value, Value)], name: &str) -> Option<&'a Value> {
+    entries
+        .iter()
+        .find(|(k, _)| k.as_text() == Some(name))
+        .map(|(_, v)| v)
+}
+
+/// Check the fields of an object: unknown ones, missing ones and those of
+/// the wrong kind.
+fn check_object<'a>(
+    value: &'a Value,
+    fields: &[Field],
+    path: &str,
+    found: &mut Vec<Violation>,
+) -> &'a [(Value, Value)] {
+    let entries = match value.as_map() {
+        Some(entries) => entries,
+        None => {
+            violation(found, path.to_string(), "must be an object");
+            return &[];
+        }
+    };
+
+    for (key, value) in entries {
+        let name = match key.as_text() {
+            Some(name) => name,
+            None => {
+                let path = format!("{}[{:?}]", path, key);
+                violation(found, path, "keys must be strings");
+                continue;
+            }
+        };
+        let path = format!("{}.{}", path, name);
+        match fields.iter().find(|f| f.name == name) {
+            Some(field) => {
+                if let Some(message) = check(field.kind, value) {
+                    violation(found, path, message);
+                }
+            }
+            None => violation(found, path, "is not part of the schema"),
+        }
+    }
+    for f in fields.iter().filter(|f| f.required) {
+        if field(entries, f.name).is_none() {
+            let path = format!("{}.{}", path, f.name);
+            violation(found, path, "is required");
+        }
+    }
+
+    entries
+}
+
+/// Every violation of the schema of an EU Digital COVID Certificate v1,
+/// rather than the first one, so that they can all be fixed at once.
+pub fn certificate_violations(certificate: &Value) -> Vec<Violation> {
+    let mut found = Vec::new();
+    let entries = check_object(certificate, CERTIFICATE, "$", &mut found);
+    if let Some(name) = field(entries, "nam") {
+        if name.as_map().is_some() {
+            check_object(name, NAME, "$.nam", &mut found);
+        }
+    }
+
+    let mut groups = 0;
+    for (group, fields) in [("v", VACCINATION), ("r", RECOVERY), ("t", TEST)] {
+        let records = match field(entries, group).and_then(Value::as_array) {
+            Some(records) => records,
+            None => continue,
+        };
+        match records.len() {
+            0 => violation(
+                &mut found,
+                format!("$.{}", group),
+                "must not be empty",
+            ),
+            1 => {}
+            _ => violation(
+                &mut found,
+                format!("$.{}", group),
+                "must hold a single record",
+            ),
+        }
+        if !records.is_empty() {
+            groups += 1;
+        }
+        for (i, record) in records.iter().enumerate() {
+            let path = format!("$.{}[{}]", group, i);
+            let record = check_object(record, fields, &path, &mut found);
+            // Dates are valid or already reported, so they are only
+            // compared when they are valid.
+            let date = |name| {
+                field(record, name)
+                    .and_then(Value::as_text)
+                    .filter(|d| is_date(d))
+            };
+            if let (Some(from), Some(until)) = (date("df"), date("du")) {
+                if until < from {
+                    let path = format!("{}.du", path);
+                    violation(&mut found, path, "must not be before df");
+                }
+            }
+        }
+    }
+    match groups {
+        0 => violation(
+            &mut found,
+            "$".to_string(),
+            "must hold a vaccination, recovery or test record",
+        ),
+        1 => {}
+        _ => violation(
+            &mut found,
+            "$".to_string(),
+            "must hold a single kind of record",
+        ),
+    }
+
+    found
+}
+
+#[test]
+fn check_test() {
+    assert!(is_date("2020-02-29"));
+    assert!(!is_date("2021-02-29"));
+    assert!(!is_date("2021-13-01"));
+    assert!(!is_date("2021-1-01"));
+    assert!(is_birth("1977"));
+    assert!(is_birth("1977-06"));
+    assert!(is_birth(""));
+    assert!(!is_birth("1977-6"));
+    assert!(is_date_time("2021-04-13T14:20:00+00:00"));
+    assert!(is_date_time("2021-04-13T14:20:00.123Z"));
+    assert!(!is_date_time("2021-04-13 14:20:00"));
+    assert!(!is_date_time("2021-04-13T25:20:00Z"));
+    assert!(is_version("1.3.0"));
+    assert!(!is_version("1.3"));
+}
+
+#[test]
+fn certificate_violations_test() {
+    let record = Value::Map(vec![
+        ("tg".into(), "840539006".into()),
+        ("fr".into(), "2021-05-02".into()),
+        ("co".into(), "it".into()),
+        ("is".into(), "IT".into()),
+        ("df".into(), "2021-05-04".into()),
+        ("du".into(), "2021-05-03".into()),
+        ("xyz".into(), 1.into()),
+    ]);
+    let certificate = Value::Map(vec![
+        ("ver".into(), "1.0".into()),
+        (
+            "nam".into(),
+            Value::Map(vec![
+                ("fn".into(), "Di Caprio".into()),
+                ("gnt".into(), "MARILU TERESA".into()),
+            ]),
+        ),
+        ("dob".into(), "1977-06-16".into()),
+        ("r".into(), Value::Array(vec![record])),
+    ]);
+
+    let found: Vec<String> = certificate_violations(&certificate)
+        .iter()
+        .map(Violation::to_string)
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            "$.ver: must be a version, like 1.3.0",
+            "$.nam.gnt: must be at most 80 uppercase letters, with < for spaces",
+            "$.nam.fnt: is required",
+            "$.r[0].co: must be an ISO 3166-1 alpha-2 country code",
+            "$.r[0].xyz: is not part of the schema",
+            "$.r[0].ci: is required",
+            "$.r[0].du: must not be before df",
+        ]
+    );
+
+    let found = certificate_violations(&Value::Map(vec![]));
+    let paths: Vec<&str> = found.iter().map(|v| v.path.as_str()).collect();
+    assert_eq!(paths, vec!["$.ver", "$.nam", "$.dob", "$"]);
+    assert_eq!(certificate_violations(&Value::Bool(true)).len(), 2);
+}
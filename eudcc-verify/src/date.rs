@@ -0,0 +1,248 @@
+use std::error::Error;
+use std::fmt;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Error raised when a certificate date can't be parsed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvalidDate(pub String);
+
+impl fmt::Display for InvalidDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid date {:?}", self.0)
+    }
+}
+
+impl Error for InvalidDate {}
+
+/// A calendar date, in the proleptic Gregorian calendar.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Date {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+}
+
+fn is_leap_year(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn number(s: &str) -> Option<u32> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+impl Date {
+    /// Parse a `YYYY-MM-DD` date. The time of an ISO 8601 date and time,
+    /// following a `T`, is ignored.
+    pub fn parse(s: &str) -> Result<Date, InvalidDate> {
+        let invalid = || InvalidDate(s.to_string());
+        let date = s.get(..10).ok_or_else(invalid)?;
+        if s.len() > 10 && !s[10..].starts_with('T') {
+            return Err(invalid());
+        }
+
+        let bytes = date.as_bytes();
+        if !date.is_ascii() || bytes[4] != b'-' || bytes[7] != b'-' {
+            return Err(invalid());
+        }
+        let (year, month, day) =
+            match (number(&date[..4]), number(&date[5..7]), number(&date[8..]))
+            {
+                (Some(year), Some(month), Some(day)) => (year, month, day),
+                _ => return Err(invalid()),
+            };
+        let (year, month, day) = (year as i32, month as u8, day as u8);
+        if !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+        {
+            return Err(invalid());
+        }
+
+        Ok(Date { year, month, day })
+    }
+
+    /// Number of days since 1970-01-01.
+    pub fn days_since_epoch(&self) -> i64 {
+        // Days from civil, counting from March so that leap days come last.
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let month = i64::from(self.month);
+        let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2)
+            / 5
+            + i64::from(self.day)
+            - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4
+            - year_of_era / 100
+            + day_of_year;
+
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// The date a given number of days since 1970-01-01.
+    pub fn from_days_since_epoch(days: i64) -> Date {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524
+            - day_of_era / 146_096)
+            / 365;
+        let day_of_year = day_of_era
+            - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Date {
+            year: year as i32,
+            month,
+            day,
+        }
+    }
+
+    /// The date of a Unix timestamp, in UTC.
+    pub fn from_timestamp(timestamp: i64) -> Date {
+        Date::from_days_since_epoch(timestamp.div_euclid(SECONDS_PER_DAY))
+    }
+
+    pub fn add_days(&self, days: i64) -> Date {
+        Date::from_days_since_epoch(self.days_since_epoch() + days)
+    }
+
+    /// Number of days from `earlier` to this date.
+    pub fn days_since(&self, earlier: Date) -> i64 {
+        self.days_since_epoch() - earlier.days_since_epoch()
+    }
+}
+
+/// Parse an ISO 8601 date and time, like `2021-05-03T10:27:15Z`, into a
+/// Unix timestamp. Fractions of seconds are ignored, and times without an
+/// offset are taken as UTC.
+pub fn parse_timestamp(s: &str) -> Result<i64, InvalidDate> {
+    let invalid = || InvalidDate(s.to_string());
+    if !s.is_ascii() || s.len() < 19 || s.as_bytes()[10] != b'T' {
+        return Err(invalid());
+    }
+    let date = Date::parse(&s[..10]).map_err(|_| invalid())?;
+
+    let time = &s.as_bytes()[11..19];
+    if time[2] != b':' || time[5] != b':' {
+        return Err(invalid());
+    }
+    let (hour, minute, second) =
+        match (number(&s[11..13]), number(&s[14..16]), number(&s[17..19])) {
+            (Some(hour), Some(minute), Some(second))
+                if hour < 24 && minute < 60 && second < 60 =>
+            {
+                (hour, minute, second)
+            }
+            _ => return Err(invalid()),
+        };
+
+    let mut zone = &s[19..];
+    if let Some(fraction) = zone.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return Err(invalid());
+        }
+        zone = &fraction[digits..];
+    }
+    let offset = match zone {
+        "" | "Z" => 0,
+        _ => {
+            let sign = match zone.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return Err(invalid()),
+            };
+            let (hours, minutes) = match zone.len() {
+                6 if zone.as_bytes()[3] == b':' => (&zone[1..3], &zone[4..]),
+                5 => (&zone[1..3], &zone[3..]),
+                _ => return Err(invalid()),
+            };
+            match (number(hours), number(minutes)) {
+                (Some(hours), Some(minutes)) if hours < 24 && minutes < 60 => {
+                    sign * i64::from(hours * 3600 + minutes * 60)
+                }
+                _ => return Err(invalid()),
+            }
+        }
+    };
+
+    let seconds = i64::from(hour * 3600 + minute * 60 + second);
+    Ok(date.days_since_epoch() * SECONDS_PER_DAY + seconds - offset)
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[test]
+fn date_test() {
+    let date = Date::parse("2021-04-10").unwrap();
+    assert_eq!(date, Date::parse("2021-04-10T08:30:00Z").unwrap());
+    assert_eq!(date.to_string(), "2021-04-10");
+    assert_eq!(date.days_since_epoch(), 18727);
+    assert_eq!(Date::from_days_since_epoch(18727), date);
+    assert_eq!(Date::from_timestamp(1_618_043_400), date);
+    assert_eq!(date.add_days(21).to_string(), "2021-05-01");
+    assert_eq!(date.add_days(-100).to_string(), "2020-12-31");
+    assert_eq!(date.days_since(Date::parse("2020-04-10").unwrap()), 365);
+
+    let leap_day = Date::parse("2020-02-29").unwrap();
+    assert_eq!(
+        Date::from_days_since_epoch(leap_day.days_since_epoch()),
+        leap_day
+    );
+    assert_eq!(Date::from_days_since_epoch(0).to_string(), "1970-01-01");
+
+    assert!(Date::parse("2021-02-29").is_err());
+    assert!(Date::parse("2021-13-01").is_err());
+    assert!(Date::parse("2021-4-10").is_err());
+    assert!(Date::parse("2021-04-10 08:30").is_err());
+    assert!(Date::parse("+021-04-10").is_err());
+    assert!(Date::parse("2021-04-1-").is_err());
+    assert!(Date::parse("2021-0\u{e8}-1").is_err());
+    assert!(Date::parse("").is_err());
+}
+
+#[test]
+fn parse_timestamp_test() {
+    assert_eq!(parse_timestamp("2021-04-10T08:30:00Z"), Ok(1_618_043_400));
+    assert_eq!(
+        parse_timestamp("2021-04-10T08:30:00.123Z"),
+        Ok(1_618_043_400)
+    );
+    assert_eq!(
+        parse_timestamp("2021-04-10T10:30:00+02:00"),
+        Ok(1_618_043_400)
+    );
+    assert_eq!(
+        parse_timestamp("2021-04-10T06:00:00-0230"),
+        Ok(1_618_043_400)
+    );
+    assert_eq!(parse_timestamp("2021-04-10T08:30:00"), Ok(1_618_043_400));
+
+    assert!(parse_timestamp("2021-04-10").is_err());
+    assert!(parse_timestamp("2021-04-10T24:00:00Z").is_err());
+    assert!(parse_timestamp("2021-04-10T08:30:00.Z").is_err());
+    assert!(parse_timestamp("2021-04-10T08:30:00+2").is_err());
+    assert!(parse_timestamp("2021-04-10T08:30:00 UTC").is_err());
+    assert!(parse_timestamp("2021-04-10T08:3\u{e8}0Z").is_err());
+}
@@ -0,0 +1,312 @@
+//! Verification as a state machine, from the decoded certificate to the
+//! verdict, one step at a time.
+//!
+//! Every state is a plain value, whose results so far can be inspected
+//! before going on, so that an application can show them as they come,
+//! or stop to ask the user something and resume later on.
+
+use eudcc_core::{
+    decode_cose_with, issuer_accepted, parse_hcert_with, Certificate,
+    CoseSign1, DecodeError, DecodeOptions, Metrics, Payload,
+};
+
+use crate::date::{Date, InvalidDate};
+use crate::validity::{
+    recovery_window, test_freshness, vaccination_completeness, Completeness,
+    RecoveryPolicy, RecoveryWindow, TestFreshness, TestPolicy,
+    VaccinationPolicy, TEST_RESULT_NOT_DETECTED,
+};
+
+/// A decoded certificate, its signature still to be checked.
+#[derive(Debug, PartialEq)]
+pub struct Decoded {
+    pub cose: CoseSign1,
+    pub payload: Payload,
+    pub certificate: Certificate,
+}
+
+impl Decoded {
+    /// Decode an `HC1:` prefixed certificate, as controlled by `options`.
+    pub fn new(
+        data: &[u8],
+        options: &DecodeOptions,
+    ) -> Result<Decoded, DecodeError> {
+        let cose = decode_cose_with(data, options)?;
+        let payload = parse_hcert_with(&cose.payload, options)?;
+        let certificate = payload.certificate()?;
+
+        Ok(Decoded {
+            cose,
+            payload,
+            certificate,
+        })
+    }
+
+    /// Check the signature with `check`, which gets the COSE structure and
+    /// tells whether its signature is valid: finding the key of the signer
+    /// and the cryptography are left to the application.
+    pub fn check_signature(
+        self,
+        check: impl FnOnce(&CoseSign1) -> bool,
+    ) -> SignatureChecked {
+        let signature_valid = check(&self.cose);
+
+        SignatureChecked {
+            decoded: self,
+            signature_valid,
+        }
+    }
+}
+
+/// Policy parameters for every kind of record, and for the issuers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Policy {
+    pub vaccination: VaccinationPolicy,
+    pub recovery: RecoveryPolicy,
+    pub test: TestPolicy,
+    /// The only issuers accepted, as country codes, or any when `None`.
+    pub accept_issuers: Option<Vec<String>>,
+    /// Issuers rejected, even when accepted by `accept_issuers`.
+    pub reject_issuers: Vec<String>,
+}
+
+impl Policy {
+    /// Whether the certificates of an issuer are accepted, its country
+    /// code compared ignoring case.
+    pub fn accepts(&self, issuer: &str) -> bool {
+        issuer_accepted(
+            issuer,
+            self.accept_issuers.as_deref(),
+            &self.reject_issuers,
+        )
+    }
+}
+
+/// A certificate whose signature was checked, its records still to be
+/// evaluated.
+#[derive(Debug, PartialEq)]
+pub struct SignatureChecked {
+    pub decoded: Decoded,
+    pub signature_valid: bool,
+}
+
+impl SignatureChecked {
+    /// Evaluate the issuer and every record at `now`, a Unix timestamp,
+    /// against `policy`. Only tests that did not detect the disease are
+    /// evaluated.
+    pub fn evaluate_rules(
+        self,
+        policy: &Policy,
+        now: i64,
+    ) -> Result<RulesEvaluated, InvalidDate> {
+        let today = Date::from_timestamp(now);
+        let certificate = &self.decoded.certificate;
+        let vaccinations = certificate
+            .v
+            .iter()
+            .map(|v| vaccination_completeness(v, &policy.vaccination, today))
+            .collect::<Result<_, _>>()?;
+        let recoveries = certificate
+            .r
+            .iter()
+            .map(|r| recovery_window(r, &policy.recovery, today))
+            .collect::<Result<_, _>>()?;
+        let tests = certificate
+            .t
+            .iter()
+            .filter(|t| t.tr == TEST_RESULT_NOT_DETECTED)
+            .map(|t| test_freshness(t, &policy.test, now))
+            .collect::<Result<_, _>>()?;
+
+        let issuer_accepted = policy.accepts(&self.decoded.payload.issuer);
+
+        Ok(RulesEvaluated {
+            checked: self,
+            now,
+            issuer_accepted,
+            vaccinations,
+            recoveries,
+            tests,
+        })
+    }
+}
+
+/// A certificate whose records were evaluated, each kind in the order of
+/// the certificate.
+#[derive(Debug, PartialEq)]
+pub struct RulesEvaluated {
+    pub checked: SignatureChecked,
+    pub now: i64,
+    pub issuer_accepted: bool,
+    pub vaccinations: Vec<Completeness>,
+    pub recoveries: Vec<RecoveryWindow>,
+    pub tests: Vec<TestFreshness>,
+}
+
+impl RulesEvaluated {
+    /// Come to the verdict.
+    pub fn finish(self) -> Final {
+        let payload = &self.checked.decoded.payload;
+        let expired = matches!(
+            i64::try_from(payload.expires_at),
+            Ok(expires_at) if expires_at < self.now
+        );
+        let valid = self.vaccinations.contains(&Completeness::Complete)
+            || self
+                .recoveries
+                .iter()
+                .any(|r| matches!(r, RecoveryWindow::Valid { .. }))
+            || self
+                .tests
+                .iter()
+                .any(|t| matches!(t, TestFreshness::Fresh { .. }));
+        let not_yet_valid = self
+            .vaccinations
+            .iter()
+            .any(|v| matches!(v, Completeness::CompleteAfter(_)))
+            || self
+                .recoveries
+                .iter()
+                .any(|r| matches!(r, RecoveryWindow::NotYetValid { .. }));
+
+        let verdict = if !self.checked.signature_valid {
+            Verdict::Invalid(Reason::BadSignature)
+        } else if !self.issuer_accepted {
+            Verdict::Invalid(Reason::IssuerRejected)
+        } else if expired {
+            Verdict::Invalid(Reason::Expired)
+        } else if valid {
+            Verdict::Valid
+        } else if not_yet_valid {
+            Verdict::NotYetValid
+        } else {
+            Verdict::Invalid(Reason::NoValidRecord)
+        };
+
+        Final {
+            evaluated: self,
+            verdict,
+        }
+    }
+
+    /// Come to the verdict, as [`finish`](Self::finish) does, reporting
+    /// it to `metrics`.
+    pub fn finish_metered(self, metrics: &dyn Metrics) -> Final {
+        let end = self.finish();
+        metrics.verified(end.verdict == Verdict::Valid);
+
+        end
+    }
+}
+
+/// Why a certificate is not valid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Reason {
+    BadSignature,
+    /// The issuer is not accepted by the policy, whatever the records say.
+    IssuerRejected,
+    /// The certificate itself expired, whatever its records say.
+    Expired,
+    NoValidRecord,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Verdict {
+    Valid,
+    /// No record is valid yet, but one will be, like a vaccination whose
+    /// days after the last dose did not pass.
+    NotYetValid,
+    Invalid(Reason),
+}
+
+/// The end of the verification, with every result that led to it.
+#[derive(Debug, PartialEq)]
+pub struct Final {
+    pub evaluated: RulesEvaluated,
+    pub verdict: Verdict,
+}
+
+#[test]
+fn flow_test() {
+    use std::collections::BTreeMap;
+
+    // Taken from:
+    // https://github.com/eu-digital-green-certificates/dgc-testdata/blob/main/IT/2DCode/raw/1.json
+    // It is licensed under Apache-2.0 License.
+    let data = "HC1:6BFOXN%TS3DH0YOJ58S S-W5HDC *M0II5XHC9B5G2+$N IOP-IA%NFQGRJPC%OQHIZC4.OI1RM8ZA.A5:S9MKN4NN3F85QNCY0O%0VZ001HOC9JU0D0HT0HB2PL/IB*09B9LW4T*8+DCMH0LDK2%K:XFE70*LP$V25$0Q:J:4MO1P0%0L0HD+9E/HY+4J6TH48S%4K.GJ2PT3QY:GQ3TE2I+-CPHN6D7LLK*2HG%89UV-0LZ 2ZJJ524-LH/CJTK96L6SR9MU9DHGZ%P WUQRENS431T1XCNCF+47AY0-IFO0500TGPN8F5G.41Q2E4T8ALW.INSV$ 07UV5SR+BNQHNML7 /KD3TU 4V*CAT3ZGLQMI/XI%ZJNSBBXK2:UG%UJMI:TU+MMPZ5$/PMX19UE:-PSR3/$NU44CBE6DQ3D7B0FBOFX0DV2DGMB$YPF62I$60/F$Z2I6IFX21XNI-LM%3/DF/U6Z9FEOJVRLVW6K$UG+BKK57:1+D10%4K83F+1VWD1NE";
+    let policy = Policy {
+        vaccination: VaccinationPolicy {
+            doses: BTreeMap::new(),
+            days_after_last_dose: 14,
+        },
+        recovery: RecoveryPolicy::default(),
+        test: TestPolicy {
+            naat_max_hours: 72,
+            rat_max_hours: 48,
+        },
+        accept_issuers: None,
+        reject_issuers: vec![],
+    };
+    let verdict = |check: fn(&CoseSign1) -> bool, now| {
+        let decoded =
+            Decoded::new(data.as_bytes(), &DecodeOptions::default()).unwrap();
+        let checked = decoded.check_signature(check);
+        let evaluated = checked.evaluate_rules(&policy, now).unwrap();
+        evaluated.finish().verdict
+    };
+
+    // 2021-06-01.
+    let decoded =
+        Decoded::new(data.as_bytes(), &DecodeOptions::default()).unwrap();
+    assert_eq!(decoded.certificate.dob, "1977-06-16");
+    let checked = decoded.check_signature(|cose| !cose.signature.is_empty());
+    assert!(checked.signature_valid);
+    let evaluated = checked.evaluate_rules(&policy, 1_622_505_600).unwrap();
+    assert_eq!(evaluated.vaccinations, [Completeness::Complete]);
+    assert_eq!(evaluated.finish().verdict, Verdict::Valid);
+
+    assert_eq!(
+        verdict(|_| false, 1_622_505_600),
+        Verdict::Invalid(Reason::BadSignature)
+    );
+    assert_eq!(
+        verdict(|_| true, 1_637_148_825),
+        Verdict::Invalid(Reason::Expired)
+    );
+    // 2021-04-20, 10 days after the last dose.
+    assert_eq!(verdict(|_| true, 1_618_876_800), Verdict::NotYetValid);
+
+    let issuers = |accept: Option<&[&str]>, reject: &[&str]| {
+        let policy = Policy {
+            accept_issuers: accept
+                .map(|a| a.iter().map(|i| i.to_string()).collect()),
+            reject_issuers: reject.iter().map(|i| i.to_string()).collect(),
+            ..policy.clone()
+        };
+        let decoded =
+            Decoded::new(data.as_bytes(), &DecodeOptions::default()).unwrap();
+        let checked = decoded.check_signature(|_| true);
+        let evaluated = checked.evaluate_rules(&policy, 1_622_505_600);
+        evaluated.unwrap().finish().verdict
+    };
+    assert_eq!(issuers(Some(&["de", "it"]), &[]), Verdict::Valid);
+    assert_eq!(
+        issuers(Some(&["DE", "FR"]), &[]),
+        Verdict::Invalid(Reason::IssuerRejected)
+    );
+    assert_eq!(
+        issuers(None, &["IT"]),
+        Verdict::Invalid(Reason::IssuerRejected)
+    );
+
+    // Decodings and verdicts can be counted on the way.
+    let counters = eudcc_core::Counters::new();
+    let decoded = eudcc_core::metered(&counters, || {
+        Decoded::new(data.as_bytes(), &DecodeOptions::default())
+    });
+    let checked = decoded.unwrap().check_signature(|_| true);
+    let evaluated = checked.evaluate_rules(&policy, 1_622_505_600).unwrap();
+    let end = evaluated.finish_metered(&counters);
+    assert_eq!(end.verdict, Verdict::Valid);
+    assert_eq!((counters.decoded(), counters.valid()), (1, 1));
+}
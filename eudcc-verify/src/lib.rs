@@ -0,0 +1,10 @@
+//! Evaluation of EU Digital COVID Certificates against policies.
+//!
+//! It builds on the records decoded by `eudcc-core`, and is kept apart so
+//! that embedded users only needing decoding don't pay for it.
+
+#![forbid(unsafe_code)]
+
+pub mod date;
+pub mod flow;
+pub mod validity;
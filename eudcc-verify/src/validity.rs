@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+
+use crate::date::{parse_timestamp, Date, InvalidDate};
+use eudcc_core::{RecoveryRecord, TestRecord, VaccineRecord};
+
+/// Policy parameters for vaccinations.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VaccinationPolicy {
+    /// Doses required for each vaccine medicinal product, by `mp` code.
+    /// Products not listed require the doses of the series in the record.
+    pub doses: BTreeMap<String, i32>,
+    /// Days that must pass after the last dose.
+    pub days_after_last_dose: i64,
+}
+
+/// Whether a vaccination completes the primary series.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Completeness {
+    Complete,
+    /// Enough doses, but not enough days since the last one yet.
+    CompleteAfter(Date),
+    Incomplete,
+}
+
+/// Evaluate, on `today`, whether a vaccination entry is complete.
+pub fn vaccination_completeness(
+    record: &VaccineRecord,
+    policy: &VaccinationPolicy,
+    today: Date,
+) -> Result<Completeness, InvalidDate> {
+    let last_dose = Date::parse(&record.dt)?;
+    let required = match policy.doses.get(&record.mp) {
+        Some(doses) => *doses,
+        None => record.sd,
+    };
+    if record.dn < required {
+        return Ok(Completeness::Incomplete);
+    }
+
+    let complete_from = last_dose.add_days(policy.days_after_last_dose);
+    if today < complete_from {
+        Ok(Completeness::CompleteAfter(complete_from))
+    } else {
+        Ok(Completeness::Complete)
+    }
+}
+
+/// Policy parameters for recoveries.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RecoveryPolicy {
+    /// Maximum days since the first positive test result, on top of the
+    /// validity of the certificate itself.
+    pub max_days_since_positive: Option<i64>,
+}
+
+/// Whether a recovery is within its validity window, with the dates
+/// controlling it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecoveryWindow {
+    NotYetValid { valid_from: Date },
+    Valid { valid_from: Date, valid_until: Date },
+    Expired { valid_until: Date },
+}
+
+/// Evaluate, on `today`, whether a recovery entry is within the window
+/// from `df` to `du`, capped by the policy.
+pub fn recovery_window(
+    record: &RecoveryRecord,
+    policy: &RecoveryPolicy,
+    today: Date,
+) -> Result<RecoveryWindow, InvalidDate> {
+    let first_positive = Date::parse(&record.fr)?;
+    let valid_from = Date::parse(&record.df)?;
+    let mut valid_until = Date::parse(&record.du)?;
+    if let Some(days) = policy.max_days_since_positive {
+        valid_until = valid_until.min(first_positive.add_days(days));
+    }
+
+    if today < valid_from {
+        Ok(RecoveryWindow::NotYetValid { valid_from })
+    } else if today > valid_until {
+        Ok(RecoveryWindow::Expired { valid_until })
+    } else {
+        Ok(RecoveryWindow::Valid {
+            valid_from,
+            valid_until,
+        })
+    }
+}
+
+/// Type of test code of nucleic acid amplification tests, like PCR.
+pub const TEST_TYPE_NAAT: &str = "LP6464-4";
+/// Type of test code of rapid antigen tests.
+pub const TEST_TYPE_RAT: &str = "LP217198-3";
+/// Test result code of tests that did not detect the disease.
+pub const TEST_RESULT_NOT_DETECTED: &str = "260415000";
+const SECONDS_PER_HOUR: i64 = 60 * 60;
+
+/// Policy parameters for tests: how many hours after the sample collection
+/// a negative result is accepted, for each type of test.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestPolicy {
+    pub naat_max_hours: i64,
+    pub rat_max_hours: i64,
+}
+
+/// Whether a test is recent enough to be accepted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TestFreshness {
+    Fresh {
+        age_hours: i64,
+        max_hours: i64,
+    },
+    Stale {
+        age_hours: i64,
+        max_hours: i64,
+    },
+    /// The sample collection is later than now.
+    InFuture,
+    /// The policy has no limit for the type of test.
+    UnknownType,
+}
+
+/// Evaluate, at `now`, a Unix timestamp, the age of a test entry from its
+/// sample collection, against the limits of the policy.
+pub fn test_freshness(
+    record: &TestRecord,
+    policy: &TestPolicy,
+    now: i64,
+) -> Result<TestFreshness, InvalidDate> {
+    let age = now - parse_timestamp(&record.sc)?;
+    let max_hours = match record.tt.as_str() {
+        TEST_TYPE_NAAT => policy.naat_max_hours,
+        TEST_TYPE_RAT => policy.rat_max_hours,
+        _ => return Ok(TestFreshness::UnknownType),
+    };
+
+    let age_hours = age / SECONDS_PER_HOUR;
+    if age < 0 {
+        Ok(TestFreshness::InFuture)
+    } else if age <= max_hours * SECONDS_PER_HOUR {
+        Ok(TestFreshness::Fresh {
+            age_hours,
+            max_hours,
+        })
+    } else {
+        Ok(TestFreshness::Stale {
+            age_hours,
+            max_hours,
+        })
+    }
+}
+
+#[test]
+fn vaccination_completeness_test() {
+    let record = VaccineRecord {
+        tg: "840539006".to_string(),
+        vp: "1119349007".to_string(),
+        mp: "EU/1/20/1528".to_string(),
+        ma: "ORG-100030215".to_string(),
+        dn: 1,
+        sd: 2,
+        dt: "2021-04-10".to_string(),
+        co: "IT".to_string(),
+        is: "IT".to_string(),
+        ci: "01ITE7300E1AB2A84C719004F103DCB1F70A#6".to_string(),
+    };
+    let mut policy = VaccinationPolicy {
+        doses: BTreeMap::new(),
+        days_after_last_dose: 14,
+    };
+    let date = |s| Date::parse(s).unwrap();
+
+    let completeness =
+        vaccination_completeness(&record, &policy, date("2021-06-01"));
+    assert_eq!(completeness, Ok(Completeness::Incomplete));
+
+    policy.doses.insert("EU/1/20/1528".to_string(), 1);
+    let completeness =
+        vaccination_completeness(&record, &policy, date("2021-04-20"));
+    assert_eq!(
+        completeness,
+        Ok(Completeness::CompleteAfter(date("2021-04-24")))
+    );
+    let completeness =
+        vaccination_completeness(&record, &policy, date("2021-04-24"));
+    assert_eq!(completeness, Ok(Completeness::Complete));
+
+    let record = VaccineRecord {
+        dt: "2021-04".to_string(),
+        ..record
+    };
+    assert!(
+        vaccination_completeness(&record, &policy, date("2021-04-24")).is_err()
+    );
+}
+
+#[test]
+fn recovery_window_test() {
+    let record = RecoveryRecord {
+        tg: "840539006".to_string(),
+        fr: "2021-05-02".to_string(),
+        co: "IT".to_string(),
+        is: "IT".to_string(),
+        df: "2021-05-04".to_string(),
+        du: "2021-10-31".to_string(),
+        ci: "01ITA65E2BD36C9E4900B0273D2E7C92EEB9#1".to_string(),
+    };
+    let mut policy = RecoveryPolicy::default();
+    let date = |s| Date::parse(s).unwrap();
+
+    let window = recovery_window(&record, &policy, date("2021-05-03"));
+    assert_eq!(
+        window,
+        Ok(RecoveryWindow::NotYetValid {
+            valid_from: date("2021-05-04")
+        })
+    );
+    let window = recovery_window(&record, &policy, date("2021-10-31"));
+    assert_eq!(
+        window,
+        Ok(RecoveryWindow::Valid {
+            valid_from: date("2021-05-04"),
+            valid_until: date("2021-10-31"),
+        })
+    );
+
+    policy.max_days_since_positive = Some(120);
+    let window = recovery_window(&record, &policy, date("2021-09-01"));
+    assert_eq!(
+        window,
+        Ok(RecoveryWindow::Expired {
+            valid_until: date("2021-08-30")
+        })
+    );
+}
+
+#[test]
+fn test_freshness_test() {
+    let record = TestRecord {
+        tg: "840539006".to_string(),
+        tt: TEST_TYPE_RAT.to_string(),
+        nm: "".to_string(),
+        ma: "1232".to_string(),
+        sc: "2021-05-03T10:27:15Z".to_string(),
+        dr: "".to_string(),
+        tr: TEST_RESULT_NOT_DETECTED.to_string(),
+        tc: "Policlinico Umberto I".to_string(),
+        co: "IT".to_string(),
+        is: "IT".to_string(),
+        ci: "01IT053059F7676042D9BEE9F874C4901F9B#3".to_string(),
+    };
+    let policy = TestPolicy {
+        naat_max_hours: 72,
+        rat_max_hours: 48,
+    };
+    let collected = parse_timestamp(&record.sc).unwrap();
+
+    let freshness = test_freshness(&record, &policy, collected + 5 * 3600);
+    assert_eq!(
+        freshness,
+        Ok(TestFreshness::Fresh {
+            age_hours: 5,
+            max_hours: 48
+        })
+    );
+    let freshness = test_freshness(&record, &policy, collected + 60 * 3600);
+    assert_eq!(
+        freshness,
+        Ok(TestFreshness::Stale {
+            age_hours: 60,
+            max_hours: 48
+        })
+    );
+    let freshness = test_freshness(&record, &policy, collected - 1);
+    assert_eq!(freshness, Ok(TestFreshness::InFuture));
+
+    let naat = TestRecord {
+        tt: TEST_TYPE_NAAT.to_string(),
+        ..record.clone()
+    };
+    let freshness = test_freshness(&naat, &policy, collected + 60 * 3600);
+    assert_eq!(
+        freshness,
+        Ok(TestFreshness::Fresh {
+            age_hours: 60,
+            max_hours: 72
+        })
+    );
+    let unknown = TestRecord {
+        tt: "LP0000-0".to_string(),
+        ..record
+    };
+    let freshness = test_freshness(&unknown, &policy, collected);
+    assert_eq!(freshness, Ok(TestFreshness::UnknownType));
+}